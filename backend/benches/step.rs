@@ -0,0 +1,42 @@
+use cellular_automata_backend::world::{
+    step_totalistic, step_totalistic_parallel, Coord, Soup, Symmetry, World,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const B3: u16 = 1 << 3;
+const S23: u16 = 1 << 2 | 1 << 3;
+
+/// случайный суп плотностью 0.3 в квадрате со стороной `2 * half_size`
+fn soup(half_size: i32) -> World {
+    let side = 2 * half_size as u32;
+    let soup = Soup::new(
+        Coord::new(-half_size, -half_size),
+        side,
+        side,
+        0.3,
+        Symmetry::C1,
+    )
+    .expect("bench soup fits into the soup size limit");
+    let mut world = World::new();
+    for cell in soup.cells(0x2545_f491_4f6c_dd1d) {
+        world.set_cell(cell, true);
+    }
+    world
+}
+
+fn bench_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("life_step");
+    for half_size in [128, 512, 1024] {
+        let world = soup(half_size);
+        group.bench_with_input(BenchmarkId::new("serial", half_size), &world, |b, w| {
+            b.iter(|| step_totalistic(w, B3, S23))
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", half_size), &world, |b, w| {
+            b.iter(|| step_totalistic_parallel(w, B3, S23))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_step);
+criterion_main!(benches);
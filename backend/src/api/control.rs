@@ -4,11 +4,14 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
 use crate::{
+    automaton::{parse_hashlife_rule, parse_rule, Rule, RuleTable},
     errors::{ApiError, ApiResult},
     simulation::{Simulation, SimulationCommand, SimulationState},
+    world::random_seed,
 };
 
 #[derive(Clone)]
@@ -40,6 +43,13 @@ pub struct StatusResponse {
     pub tick_count: u64,
     pub tps: u32,
     pub active_cells: usize,
+    pub rule: String,
+    /// число состояний клетки в текущем правиле, включая мёртвое
+    pub state_count: u16,
+    /// зерно генератора вероятностных правил; с ним прогон повторяется
+    pub seed: u64,
+    /// обратимо ли правило, то есть доступен ли шаг назад
+    pub reversible: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +57,48 @@ pub struct SetSpeedRequest {
     pub tps: u32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetRuleRequest {
+    pub rule: String,
+    /// `hashlife` для Life-like правил, по умолчанию обычный движок
+    #[serde(default)]
+    pub engine: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoadRuleTableRequest {
+    /// содержимое файла Golly `.rule`
+    pub table: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleTableResponse {
+    pub rule: String,
+    pub description: String,
+    pub state_count: u16,
+    pub transitions: usize,
+    /// секции файла кроме `@RULE` и `@TABLE`, например `COLORS` и `ICONS`
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSeedRequest {
+    /// без зерна выбирается новое случайное
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JumpRequest {
+    pub generations: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleResponse {
+    pub rule: String,
+    pub state_count: u16,
+}
+
 pub async fn start_simulation(
     State(state): State<AppState>,
 ) -> ApiResult<(StatusCode, Json<StatusResponse>)> {
@@ -115,6 +167,63 @@ pub async fn step_simulation(
     Ok((StatusCode::OK, Json(get_status_response(&state))))
 }
 
+pub async fn jump_simulation(
+    State(state): State<AppState>,
+    Json(payload): Json<JumpRequest>,
+) -> ApiResult<(StatusCode, Json<StatusResponse>)> {
+    if payload.generations == 0 {
+        return Err(ApiError::InvalidRequest(
+            "Jump needs at least one generation".to_string(),
+        ));
+    }
+    let rule = state.simulation.get_rule();
+    let max_jump = rule.max_jump(&state.simulation.get_world());
+    if payload.generations > max_jump {
+        return Err(ApiError::InvalidRequest(format!(
+            "{} jumps at most {} generations at once here; \
+             HashLife on an unbounded world jumps further",
+            rule.name(),
+            max_jump
+        )));
+    }
+
+    state.send_command(SimulationCommand::Jump(payload.generations))?;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    Ok((StatusCode::OK, Json(get_status_response(&state))))
+}
+
+/// шаг назад; есть только у обратимых правил второго порядка
+pub async fn step_back_simulation(
+    State(state): State<AppState>,
+) -> ApiResult<(StatusCode, Json<StatusResponse>)> {
+    let rule = state.simulation.get_rule();
+    if rule.second_order().is_none() {
+        return Err(ApiError::InvalidRule(format!(
+            "{} is not reversible; use a second-order rule like 2nd/B3/S23",
+            rule.name()
+        )));
+    }
+    if state.simulation.get_tick_count() == 0 {
+        return Err(ApiError::InvalidRequest(
+            "Already at generation 0".to_string(),
+        ));
+    }
+    if state.simulation.get_previous_world().is_none() {
+        return Err(ApiError::InvalidRequest(
+            "The previous generation is unknown after editing the world; step forward first"
+                .to_string(),
+        ));
+    }
+
+    state.send_command(SimulationCommand::StepBack)?;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    Ok((StatusCode::OK, Json(get_status_response(&state))))
+}
+
 pub async fn set_speed(
     State(state): State<AppState>,
     Json(payload): Json<SetSpeedRequest>,
@@ -130,6 +239,71 @@ pub async fn set_speed(
     Ok((StatusCode::OK, Json(get_status_response(&state))))
 }
 
+pub async fn set_rule(
+    State(state): State<AppState>,
+    Json(payload): Json<SetRuleRequest>,
+) -> ApiResult<(StatusCode, Json<StatusResponse>)> {
+    let rule = match payload.engine.as_deref() {
+        None | Some("default") => parse_rule(&payload.rule),
+        Some("hashlife") => parse_hashlife_rule(&payload.rule),
+        Some(other) => {
+            return Err(ApiError::InvalidRequest(format!("Unknown engine: {}", other)));
+        }
+    }
+    .map_err(|e| ApiError::InvalidRule(e.to_string()))?;
+    rule.check_topology(state.simulation.get_world().topology())
+        .map_err(ApiError::InvalidTopology)?;
+
+    state.send_command(SimulationCommand::SetRule(rule))?;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    Ok((StatusCode::OK, Json(get_status_response(&state))))
+}
+
+pub async fn set_seed(
+    State(state): State<AppState>,
+    Json(payload): Json<SetSeedRequest>,
+) -> ApiResult<(StatusCode, Json<StatusResponse>)> {
+    let seed = payload.seed.unwrap_or_else(random_seed);
+    state.send_command(SimulationCommand::SetSeed(seed))?;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    Ok((StatusCode::OK, Json(get_status_response(&state))))
+}
+
+pub async fn load_rule_table(
+    State(state): State<AppState>,
+    Json(payload): Json<LoadRuleTableRequest>,
+) -> ApiResult<(StatusCode, Json<RuleTableResponse>)> {
+    let table = RuleTable::parse(&payload.table).map_err(|e| ApiError::InvalidRule(e.to_string()))?;
+    table
+        .check_topology(state.simulation.get_world().topology())
+        .map_err(ApiError::InvalidTopology)?;
+
+    let response = RuleTableResponse {
+        rule: table.name().to_string(),
+        description: table.description().to_string(),
+        state_count: table.state_count(),
+        transitions: table.transition_count(),
+        metadata: table.metadata().clone(),
+    };
+    state.send_command(SimulationCommand::SetRule(Arc::new(table)))?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn get_rule(
+    State(state): State<AppState>,
+) -> Json<RuleResponse> {
+    let rule = state.simulation.get_rule();
+    Json(RuleResponse {
+        rule: rule.name().to_string(),
+        state_count: rule.state_count(),
+    })
+}
+
 pub async fn get_status(
     State(state): State<AppState>,
 ) -> Json<StatusResponse> {
@@ -149,5 +323,9 @@ fn get_status_response(state: &AppState) -> StatusResponse {
         tick_count: state.simulation.get_tick_count(),
         tps: state.simulation.get_tps(),
         active_cells: world.active_cell_count(),
+        rule: state.simulation.get_rule_name(),
+        state_count: state.simulation.get_rule().state_count(),
+        seed: state.simulation.get_seed(),
+        reversible: state.simulation.get_rule().second_order().is_some(),
     }
 }
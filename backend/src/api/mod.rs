@@ -1,4 +1,5 @@
 pub mod control;
 pub mod world;
+pub mod ws;
 
 pub use control::AppState;
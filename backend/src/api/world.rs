@@ -1,15 +1,19 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
+    response::IntoResponse,
     Json,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
     api::control::AppState,
+    automaton::{parse_rule, Rule},
     errors::{ApiError, ApiResult},
+    pattern::{self, PatternFormat},
     presets::Preset,
-    world::Coord,
+    simulation::WorldDiff,
+    world::{random_seed, Ant, Coord, Heading, Soup, Symmetry, Topology},
 };
 
 #[derive(Debug, Deserialize)]
@@ -30,6 +34,47 @@ pub struct RegionResponse {
 pub struct CellData {
     pub x: i32,
     pub y: i32,
+    /// 0 - мёртвая клетка, 1 - живая в Life-like правилах
+    pub state: u8,
+}
+
+impl CellData {
+    pub fn new(coord: Coord, state: u8) -> Self {
+        Self {
+            x: coord.x,
+            y: coord.y,
+            state,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AntData {
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub heading: Heading,
+}
+
+impl From<&Ant> for AntData {
+    fn from(ant: &Ant) -> Self {
+        Self {
+            x: ant.position.x,
+            y: ant.position.y,
+            heading: ant.heading,
+        }
+    }
+}
+
+impl AntData {
+    pub fn from_ants(ants: &[Ant]) -> Vec<Self> {
+        ants.iter().map(Self::from).collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AntsResponse {
+    pub ants: Vec<AntData>,
 }
 
 #[derive(Debug, Serialize)]
@@ -40,11 +85,46 @@ pub struct BoundsData {
     pub y2: i32,
 }
 
+/// наибольшая площадь прямоугольника, который ответ обходит клетка за клеткой
+const MAX_REGION_AREA: u64 = 1 << 22;
+
+#[derive(Debug, Serialize)]
+pub struct FieldRegionResponse {
+    pub bounds: BoundsData,
+    pub width: u32,
+    pub height: u32,
+    /// значения поля, квантованные в `0..=255`, по строкам сверху вниз
+    pub values: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    pub since: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffResponse {
+    pub tick: u64,
+    /// `true`, если изменения не восстановить и `cells` содержит весь мир
+    pub full_snapshot: bool,
+    pub cells: Vec<CellData>,
+    pub born: Vec<CellData>,
+    pub died: Vec<CellData>,
+    pub changed: Vec<CellData>,
+    /// позиции муравьёв, если они изменились (в снимке - всегда)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ants: Option<Vec<AntData>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetCellRequest {
     pub x: i32,
     pub y: i32,
+    #[serde(default)]
     pub alive: bool,
+    /// если задано, перекрывает `alive`
+    #[serde(default)]
+    pub state: Option<u8>,
 }
 
 #[derive(Debug, Serialize)]
@@ -52,6 +132,7 @@ pub struct SetCellResponse {
     pub x: i32,
     pub y: i32,
     pub alive: bool,
+    pub state: u8,
     pub active_cells: usize,
 }
 
@@ -62,6 +143,9 @@ pub struct LoadPresetRequest {
     pub offset_x: i32,
     #[serde(default)]
     pub offset_y: i32,
+    /// перекрывает топологию шаблона
+    #[serde(default)]
+    pub topology: Option<Topology>,
 }
 
 #[derive(Debug, Serialize)]
@@ -74,12 +158,87 @@ pub struct PresetInfo {
     pub name: String,
     pub description: String,
     pub cell_count: usize,
+    pub topology: Option<Topology>,
+    pub rule: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct LoadPresetResponse {
     pub preset_name: String,
     pub active_cells: usize,
+    pub topology: Topology,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TopologyPayload {
+    pub topology: Topology,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetTopologyResponse {
+    pub topology: Topology,
+    pub active_cells: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RandomSoupRequest {
+    /// левый верхний угол прямоугольника
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// доля живых клеток от 0 до 1
+    pub density: f64,
+    /// без зерна выбирается новое случайное
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub symmetry: Symmetry,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RandomSoupResponse {
+    /// зерно, с которым суп можно получить снова
+    pub seed: u64,
+    pub symmetry: Symmetry,
+    /// живые клетки внутри прямоугольника
+    pub soup_cells: usize,
+    pub active_cells: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportRequest {
+    pub pattern: String,
+    /// `rle`, `cells` или `life106`; по умолчанию определяется по содержимому
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub offset_x: i32,
+    #[serde(default)]
+    pub offset_y: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub name: String,
+    pub description: String,
+    pub rule: Option<String>,
+    pub format: String,
+    pub active_cells: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default = "default_export_format")]
+    pub format: String,
+    pub x1: Option<i32>,
+    pub y1: Option<i32>,
+    pub x2: Option<i32>,
+    pub y2: Option<i32>,
+}
+
+fn default_export_format() -> String {
+    "rle".to_string()
 }
 
 pub async fn get_region(
@@ -93,8 +252,9 @@ pub async fn get_region(
     for x in query.x1..=query.x2 {
         for y in query.y1..=query.y2 {
             let coord = Coord::new(x, y);
-            if world.get_cell(coord) {
-                cells.push(CellData { x, y });
+            let cell_state = world.get_state(coord);
+            if cell_state != 0 {
+                cells.push(CellData::new(coord, cell_state));
             }
         }
     }
@@ -110,30 +270,141 @@ pub async fn get_region(
     })
 }
 
+/// значения непрерывного поля в прямоугольнике; края заворачиваются как у тора
+pub async fn get_field_region(
+    State(state): State<AppState>,
+    Query(query): Query<GetRegionQuery>,
+) -> ApiResult<(StatusCode, Json<FieldRegionResponse>)> {
+    let field = state.simulation.get_field().ok_or_else(|| {
+        ApiError::InvalidRequest("The field needs a finite world topology".to_string())
+    })?;
+
+    let min = Coord::new(query.x1.min(query.x2), query.y1.min(query.y2));
+    let max = Coord::new(query.x1.max(query.x2), query.y1.max(query.y2));
+    let (width, height) = region_size("Field", min, max)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(FieldRegionResponse {
+            bounds: BoundsData {
+                x1: min.x,
+                y1: min.y,
+                x2: max.x,
+                y2: max.y,
+            },
+            width,
+            height,
+            values: field.region(min, max),
+        }),
+    ))
+}
+
+/// не выносит ли сдвиг `offset` клетки или муравьёв шаблона за пределы координат
+fn check_offset(preset: &Preset, offset: Coord) -> ApiResult<()> {
+    let fits =
+        |x: i32, y: i32| offset.x.checked_add(x).is_some() && offset.y.checked_add(y).is_some();
+    let in_range = preset.cells.iter().all(|&(x, y)| fits(x, y))
+        && preset.ants.iter().all(|&(x, y, _)| fits(x, y));
+    if !in_range {
+        return Err(ApiError::InvalidRequest(
+            "Offset moves the pattern outside of the coordinate range".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// подходит ли правило к форме мира; `None` - текущее правило или текущая форма
+fn check_rule_topology(
+    state: &AppState,
+    rule: Option<&dyn Rule>,
+    topology: Option<Topology>,
+) -> ApiResult<()> {
+    let topology = topology.unwrap_or_else(|| state.simulation.get_world().topology());
+    match rule {
+        Some(rule) => rule.check_topology(topology),
+        None => state.simulation.get_rule().check_topology(topology),
+    }
+    .map_err(ApiError::InvalidTopology)
+}
+
+/// стороны прямоугольника `min..=max`, если его площадь не больше `MAX_REGION_AREA`
+fn region_size(kind: &str, min: Coord, max: Coord) -> ApiResult<(u32, u32)> {
+    let width = (i64::from(max.x) - i64::from(min.x) + 1) as u64;
+    let height = (i64::from(max.y) - i64::from(min.y) + 1) as u64;
+    if width.saturating_mul(height) > MAX_REGION_AREA {
+        return Err(ApiError::InvalidRequest(format!(
+            "{} region {}x{} is larger than {} cells",
+            kind, width, height, MAX_REGION_AREA
+        )));
+    }
+    Ok((width as u32, height as u32))
+}
+
 pub async fn get_all_cells(
     State(state): State<AppState>,
 ) -> Json<Vec<CellData>> {
     let world = state.simulation.get_world();
 
     let cells: Vec<CellData> = world
-        .iter_active_cells()
-        .map(|coord| CellData {
-            x: coord.x,
-            y: coord.y,
-        })
+        .iter_cell_states()
+        .map(|(coord, state)| CellData::new(coord, state))
         .collect();
 
     Json(cells)
 }
 
+pub async fn get_diff(
+    State(state): State<AppState>,
+    Query(query): Query<DiffQuery>,
+) -> Json<DiffResponse> {
+    let to_cells = |cells: &[(Coord, u8)]| -> Vec<CellData> {
+        cells
+            .iter()
+            .map(|&(coord, state)| CellData::new(coord, state))
+            .collect()
+    };
+
+    let response = match state.simulation.diff_since(query.since) {
+        WorldDiff::Delta { tick, delta } => DiffResponse {
+            tick,
+            full_snapshot: false,
+            cells: Vec::new(),
+            born: to_cells(&delta.born),
+            died: delta.died.iter().map(|&coord| CellData::new(coord, 0)).collect(),
+            changed: to_cells(&delta.changed),
+            ants: delta.ants.as_deref().map(AntData::from_ants),
+        },
+        WorldDiff::Snapshot { tick, world } => DiffResponse {
+            tick,
+            full_snapshot: true,
+            cells: to_cells(&world.iter_cell_states().collect::<Vec<_>>()),
+            born: Vec::new(),
+            died: Vec::new(),
+            changed: Vec::new(),
+            ants: Some(AntData::from_ants(world.ants())),
+        },
+    };
+
+    Json(response)
+}
+
 pub async fn set_cell(
     State(state): State<AppState>,
     Json(payload): Json<SetCellRequest>,
 ) -> ApiResult<(StatusCode, Json<SetCellResponse>)> {
-    let mut world = state.simulation.get_world();
     let coord = Coord::new(payload.x, payload.y);
+    let cell_state = payload.state.unwrap_or(payload.alive as u8);
+    let state_count = state.simulation.get_rule().state_count();
+    if u16::from(cell_state) >= state_count {
+        return Err(ApiError::InvalidRequest(format!(
+            "State {} is out of range: the rule has {} states",
+            cell_state, state_count
+        )));
+    }
 
-    world.set_cell(coord, payload.alive);
+    let mut world = state.simulation.get_world();
+
+    world.set_state(coord, cell_state);
     state.simulation.set_world(world.clone());
 
     Ok((
@@ -141,12 +412,41 @@ pub async fn set_cell(
         Json(SetCellResponse {
             x: payload.x,
             y: payload.y,
-            alive: payload.alive,
+            alive: cell_state != 0,
+            state: cell_state,
             active_cells: world.active_cell_count(),
         }),
     ))
 }
 
+pub async fn get_ants(State(state): State<AppState>) -> Json<AntsResponse> {
+    Json(AntsResponse {
+        ants: AntData::from_ants(state.simulation.get_world().ants()),
+    })
+}
+
+pub async fn add_ant(
+    State(state): State<AppState>,
+    Json(payload): Json<AntData>,
+) -> ApiResult<(StatusCode, Json<AntsResponse>)> {
+    let mut world = state.simulation.get_world();
+    let ant = Ant::new(Coord::new(payload.x, payload.y), payload.heading);
+    if !world.add_ant(ant) {
+        return Err(ApiError::InvalidRequest(format!(
+            "Cell ({}, {}) is outside the world",
+            payload.x, payload.y
+        )));
+    }
+    state.simulation.set_world(world.clone());
+
+    Ok((
+        StatusCode::OK,
+        Json(AntsResponse {
+            ants: AntData::from_ants(world.ants()),
+        }),
+    ))
+}
+
 pub async fn get_presets() -> Json<PresetsListResponse> {
     let presets = Preset::all();
 
@@ -156,6 +456,8 @@ pub async fn get_presets() -> Json<PresetsListResponse> {
             name: p.name.clone(),
             description: p.description.clone(),
             cell_count: p.cells.len(),
+            topology: p.topology,
+            rule: p.rule.clone(),
         })
         .collect();
 
@@ -168,20 +470,66 @@ pub async fn load_preset(
     State(state): State<AppState>,
     Json(payload): Json<LoadPresetRequest>,
 ) -> ApiResult<(StatusCode, Json<LoadPresetResponse>)> {
-    let preset = Preset::find(&payload.name)
+    let mut preset = Preset::find(&payload.name)
         .ok_or_else(|| ApiError::PresetNotFound(payload.name.clone()))?;
 
-    let mut world = state.simulation.get_world();
+    if let Some(topology) = payload.topology {
+        topology
+            .validate()
+            .map_err(|e| ApiError::InvalidTopology(e.to_string()))?;
+        preset.topology = Some(topology);
+    }
+
+    let rule = match &preset.rule {
+        Some(rulestring) => {
+            Some(parse_rule(rulestring).map_err(|e| ApiError::InvalidRule(e.to_string()))?)
+        }
+        None => None,
+    };
+    check_rule_topology(&state, rule.as_deref(), preset.topology)?;
+
     let offset = Coord::new(payload.offset_x, payload.offset_y);
+    check_offset(&preset, offset)?;
 
+    let mut world = state.simulation.get_world();
     preset.load_into(&mut world, offset);
-    state.simulation.set_world(world.clone());
+    state.simulation.load_world(world.clone(), rule);
 
     Ok((
         StatusCode::OK,
         Json(LoadPresetResponse {
             preset_name: preset.name,
             active_cells: world.active_cell_count(),
+            topology: world.topology(),
+        }),
+    ))
+}
+
+pub async fn get_topology(State(state): State<AppState>) -> Json<TopologyPayload> {
+    Json(TopologyPayload {
+        topology: state.simulation.get_world().topology(),
+    })
+}
+
+pub async fn set_topology(
+    State(state): State<AppState>,
+    Json(payload): Json<TopologyPayload>,
+) -> ApiResult<(StatusCode, Json<SetTopologyResponse>)> {
+    payload
+        .topology
+        .validate()
+        .map_err(|e| ApiError::InvalidTopology(e.to_string()))?;
+    check_rule_topology(&state, None, Some(payload.topology))?;
+
+    let mut world = state.simulation.get_world();
+    world.set_topology(payload.topology);
+    state.simulation.set_world(world.clone());
+
+    Ok((
+        StatusCode::OK,
+        Json(SetTopologyResponse {
+            topology: world.topology(),
+            active_cells: world.active_cell_count(),
         }),
     ))
 }
@@ -199,7 +547,132 @@ pub async fn clear_world(
             x: 0,
             y: 0,
             alive: false,
+            state: 0,
             active_cells: 0,
         }),
     ))
 }
+
+/// заполняет прямоугольник случайным супом; прежние клетки в нём стираются
+pub async fn random_soup(
+    State(state): State<AppState>,
+    Json(payload): Json<RandomSoupRequest>,
+) -> ApiResult<(StatusCode, Json<RandomSoupResponse>)> {
+    let soup = Soup::new(
+        Coord::new(payload.x, payload.y),
+        payload.width,
+        payload.height,
+        payload.density,
+        payload.symmetry,
+    )
+    .map_err(|e| ApiError::InvalidSoup(e.to_string()))?;
+    let seed = payload.seed.unwrap_or_else(random_seed);
+
+    let mut world = state.simulation.get_world();
+    for coord in soup.area() {
+        world.set_state(coord, 0);
+    }
+    let cells = soup.cells(seed);
+    for &coord in &cells {
+        world.set_cell(coord, true);
+    }
+    state.simulation.set_world(world.clone());
+
+    Ok((
+        StatusCode::OK,
+        Json(RandomSoupResponse {
+            seed,
+            symmetry: payload.symmetry,
+            soup_cells: cells.len(),
+            active_cells: world.active_cell_count(),
+        }),
+    ))
+}
+
+pub async fn import_pattern(
+    State(state): State<AppState>,
+    Json(payload): Json<ImportRequest>,
+) -> ApiResult<(StatusCode, Json<ImportResponse>)> {
+    let format = match &payload.format {
+        Some(format) => format.parse::<PatternFormat>(),
+        None => PatternFormat::detect(&payload.pattern),
+    }
+    .map_err(|e| ApiError::InvalidPattern(e.to_string()))?;
+
+    let preset = pattern::parse_as(format, &payload.pattern)
+        .map_err(|e| ApiError::InvalidPattern(e.to_string()))?;
+
+    let rule = match &preset.rule {
+        Some(rulestring) => {
+            Some(parse_rule(rulestring).map_err(|e| ApiError::InvalidRule(e.to_string()))?)
+        }
+        None => None,
+    };
+    let state_count = match &rule {
+        Some(rule) => rule.state_count(),
+        None => state.simulation.get_rule().state_count(),
+    };
+    if let Some(((x, y), cell_state)) = preset
+        .cell_states()
+        .find(|&(_, cell_state)| u16::from(cell_state) >= state_count)
+    {
+        return Err(ApiError::InvalidPattern(format!(
+            "cell ({}, {}) has state {}, but the rule has {} states",
+            x, y, cell_state, state_count
+        )));
+    }
+
+    check_rule_topology(&state, rule.as_deref(), preset.topology)?;
+    let offset = Coord::new(payload.offset_x, payload.offset_y);
+    check_offset(&preset, offset)?;
+
+    let mut world = state.simulation.get_world();
+    preset.load_into(&mut world, offset);
+    state.simulation.load_world(world.clone(), rule);
+
+    Ok((
+        StatusCode::OK,
+        Json(ImportResponse {
+            name: preset.name,
+            description: preset.description,
+            rule: preset.rule,
+            format: format.to_string(),
+            active_cells: world.active_cell_count(),
+        }),
+    ))
+}
+
+pub async fn export_pattern(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let format = query
+        .format
+        .parse::<PatternFormat>()
+        .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+
+    let region = match (query.x1, query.y1, query.x2, query.y2) {
+        (Some(x1), Some(y1), Some(x2), Some(y2)) => Some((
+            Coord::new(x1.min(x2), y1.min(y2)),
+            Coord::new(x1.max(x2), y1.max(y2)),
+        )),
+        (None, None, None, None) => None,
+        _ => {
+            return Err(ApiError::InvalidRequest(
+                "Export region needs all of x1, y1, x2, y2".to_string(),
+            ))
+        }
+    };
+
+    let world = state.simulation.get_world();
+    // Life 1.06 перечисляет живые клетки, остальные форматы обходят весь прямоугольник
+    if format != PatternFormat::Life106 {
+        if let Some((min, max)) = region.or_else(|| world.get_bounds()) {
+            region_size("Export", min, max)?;
+        }
+    }
+    let rule = state.simulation.get_rule();
+    let body = pattern::write(format, &world, region, rule.rulestring());
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body))
+}
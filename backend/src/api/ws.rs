@@ -0,0 +1,158 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+use crate::{
+    api::{
+        control::AppState,
+        world::{AntData, CellData},
+    },
+    world::{Coord, World},
+};
+
+/// прямоугольник, на который подписан клиент
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Viewport {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Viewport {
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.x >= self.x1 && coord.x <= self.x2 && coord.y >= self.y1 && coord.y <= self.y2
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe(Viewport),
+    Unsubscribe,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Snapshot {
+        tick: u64,
+        cells: Vec<CellData>,
+        ants: Vec<AntData>,
+    },
+    Delta {
+        tick: u64,
+        born: Vec<CellData>,
+        died: Vec<CellData>,
+        changed: Vec<CellData>,
+        /// все муравьи, если кто-то из них сдвинулся
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ants: Option<Vec<AntData>>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: AppState) {
+    let mut viewport: Option<Viewport> = None;
+
+    let (world, tick, mut updates) = state.simulation.subscribe();
+    if send(&mut socket, &snapshot_message(&world, tick, viewport)).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => continue,
+                };
+
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(msg) => {
+                        viewport = match msg {
+                            ClientMessage::Subscribe(rect) => Some(rect),
+                            ClientMessage::Unsubscribe => None,
+                        };
+                        // переподписываемся, чтобы снимок и поток дельт остались согласованы
+                        let (world, tick, rx) = state.simulation.subscribe();
+                        updates = rx;
+                        snapshot_message(&world, tick, viewport)
+                    }
+                    Err(e) => ServerMessage::Error {
+                        message: format!("Invalid message: {}", e),
+                    },
+                };
+
+                if send(&mut socket, &reply).await.is_err() {
+                    break;
+                }
+            }
+            update = updates.recv() => {
+                let msg = match update {
+                    Ok(update) => {
+                        let delta = &update.delta;
+                        let born = filter_cells(&delta.born, viewport);
+                        let died: Vec<(Coord, u8)> =
+                            delta.died.iter().map(|&coord| (coord, 0)).collect();
+                        let died = filter_cells(&died, viewport);
+                        let changed = filter_cells(&delta.changed, viewport);
+                        let ants = delta.ants.as_deref().map(AntData::from_ants);
+                        if born.is_empty() && died.is_empty() && changed.is_empty() && ants.is_none() {
+                            continue;
+                        }
+                        ServerMessage::Delta { tick: update.tick, born, died, changed, ants }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("WebSocket client lagged by {} updates, resyncing", skipped);
+                        let (world, tick, rx) = state.simulation.subscribe();
+                        updates = rx;
+                        snapshot_message(&world, tick, viewport)
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                if send(&mut socket, &msg).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, msg: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(msg).expect("server messages are always serialisable");
+    socket.send(Message::Text(text)).await
+}
+
+fn snapshot_message(world: &World, tick: u64, viewport: Option<Viewport>) -> ServerMessage {
+    let cells: Vec<(Coord, u8)> = world.iter_cell_states().collect();
+
+    ServerMessage::Snapshot {
+        tick,
+        cells: filter_cells(&cells, viewport),
+        ants: AntData::from_ants(world.ants()),
+    }
+}
+
+fn filter_cells(cells: &[(Coord, u8)], viewport: Option<Viewport>) -> Vec<CellData> {
+    cells
+        .iter()
+        .filter(|&&(coord, _)| viewport.is_none_or(|v| v.contains(coord)))
+        .map(|&(coord, state)| CellData::new(coord, state))
+        .collect()
+}
@@ -0,0 +1,504 @@
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{Coord, Neighborhood, World};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// параметры возбуждения из записи MCell `R1/T3/C3/NM`: радиус и вид
+/// окрестности, порог и число цветов
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Excitation {
+    neighborhood: Neighborhood,
+    threshold: usize,
+    states: u8,
+}
+
+impl Excitation {
+    fn new(
+        neighborhood: Neighborhood,
+        threshold: usize,
+        states: u8,
+        min_states: u8,
+    ) -> Result<Self, RuleParseError> {
+        if !matches!(
+            neighborhood,
+            Neighborhood::Moore(_) | Neighborhood::VonNeumann(_)
+        ) {
+            return Err(RuleParseError::Malformed(
+                "only Moore and von Neumann neighbourhoods are supported".to_string(),
+            ));
+        }
+        neighborhood
+            .validate()
+            .map_err(|e| RuleParseError::Malformed(e.to_string()))?;
+        let size = neighborhood.size();
+        if threshold == 0 {
+            return Err(RuleParseError::Malformed(
+                "threshold must be at least 1".to_string(),
+            ));
+        }
+        if threshold > size {
+            return Err(RuleParseError::CountExceedsNeighborhood {
+                count: u8::try_from(threshold).unwrap_or(u8::MAX),
+                size,
+            });
+        }
+        if states < min_states {
+            return Err(RuleParseError::Malformed(format!(
+                "state count must be from {} to 255",
+                min_states
+            )));
+        }
+        Ok(Self {
+            neighborhood,
+            threshold,
+            states,
+        })
+    }
+
+    fn parse(s: &str, min_states: u8) -> Result<Self, RuleParseError> {
+        let mut radius = None;
+        let mut threshold = None;
+        let mut states = None;
+        let mut von_neumann = false;
+        for token in s.split('/').map(str::trim) {
+            let Some(key) = token.chars().next() else {
+                return Err(RuleParseError::Malformed("empty field".to_string()));
+            };
+            let value = &token[key.len_utf8()..];
+            let number = || {
+                value.parse::<u32>().map_err(|_| {
+                    RuleParseError::Malformed(format!("invalid number in '{}'", token))
+                })
+            };
+            match key.to_ascii_uppercase() {
+                'R' => radius = Some(number()?),
+                'T' => threshold = Some(number()? as usize),
+                'C' => {
+                    states = Some(u8::try_from(number()?).map_err(|_| {
+                        RuleParseError::Malformed("state count must be at most 255".to_string())
+                    })?)
+                }
+                'N' => {
+                    von_neumann = match value.to_ascii_uppercase().as_str() {
+                        "M" => false,
+                        "N" => true,
+                        _ => {
+                            return Err(RuleParseError::Malformed(format!(
+                                "unknown neighbourhood '{}'",
+                                token
+                            )))
+                        }
+                    }
+                }
+                c => return Err(RuleParseError::UnexpectedChar(c)),
+            }
+        }
+
+        let missing = |field: &str| RuleParseError::Malformed(format!("missing {}", field));
+        let radius = radius.ok_or_else(|| missing("range R"))?;
+        let neighborhood = if von_neumann {
+            Neighborhood::VonNeumann(radius)
+        } else {
+            Neighborhood::Moore(radius)
+        };
+        Self::new(
+            neighborhood,
+            threshold.ok_or_else(|| missing("threshold T"))?,
+            states.ok_or_else(|| missing("state count C"))?,
+            min_states,
+        )
+    }
+
+    fn rulestring(&self) -> String {
+        let kind = match self.neighborhood {
+            Neighborhood::VonNeumann(_) => 'N',
+            _ => 'M',
+        };
+        format!(
+            "R{}/T{}/C{}/N{}",
+            self.neighborhood.radius(),
+            self.threshold,
+            self.states,
+            kind
+        )
+    }
+
+    fn successor(&self, state: u8) -> u8 {
+        if state.saturating_add(1) >= self.states {
+            0
+        } else {
+            state + 1
+        }
+    }
+
+    /// один шаг: клетка переходит в следующий цвет, если соседей этого цвета
+    /// не меньше порога; при `excitable` ненулевые клетки переходят всегда
+    fn step(&self, current: &World, excitable: bool) -> World {
+        let topology = current.topology();
+        let offsets = self.neighborhood.offsets();
+        let around = |coord: Coord| {
+            offsets
+                .iter()
+                .filter_map(move |&offset| topology.wrap(coord + offset))
+        };
+
+        // фон 0 меняется только рядом с клетками цвета 1
+        let mut candidates: HashSet<Coord> = HashSet::new();
+        for (coord, state) in current.iter_cell_states() {
+            candidates.insert(coord);
+            if state == 1 {
+                candidates.extend(around(coord));
+            }
+        }
+
+        let changed: Vec<(Coord, u8)> = candidates
+            .into_par_iter()
+            .filter_map(|coord| {
+                let state = current.get_state(coord);
+                let next = self.successor(state);
+                let advances = (excitable && state != 0)
+                    || around(coord)
+                        .filter(|&neighbor| current.get_state(neighbor) == next)
+                        .take(self.threshold)
+                        .count()
+                        == self.threshold;
+                advances.then_some((coord, next))
+            })
+            .collect();
+
+        let mut next = current.clone();
+        for (coord, state) in changed {
+            next.set_state(coord, state);
+        }
+        next
+    }
+}
+
+/// циклический автомат Гриффита: N цветов по кругу, клетка цвета k переходит
+/// в k+1 (mod N), если среди соседей не меньше порога клеток цвета k+1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cyclic {
+    excitation: Excitation,
+    rulestring: String,
+}
+
+impl Cyclic {
+    pub fn new(
+        neighborhood: Neighborhood,
+        threshold: usize,
+        states: u8,
+    ) -> Result<Self, RuleParseError> {
+        Self::from_excitation(Excitation::new(neighborhood, threshold, states, 2)?)
+    }
+
+    fn from_excitation(excitation: Excitation) -> Result<Self, RuleParseError> {
+        Ok(Self {
+            rulestring: excitation.rulestring(),
+            excitation,
+        })
+    }
+
+    /// разбирает запись MCell `R3/T5/C3/NM`: радиус, порог, число цветов и
+    /// окрестность Мура (`NM`) или фон Неймана (`NN`)
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        Self::from_excitation(Excitation::parse(s, 2)?)
+    }
+
+    /// правило 313 Гриффита: из случайного супа вырастают спирали
+    pub fn spirals() -> Self {
+        Self::parse("R3/T5/C3/NM").unwrap()
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn states(&self) -> u8 {
+        self.excitation.states
+    }
+
+    /// похожа ли строка на запись MCell `R.../T...`
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim().to_ascii_lowercase();
+        s.starts_with('r') && s[1..].starts_with(|c: char| c.is_ascii_digit()) && s.contains("/t")
+    }
+}
+
+impl FromStr for Cyclic {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Cyclic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for Cyclic {
+    fn apply(&self, current: &World) -> World {
+        self.excitation.step(current, false)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn state_count(&self) -> u16 {
+        u16::from(self.excitation.states)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.excitation.neighborhood.clone()
+    }
+}
+
+/// возбудимая среда Гринберга-Хастингса: покоящаяся клетка (0) возбуждается (1),
+/// если возбуждённых соседей не меньше порога, а возбуждённая проходит
+/// рефрактерные состояния 2..N-1 и снова успокаивается
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenbergHastings {
+    excitation: Excitation,
+    rulestring: String,
+}
+
+impl GreenbergHastings {
+    pub fn new(
+        neighborhood: Neighborhood,
+        threshold: usize,
+        states: u8,
+    ) -> Result<Self, RuleParseError> {
+        Self::from_excitation(Excitation::new(neighborhood, threshold, states, 3)?)
+    }
+
+    fn from_excitation(excitation: Excitation) -> Result<Self, RuleParseError> {
+        Ok(Self {
+            rulestring: format!("GH/{}", excitation.rulestring()),
+            excitation,
+        })
+    }
+
+    /// разбирает `GH/R1/T1/C5/NN` - те же поля, что у циклического автомата
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let body = strip_marker(s).ok_or_else(|| {
+            RuleParseError::Malformed("expected notation like GH/R1/T1/C5/NN".to_string())
+        })?;
+        Self::from_excitation(Excitation::parse(body, 3)?)
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn states(&self) -> u8 {
+        self.excitation.states
+    }
+
+    /// начинается ли строка с `GH/`
+    pub fn looks_like(rulestring: &str) -> bool {
+        strip_marker(rulestring.trim()).is_some()
+    }
+}
+
+fn strip_marker(s: &str) -> Option<&str> {
+    let (marker, body) = s.split_once('/')?;
+    marker.trim().eq_ignore_ascii_case("gh").then_some(body)
+}
+
+impl FromStr for GreenbergHastings {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for GreenbergHastings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for GreenbergHastings {
+    fn apply(&self, current: &World) -> World {
+        self.excitation.step(current, true)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn state_count(&self) -> u16 {
+        u16::from(self.excitation.states)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.excitation.neighborhood.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{Rng, Topology};
+
+    fn torus_soup(side: u32, states: u8, seed: u64) -> World {
+        let mut rng = Rng::new(seed);
+        let mut world = World::with_topology(Topology::Torus {
+            width: side,
+            height: side,
+        });
+        for y in 0..side as i32 {
+            for x in 0..side as i32 {
+                world.set_state(Coord::new(x, y), (rng.next_u64() % states as u64) as u8);
+            }
+        }
+        world
+    }
+
+    /// прямой пересчёт по определению для сравнения
+    fn naive(rule: &Excitation, world: &World, excitable: bool) -> World {
+        let (width, height) = world.topology().size().unwrap();
+        let mut next = world.empty_like();
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let coord = Coord::new(x, y);
+                let state = world.get_state(coord);
+                let successor = (state + 1) % rule.states;
+                let count = rule
+                    .neighborhood
+                    .around(coord, world.topology())
+                    .filter(|&n| world.get_state(n) == successor)
+                    .count();
+                let advances = (excitable && state != 0) || count >= rule.threshold;
+                next.set_state(coord, if advances { successor } else { state });
+            }
+        }
+        next
+    }
+
+    #[test]
+    fn test_parse_notation() {
+        let rule = Cyclic::parse("r3/t5/c3/nm").unwrap();
+        assert_eq!(rule, Cyclic::spirals());
+        assert_eq!(rule.rulestring(), "R3/T5/C3/NM");
+        assert_eq!(rule.neighborhood(), Neighborhood::Moore(3));
+
+        let vn = Cyclic::parse("R2/T4/C14/NN").unwrap();
+        assert_eq!(vn.neighborhood(), Neighborhood::VonNeumann(2));
+        assert_eq!(vn.state_count(), 14);
+
+        let gh = GreenbergHastings::parse("gh/R1/T1/C5/NN").unwrap();
+        assert_eq!(gh.rulestring(), "GH/R1/T1/C5/NN");
+        assert_eq!(GreenbergHastings::parse(gh.rulestring()).unwrap(), gh);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Cyclic::parse(""), Err(RuleParseError::Empty));
+        assert!(Cyclic::parse("R1/T3/NM").is_err());
+        assert!(Cyclic::parse("R1/T0/C3/NM").is_err());
+        assert!(Cyclic::parse("R1/T3/C1/NM").is_err());
+        assert!(Cyclic::parse("R1/T3/C3/NX").is_err());
+        assert!(Cyclic::parse("R0/T1/C3/NM").is_err());
+        assert_eq!(
+            Cyclic::parse("R1/T9/C3/NM"),
+            Err(RuleParseError::CountExceedsNeighborhood { count: 9, size: 8 })
+        );
+        // у Гринберга-Хастингса нужно хотя бы одно рефрактерное состояние
+        assert!(GreenbergHastings::parse("GH/R1/T1/C2/NN").is_err());
+        assert!(GreenbergHastings::parse("R1/T1/C3/NN").is_err());
+    }
+
+    #[test]
+    fn test_looks_like() {
+        assert!(Cyclic::looks_like("R3/T5/C3/NM"));
+        assert!(!Cyclic::looks_like("R5,C0,M1,S34..58,B34..45,NM"));
+        assert!(!Cyclic::looks_like("RLR"));
+        assert!(GreenbergHastings::looks_like("GH/R1/T1/C5/NN"));
+        assert!(!GreenbergHastings::looks_like("R1/T1/C5/NN"));
+    }
+
+    #[test]
+    fn test_colours_advance_in_a_cycle() {
+        let rule = Cyclic::parse("R1/T1/C3/NN").unwrap();
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 2);
+        world.set_state(Coord::new(1, 0), 1);
+
+        // фон плоскости - это цвет 0: двойка рядом с ним уходит в 0,
+        // единица рядом с двойкой - в 2, а нули рядом с единицей - в 1
+        let next = rule.apply(&world);
+        assert_eq!(next.get_state(Coord::new(0, 0)), 0);
+        assert_eq!(next.get_state(Coord::new(1, 0)), 2);
+        assert_eq!(next.get_state(Coord::new(2, 0)), 1);
+        assert_eq!(next.get_state(Coord::new(1, 1)), 1);
+        assert_eq!(next.get_state(Coord::new(0, 1)), 0);
+    }
+
+    #[test]
+    fn test_matches_naive_on_dense_torus() {
+        let cases = [
+            (Cyclic::spirals().excitation, false),
+            (Cyclic::parse("R2/T3/C5/NN").unwrap().excitation, false),
+            (
+                GreenbergHastings::parse("GH/R1/T2/C6/NM")
+                    .unwrap()
+                    .excitation,
+                true,
+            ),
+        ];
+        for (rule, excitable) in cases {
+            let mut world = torus_soup(24, rule.states, 17);
+            let mut expected = world.clone();
+            for _ in 0..12 {
+                world = rule.step(&world, excitable);
+                expected = naive(&rule, &expected, excitable);
+            }
+            assert_eq!(world, expected, "{}", rule.rulestring());
+        }
+    }
+
+    #[test]
+    fn test_excited_cell_emits_a_ring() {
+        let rule = GreenbergHastings::parse("GH/R1/T1/C4/NN").unwrap();
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 1);
+
+        let world = rule.apply(&world);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 2);
+        for neighbor in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            assert_eq!(world.get_state(Coord::new(neighbor.0, neighbor.1)), 1);
+        }
+
+        // рефрактерная клетка не возбуждается снова, волна уходит наружу
+        let world = rule.apply(&world);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 3);
+        assert_eq!(world.get_state(Coord::new(2, 0)), 1);
+        let world = rule.apply(&world);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 0);
+        assert_eq!(world.get_state(Coord::new(3, 0)), 1);
+    }
+}
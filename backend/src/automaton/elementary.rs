@@ -0,0 +1,315 @@
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{Coord, World, CHUNK_SIZE};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// элементарный одномерный автомат Вольфрама: поколение номер `g` (считая от
+/// установки правила) лежит в строке `start_row + g`, следующее пишется строкой
+/// ниже, так что мир становится пространственно-временной диаграммой; на
+/// бесконечной плоскости фон считается мёртвым, и нечётные правила (000 -> 1)
+/// зажигают только клетки рядом с живыми
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elementary {
+    number: u8,
+    start_row: i32,
+    rulestring: String,
+}
+
+impl Elementary {
+    pub fn new(number: u8, start_row: i32) -> Self {
+        let rulestring = if start_row == 0 {
+            format!("W{}", number)
+        } else {
+            format!("W{}@{}", number, start_row)
+        };
+        Self {
+            number,
+            start_row,
+            rulestring,
+        }
+    }
+
+    /// разбирает `W30`, `Rule 110` или `W110@-20` (со строкой начала)
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim().to_ascii_lowercase();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let body = s
+            .strip_prefix("rule")
+            .or_else(|| s.strip_prefix('w'))
+            .ok_or_else(|| RuleParseError::Malformed("expected W<number> like W30".to_string()))?
+            .trim_start();
+
+        let (number, start_row) = match body.split_once('@') {
+            Some((number, row)) => (number, Some(row)),
+            None => (body, None),
+        };
+        let number = number.trim().parse::<u8>().map_err(|_| {
+            RuleParseError::Malformed(format!("rule number '{}' must be from 0 to 255", number))
+        })?;
+        let start_row = match start_row {
+            Some(row) => row
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| RuleParseError::Malformed(format!("invalid start row '{}'", row)))?,
+            None => 0,
+        };
+        Ok(Self::new(number, start_row))
+    }
+
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    pub fn start_row(&self) -> i32 {
+        self.start_row
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    /// `W30`, `w110@5` или `Rule 90`
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim().to_ascii_lowercase();
+        let body = s.strip_prefix("rule").or_else(|| s.strip_prefix('w'));
+        body.is_some_and(|body| body.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+    }
+
+    /// новое состояние клетки по тройке (левая, сама, правая)
+    fn next_cell(&self, left: bool, center: bool, right: bool) -> bool {
+        let pattern = (left as u8) << 2 | (center as u8) << 1 | right as u8;
+        self.number >> pattern & 1 == 1
+    }
+
+    /// строка поколения номер `generation`, если она помещается в i32
+    fn generation_row(&self, generation: u64) -> Option<i32> {
+        i32::try_from(generation)
+            .ok()
+            .and_then(|generation| self.start_row.checked_add(generation))
+    }
+}
+
+/// x живых клеток строки `y`
+fn row_cells(world: &World, y: i32) -> impl Iterator<Item = i32> + '_ {
+    let chunk_y = y.div_euclid(CHUNK_SIZE);
+    let local_y = y.rem_euclid(CHUNK_SIZE);
+    world
+        .chunk_keys()
+        .filter(move |&(_, key_y)| key_y == chunk_y)
+        .flat_map(move |(chunk_x, _)| {
+            let mut bits = world.chunk(chunk_x, chunk_y).map_or(0, |c| c.row(local_y));
+            std::iter::from_fn(move || {
+                (bits != 0).then(|| {
+                    let x = bits.trailing_zeros() as i32;
+                    bits &= bits - 1;
+                    chunk_x * CHUNK_SIZE + x
+                })
+            })
+        })
+}
+
+impl FromStr for Elementary {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Elementary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for Elementary {
+    fn apply(&self, current: &World) -> World {
+        self.apply_at(current, 0)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn jump(&self, current: &World, generations: u64) -> World {
+        self.jump_at(current, 0, generations)
+    }
+
+    /// строка поколения берётся из его номера, а не из мира: пустое поколение
+    /// тоже занимает свою строку, и диаграмма идёт дальше
+    fn apply_at(&self, current: &World, generation: u64) -> World {
+        let Some((y, next_y)) = self
+            .generation_row(generation)
+            .and_then(|y| Some((y, y.checked_add(1)?)))
+        else {
+            return current.clone();
+        };
+        let topology = current.topology();
+        if !topology.contains(Coord::new(0, next_y)) {
+            // диаграмма дошла до нижнего края конечного мира
+            return current.clone();
+        }
+
+        let candidates: BTreeSet<i32> = match topology.size() {
+            Some((width, _)) => (0..width as i32).collect(),
+            None => row_cells(current, y).flat_map(|x| x - 1..=x + 1).collect(),
+        };
+
+        let mut next = current.clone();
+        for x in candidates {
+            let alive = self.next_cell(
+                current.get_cell(Coord::new(x - 1, y)),
+                current.get_cell(Coord::new(x, y)),
+                current.get_cell(Coord::new(x + 1, y)),
+            );
+            next.set_cell(Coord::new(x, next_y), alive);
+        }
+        next
+    }
+
+    fn jump_at(&self, current: &World, generation: u64, generations: u64) -> World {
+        let mut world = current.clone();
+        for offset in 0..generations {
+            world = self.apply_at(&world, generation + offset);
+        }
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Topology;
+
+    /// прямой расчёт строк на отрезке с мёртвыми краями
+    fn naive_rows(number: u8, generations: usize) -> Vec<Vec<bool>> {
+        let width = 2 * generations + 3;
+        let mut row = vec![false; width];
+        row[generations + 1] = true;
+        let mut rows = vec![row.clone()];
+        for _ in 0..generations {
+            let next = (0..width)
+                .map(|x| {
+                    let left = x > 0 && row[x - 1];
+                    let right = x + 1 < width && row[x + 1];
+                    let pattern = (left as u8) << 2 | (row[x] as u8) << 1 | right as u8;
+                    number >> pattern & 1 == 1
+                })
+                .collect::<Vec<_>>();
+            rows.push(next.clone());
+            row = next;
+        }
+        rows
+    }
+
+    fn seed() -> World {
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        world
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Elementary::parse("W30").unwrap(), Elementary::new(30, 0));
+        assert_eq!(
+            Elementary::parse("rule 110").unwrap(),
+            Elementary::new(110, 0)
+        );
+        let rule = Elementary::parse("w90@-5").unwrap();
+        assert_eq!(rule.start_row(), -5);
+        assert_eq!(rule.rulestring(), "W90@-5");
+        assert_eq!(Elementary::parse(rule.rulestring()).unwrap(), rule);
+
+        assert!(Elementary::parse("W256").is_err());
+        assert!(Elementary::parse("W30@x").is_err());
+        assert!(Elementary::parse("B3/S23").is_err());
+
+        assert!(Elementary::looks_like("W30"));
+        assert!(Elementary::looks_like("Rule 30"));
+        assert!(!Elementary::looks_like("WireWorld"));
+        assert!(!Elementary::looks_like("RL"));
+    }
+
+    #[test]
+    fn test_rules_match_naive_diagram() {
+        for number in [30, 90, 110, 150, 184] {
+            let rule = Elementary::new(number, 0);
+            let world = rule.jump(&seed(), 40);
+            for (y, row) in naive_rows(number, 40).iter().enumerate() {
+                for (i, &alive) in row.iter().enumerate() {
+                    let coord = Coord::new(i as i32 - 41, y as i32);
+                    assert_eq!(world.get_cell(coord), alive, "W{} at {:?}", number, coord);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rule_90_is_sierpinski() {
+        let world = Elementary::new(90, 0).jump(&seed(), 31);
+        for y in 0..32 {
+            let count = world.iter_active_cells().filter(|c| c.y == y).count();
+            assert_eq!(count, 1 << (y as u32).count_ones(), "row {}", y);
+        }
+    }
+
+    #[test]
+    fn test_start_row_ignores_cells_above() {
+        let rule = Elementary::new(30, 10);
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        assert_eq!(rule.apply(&world), world);
+
+        world.set_cell(Coord::new(0, 10), true);
+        let next = rule.apply(&world);
+        assert_eq!(next.active_cell_count(), 5);
+        assert!(next.get_cell(Coord::new(-1, 11)));
+        assert!(!next.get_cell(Coord::new(-1, 1)));
+    }
+
+    #[test]
+    fn test_wraps_on_torus_and_stops_at_bottom() {
+        let mut world = World::with_topology(Topology::Torus {
+            width: 8,
+            height: 3,
+        });
+        world.set_cell(Coord::new(0, 0), true);
+
+        let rule = Elementary::new(90, 0);
+        let world = rule.apply(&world);
+        assert!(world.get_cell(Coord::new(1, 1)));
+        assert!(world.get_cell(Coord::new(7, 1)));
+
+        let full = rule.jump_at(&world, 1, 5);
+        assert_eq!(full, rule.apply_at(&world, 1));
+        assert_eq!(full.get_bounds().unwrap().1.y, 2);
+    }
+
+    #[test]
+    fn test_empty_generation_keeps_its_row() {
+        // W1 гасит сплошную строку и зажигает всю строку после пустой
+        let mut world = World::with_topology(Topology::Torus {
+            width: 8,
+            height: 4,
+        });
+        for x in 0..8 {
+            world.set_cell(Coord::new(x, 0), true);
+        }
+        let world = Elementary::new(1, 0).jump(&world, 3);
+        for (y, count) in [8, 0, 8, 0].into_iter().enumerate() {
+            let row = world
+                .iter_active_cells()
+                .filter(|c| c.y == y as i32)
+                .count();
+            assert_eq!(row, count, "row {}", y);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use rayon::prelude::*;
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+/// комплексное число для БПФ свёрток на торе
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// итеративное БПФ по основанию 2 на месте, длина - степень двойки;
+/// обратное сразу делит на длину
+pub(crate) fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+    debug_assert!(n.is_power_of_two());
+
+    // перестановка с обратным порядком битов
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f64;
+        let step = Complex::new(angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + len / 2] * w;
+                data[start + k] = even + odd;
+                data[start + k + len / 2] = even - odd;
+                w = w * step;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f64;
+        for value in data.iter_mut() {
+            *value = Complex::new(value.re * scale, value.im * scale);
+        }
+    }
+}
+
+/// двумерное БПФ массива `width` x `height`, записанного по строкам:
+/// строки, затем столбцы через транспонирование, и то и другое параллельно
+pub(crate) fn fft2(data: &mut [Complex], width: usize, height: usize, inverse: bool) {
+    data.par_chunks_mut(width).for_each(|row| fft(row, inverse));
+    let mut columns = transpose(data, width, height);
+    columns
+        .par_chunks_mut(height)
+        .for_each(|column| fft(column, inverse));
+    data.copy_from_slice(&transpose(&columns, height, width));
+}
+
+fn transpose(data: &[Complex], width: usize, height: usize) -> Vec<Complex> {
+    let mut out = vec![Complex::default(); data.len()];
+    for (y, row) in data.chunks(width).enumerate() {
+        for (x, &value) in row.iter().enumerate() {
+            out[x * height + y] = value;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dft(data: &[Complex]) -> Vec<Complex> {
+        let n = data.len();
+        (0..n)
+            .map(|k| {
+                data.iter()
+                    .enumerate()
+                    .fold(Complex::default(), |sum, (t, &x)| {
+                        let angle = -2.0 * PI * (k * t) as f64 / n as f64;
+                        sum + x * Complex::new(angle.cos(), angle.sin())
+                    })
+            })
+            .collect()
+    }
+
+    fn close(a: &[Complex], b: &[Complex]) -> bool {
+        a.iter()
+            .zip(b)
+            .all(|(x, y)| (x.re - y.re).abs() < 1e-9 && (x.im - y.im).abs() < 1e-9)
+    }
+
+    #[test]
+    fn test_matches_naive_dft() {
+        let data: Vec<Complex> = (0..16)
+            .map(|i| Complex::new((i * 7 % 5) as f64, (i % 3) as f64 - 1.0))
+            .collect();
+        let mut fast = data.clone();
+        fft(&mut fast, false);
+        assert!(close(&fast, &dft(&data)));
+
+        fft(&mut fast, true);
+        assert!(close(&fast, &data));
+    }
+
+    #[test]
+    fn test_2d_round_trip() {
+        let data: Vec<Complex> = (0..8 * 4)
+            .map(|i| Complex::new((i * 13 % 11) as f64, 0.0))
+            .collect();
+        let mut transformed = data.clone();
+        fft2(&mut transformed, 8, 4, false);
+        assert!((transformed[0].re - data.iter().map(|c| c.re).sum::<f64>()).abs() < 1e-9);
+        fft2(&mut transformed, 8, 4, true);
+        assert!(close(&transformed, &data));
+    }
+}
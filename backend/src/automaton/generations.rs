@@ -0,0 +1,246 @@
+use crate::automaton::life_like::{split_neighborhood, LifeLike, RuleParseError};
+use crate::automaton::Rule;
+use crate::world::{Neighborhood, World};
+use std::fmt;
+use std::str::FromStr;
+
+/// правило семейства Generations: живая клетка (1), не выжив, проходит через
+/// состояния угасания 2..C-1 и только потом становится мёртвой (0)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generations {
+    life: LifeLike,
+    states: u8,
+    rulestring: String,
+}
+
+impl Generations {
+    pub fn new(birth: &[u8], survival: &[u8], states: u8) -> Result<Self, RuleParseError> {
+        Self::from_life(LifeLike::new(birth, survival)?, states)
+    }
+
+    fn from_life(life: LifeLike, states: u8) -> Result<Self, RuleParseError> {
+        if states < 2 {
+            return Err(RuleParseError::Malformed(
+                "state count must be from 2 to 255".to_string(),
+            ));
+        }
+        // буква окрестности идёт в самый конец: `B2/S34/C3H`
+        let suffix = life.neighborhood().suffix();
+        let base = life.rulestring().trim_end_matches(|c| Some(c) == suffix);
+        let mut rulestring = format!("{}/C{}", base, states);
+        rulestring.extend(suffix);
+        Ok(Self {
+            life,
+            states,
+            rulestring,
+        })
+    }
+
+    /// разбирает `B2/S/C3`, `b2s345c4` или `345/2/4` (S/B/C), с буквой окрестности в конце
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim().to_ascii_lowercase();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let (s, neighborhood) = split_neighborhood(&s);
+
+        let (life_part, states_part) = match s.rfind(['c', 'g']) {
+            Some(index) => (s[..index].trim_end_matches('/'), &s[index + 1..]),
+            None => s.rsplit_once('/').ok_or_else(|| {
+                RuleParseError::Malformed("expected S/B/C notation like 345/2/4".to_string())
+            })?,
+        };
+
+        let states = states_part.parse::<u8>().map_err(|_| {
+            RuleParseError::Malformed(format!("invalid state count '{}'", states_part))
+        })?;
+        let life = LifeLike::parse(life_part)?;
+        let life = LifeLike::with_neighborhood(
+            &life.birth_counts(),
+            &life.survival_counts(),
+            neighborhood,
+        )?;
+        Self::from_life(life, states)
+    }
+
+    pub fn brians_brain() -> Self {
+        Self::new(&[2], &[], 3).unwrap()
+    }
+
+    pub fn star_wars() -> Self {
+        Self::new(&[2], &[3, 4, 5], 4).unwrap()
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// похожа ли строка на правило Generations, а не на обычное Life-like
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.to_ascii_lowercase();
+        s.contains(['c', 'g']) || s.matches('/').count() == 2
+    }
+}
+
+impl FromStr for Generations {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Generations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+/// следующее поколение многоцветного правила по шагу `stepped` живых клеток:
+/// рождаются только мёртвые клетки, не выжившие уходят в угасание 2..states-1
+pub(crate) fn decay(current: &World, stepped: &World, states: u8) -> World {
+    let mut next = current.empty_like();
+    for coord in stepped.iter_active_cells() {
+        if current.get_state(coord) <= 1 {
+            next.set_state(coord, 1);
+        }
+    }
+    for (coord, state) in current.iter_cell_states() {
+        let decayed = match state {
+            1 if stepped.get_cell(coord) => continue,
+            1 => 2,
+            // состояния вне правила (после смены правила или правки) тоже угасают
+            state => state.saturating_add(1),
+        };
+        if decayed < states {
+            next.set_state(coord, decayed);
+        }
+    }
+    next
+}
+
+impl Rule for Generations {
+    fn apply(&self, current: &World) -> World {
+        // соседей считают только клетки в состоянии 1, угасающие не рождаются заново
+        let stepped = self.life.step(&current.cells_in_state(1));
+        decay(current, &stepped, self.states)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn state_count(&self) -> u16 {
+        u16::from(self.states)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.life.neighborhood().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::Preset;
+    use crate::world::Coord;
+
+    #[test]
+    fn test_parse_notations() {
+        let brain = Generations::brians_brain();
+        assert_eq!(brain.rulestring(), "B2/S/C3");
+        assert_eq!(Generations::parse("/2/3").unwrap(), brain);
+        assert_eq!(Generations::parse("B2/S/C3").unwrap(), brain);
+        assert_eq!(Generations::parse("b2s/c3").unwrap(), brain);
+
+        let star_wars = Generations::star_wars();
+        assert_eq!(star_wars.rulestring(), "B2/S345/C4");
+        assert_eq!(Generations::parse("345/2/4").unwrap(), star_wars);
+        assert_eq!(Generations::parse("B2/S345/G4").unwrap(), star_wars);
+        assert_eq!(star_wars.state_count(), 4);
+
+        let hex = Generations::parse("b2s34c3h").unwrap();
+        assert_eq!(hex.rulestring(), "B2/S34/C3H");
+        assert_eq!(hex.neighborhood(), Neighborhood::Hexagonal);
+        assert_eq!(Generations::parse("34/2/3H").unwrap(), hex);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Generations::parse(""), Err(RuleParseError::Empty));
+        assert!(Generations::parse("345/2/1").is_err());
+        assert!(Generations::parse("345/2/x").is_err());
+        assert!(Generations::parse("345/2/300").is_err());
+        assert_eq!(
+            Generations::parse("/02/3"),
+            Err(RuleParseError::BirthOnZero)
+        );
+    }
+
+    #[test]
+    fn test_looks_like() {
+        assert!(Generations::looks_like("/2/3"));
+        assert!(Generations::looks_like("B2/S/C3"));
+        assert!(!Generations::looks_like("B3/S23"));
+        assert!(!Generations::looks_like("23/3"));
+    }
+
+    #[test]
+    fn test_cells_decay_through_refractory_states() {
+        let rule = Generations::star_wars();
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 1);
+
+        let world = rule.apply(&world);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 2);
+        let world = rule.apply(&world);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 3);
+        let world = rule.apply(&world);
+        assert_eq!(world.active_cell_count(), 0);
+    }
+
+    #[test]
+    fn test_refractory_cells_block_births() {
+        let rule = Generations::brians_brain();
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 1);
+        world.set_state(Coord::new(2, 0), 1);
+        world.set_state(Coord::new(1, 0), 2);
+
+        let next = rule.apply(&world);
+        // у (1, 0) двое живых соседей, но она угасает и не рождается
+        assert_eq!(next.get_state(Coord::new(1, 0)), 0);
+        assert_eq!(next.get_state(Coord::new(1, 1)), 1);
+        assert_eq!(next.get_state(Coord::new(1, -1)), 1);
+        assert_eq!(next.get_state(Coord::new(0, 0)), 2);
+    }
+
+    #[test]
+    fn test_out_of_range_states_die() {
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 255);
+        world.set_state(Coord::new(5, 5), 7);
+        assert_eq!(Generations::brians_brain().apply(&world).active_cell_count(), 0);
+    }
+
+    #[test]
+    fn test_generations_with_two_states_is_life() {
+        let rule = Generations::new(&[3], &[2, 3], 2).unwrap();
+        let life = LifeLike::conway();
+        let mut world = Preset::r_pentomino().to_world();
+        let mut expected = world.clone();
+        for _ in 0..50 {
+            world = rule.apply(&world);
+            expected = life.apply(&expected);
+        }
+        assert_eq!(world, expected);
+    }
+}
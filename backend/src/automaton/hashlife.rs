@@ -0,0 +1,667 @@
+use crate::automaton::{LifeLike, Rule, MAX_STEPWISE_JUMP};
+use crate::world::{Coord, Topology, World};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// после стольких узлов кэш сбрасывается, чтобы память не росла бесконечно
+const MAX_NODES: usize = 4_000_000;
+
+/// наибольший прыжок HashLife: корень дерева растёт до уровня около
+/// log2(прыжка), и координаты его углов должны помещаться в i64
+pub const MAX_HASHLIFE_JUMP: u64 = 1 << 48;
+
+/// минимальный уровень корня: 8x8 клеток
+const MIN_ROOT_LEVEL: u8 = 3;
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// узел квадродерева уровня `level` покрывает квадрат 2^level x 2^level;
+/// дети в порядке nw, ne, sw, se
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    children: [NodeId; 4],
+    population: u64,
+}
+
+/// корень дерева вместе с координатой его левого верхнего угла
+#[derive(Debug, Clone, Copy)]
+struct Root {
+    node: NodeId,
+    x: i64,
+    y: i64,
+}
+
+/// хранилище канонических узлов и мемоизированных результатов шагов
+#[derive(Debug)]
+struct Universe {
+    rule: LifeLike,
+    nodes: Vec<Node>,
+    index: HashMap<[NodeId; 4], NodeId>,
+    empty: Vec<NodeId>,
+    /// (узел, j) -> центр узла через 2^j поколений
+    results: HashMap<(NodeId, u8), NodeId>,
+}
+
+impl Universe {
+    fn new(rule: LifeLike) -> Self {
+        let leaf = |population| Node {
+            level: 0,
+            children: [DEAD; 4],
+            population,
+        };
+
+        Self {
+            rule,
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            empty: vec![DEAD],
+            results: HashMap::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new(self.rule.clone());
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.nodes[id as usize].level
+    }
+
+    fn population(&self, id: NodeId) -> u64 {
+        self.nodes[id as usize].population
+    }
+
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+
+        let node = Node {
+            level: self.level(nw) + 1,
+            children,
+            population: children.iter().map(|&c| self.population(c)).sum(),
+        };
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    /// центральный подузел уровнем ниже
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.node(id).children;
+        let (nw, ne, sw, se) = (self.node(nw), self.node(ne), self.node(sw), self.node(se));
+        self.join(
+            nw.children[3],
+            ne.children[2],
+            sw.children[1],
+            se.children[0],
+        )
+    }
+
+    /// все живые клетки лежат в центральном подквадрате корня
+    fn is_padded(&self, id: NodeId) -> bool {
+        let node = self.node(id);
+        if node.level < 2 {
+            return false;
+        }
+        let inner = [3usize, 2, 1, 0];
+        node.children.iter().zip(inner).all(|(&child, keep)| {
+            let child = self.node(child);
+            child.population == self.population(child.children[keep])
+        })
+    }
+
+    fn expand(&mut self, root: Root) -> Root {
+        let level = self.level(root.node);
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.node(root.node).children;
+
+        let nw = self.join(e, e, e, nw);
+        let ne = self.join(e, e, ne, e);
+        let sw = self.join(e, sw, e, e);
+        let se = self.join(se, e, e, e);
+
+        let half = 1i64 << (level - 1);
+        Root {
+            node: self.join(nw, ne, sw, se),
+            x: root.x - half,
+            y: root.y - half,
+        }
+    }
+
+    fn shrink(&mut self, mut root: Root) -> Root {
+        while self.level(root.node) > MIN_ROOT_LEVEL && self.is_padded(root.node) {
+            let quarter = 1i64 << (self.level(root.node) - 2);
+            root = Root {
+                node: self.centre(root.node),
+                x: root.x + quarter,
+                y: root.y + quarter,
+            };
+        }
+        root
+    }
+
+    /// центр узла (уровнем ниже) через 2^j поколений, j <= level - 2
+    fn step(&mut self, id: NodeId, j: u8) -> NodeId {
+        let node = self.node(id);
+        debug_assert!(node.level >= 2 && j <= node.level - 2);
+
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.step_base(id)
+        } else {
+            self.step_recursive(id, j)
+        };
+
+        self.results.insert((id, j), result);
+        result
+    }
+
+    /// узел 4x4 -> центр 2x2 через одно поколение
+    fn step_base(&mut self, id: NodeId) -> NodeId {
+        let mut grid = [[false; 4]; 4];
+        let children = self.node(id).children;
+        for (q, &child) in children.iter().enumerate() {
+            let leaves = self.node(child).children;
+            for (i, &leaf) in leaves.iter().enumerate() {
+                let x = (q % 2) * 2 + i % 2;
+                let y = (q / 2) * 2 + i / 2;
+                grid[y][x] = leaf == ALIVE;
+            }
+        }
+
+        let mut next = [DEAD; 4];
+        for (i, cell) in next.iter_mut().enumerate() {
+            let (x, y) = (1 + i % 2, 1 + i / 2);
+            let mut neighbors = 0u8;
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    if (dx, dy) != (1, 1) && grid[y + dy - 1][x + dx - 1] {
+                        neighbors += 1;
+                    }
+                }
+            }
+            let alive = if grid[y][x] {
+                self.rule.survives_on(neighbors)
+            } else {
+                self.rule.births_on(neighbors)
+            };
+            *cell = if alive { ALIVE } else { DEAD };
+        }
+
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    fn step_recursive(&mut self, id: NodeId, j: u8) -> NodeId {
+        let level = self.level(id);
+        let [a, b, c, d] = self.node(id).children;
+        let (an, bn, cn, dn) = (self.node(a), self.node(b), self.node(c), self.node(d));
+
+        // девять перекрывающихся подузлов уровня level - 1
+        let subs = [
+            a,
+            self.join(
+                an.children[1],
+                bn.children[0],
+                an.children[3],
+                bn.children[2],
+            ),
+            b,
+            self.join(
+                an.children[2],
+                an.children[3],
+                cn.children[0],
+                cn.children[1],
+            ),
+            self.join(
+                an.children[3],
+                bn.children[2],
+                cn.children[1],
+                dn.children[0],
+            ),
+            self.join(
+                bn.children[2],
+                bn.children[3],
+                dn.children[0],
+                dn.children[1],
+            ),
+            c,
+            self.join(
+                cn.children[1],
+                dn.children[0],
+                cn.children[3],
+                dn.children[2],
+            ),
+            d,
+        ];
+
+        // на полной скорости обе половины шага продвигают время, иначе только вторая
+        let full_speed = j == level - 2;
+        let mut r = [DEAD; 9];
+        for (out, &sub) in r.iter_mut().zip(subs.iter()) {
+            *out = if full_speed {
+                self.step(sub, j - 1)
+            } else {
+                self.centre(sub)
+            };
+        }
+
+        let q = [
+            self.join(r[0], r[1], r[3], r[4]),
+            self.join(r[1], r[2], r[4], r[5]),
+            self.join(r[3], r[4], r[6], r[7]),
+            self.join(r[4], r[5], r[7], r[8]),
+        ];
+        let second_j = if full_speed { j - 1 } else { j };
+        let mut s = [DEAD; 4];
+        for (out, &quad) in s.iter_mut().zip(q.iter()) {
+            *out = self.step(quad, second_j);
+        }
+
+        self.join(s[0], s[1], s[2], s[3])
+    }
+
+    fn set_alive(&mut self, id: NodeId, x: i64, y: i64) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return ALIVE;
+        }
+
+        let half = 1i64 << (node.level - 1);
+        let quadrant = usize::from(x >= half) + 2 * usize::from(y >= half);
+        let mut children = node.children;
+        children[quadrant] = self.set_alive(
+            children[quadrant],
+            x - if x >= half { half } else { 0 },
+            y - if y >= half { half } else { 0 },
+        );
+        self.join(children[0], children[1], children[2], children[3])
+    }
+
+    fn build(&mut self, world: &World) -> Option<Root> {
+        let (min, max) = world.get_bounds()?;
+        // мир может занимать весь диапазон i32, поэтому размеры считаются в i64
+        let width = i64::from(max.x) - i64::from(min.x);
+        let height = i64::from(max.y) - i64::from(min.y);
+        let size = width.max(height) + 1;
+
+        let mut level = MIN_ROOT_LEVEL;
+        while (1i64 << level) < size {
+            level += 1;
+        }
+
+        let mut node = self.empty(level);
+        for cell in world.iter_active_cells() {
+            node = self.set_alive(
+                node,
+                i64::from(cell.x) - i64::from(min.x),
+                i64::from(cell.y) - i64::from(min.y),
+            );
+        }
+
+        Some(Root {
+            node,
+            x: i64::from(min.x),
+            y: i64::from(min.y),
+        })
+    }
+
+    /// клетки за пределами диапазона i32 отбрасываются
+    fn to_world(&self, root: Root) -> World {
+        let mut world = World::new();
+        let mut stack = vec![(root.node, root.x, root.y)];
+
+        while let Some((id, x, y)) = stack.pop() {
+            let node = self.node(id);
+            if node.population == 0 {
+                continue;
+            }
+            if node.level == 0 {
+                if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
+                    world.set_cell(Coord::new(x, y), true);
+                }
+                continue;
+            }
+
+            let half = 1i64 << (node.level - 1);
+            stack.push((node.children[0], x, y));
+            stack.push((node.children[1], x + half, y));
+            stack.push((node.children[2], x, y + half));
+            stack.push((node.children[3], x + half, y + half));
+        }
+        world
+    }
+
+    fn advance(&mut self, mut root: Root, generations: u64) -> Root {
+        for j in 0..u64::BITS as u8 {
+            if generations & (1u64 << j) == 0 {
+                continue;
+            }
+            while self.level(root.node) < j + 2 || !self.is_padded(root.node) {
+                root = self.expand(root);
+            }
+            let expanded = self.expand(root);
+            root.node = self.step(expanded.node, j);
+            root = self.shrink(root);
+            // длинный прыжок растущего узора заполняет кэш внутри одного вызова
+            if self.nodes.len() > MAX_NODES {
+                root.node = self.compact(root.node);
+            }
+        }
+        root
+    }
+
+    /// переносит дерево `root` в чистое хранилище; остальные узлы и результаты
+    /// шагов забываются
+    fn compact(&mut self, root: NodeId) -> NodeId {
+        let old = std::mem::replace(self, Self::new(self.rule.clone()));
+        let mut copied = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+        self.copy_node(&old, root, &mut copied)
+    }
+
+    fn copy_node(
+        &mut self,
+        old: &Universe,
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if let Some(&new) = copied.get(&id) {
+            return new;
+        }
+        let [nw, ne, sw, se] = old
+            .node(id)
+            .children
+            .map(|child| self.copy_node(old, child, copied));
+        let new = self.join(nw, ne, sw, se);
+        copied.insert(id, new);
+        new
+    }
+}
+
+/// HashLife: мемоизированное квадродерево для Life-like правил
+#[derive(Debug)]
+pub struct HashLife {
+    rule: LifeLike,
+    name: String,
+    universe: Mutex<Universe>,
+}
+
+impl HashLife {
+    pub fn new(rule: LifeLike) -> Self {
+        Self {
+            name: format!("{} (HashLife)", rule.rulestring()),
+            universe: Mutex::new(Universe::new(rule.clone())),
+            rule,
+        }
+    }
+
+    pub fn rule(&self) -> &LifeLike {
+        &self.rule
+    }
+
+    /// число узлов в кэше
+    pub fn node_count(&self) -> usize {
+        self.universe.lock().unwrap().nodes.len()
+    }
+
+    fn run(&self, current: &World, generations: u64) -> World {
+        // квадродерево описывает только бесконечную плоскость
+        if current.topology() != Topology::Plane {
+            let mut world = current.clone();
+            for _ in 0..generations {
+                world = self.rule.apply(&world);
+            }
+            return world;
+        }
+
+        let mut universe = self.universe.lock().unwrap();
+        if universe.nodes.len() > MAX_NODES {
+            universe.clear();
+        }
+
+        match universe.build(current) {
+            Some(root) => {
+                let root = universe.advance(root, generations);
+                universe.to_world(root)
+            }
+            None => World::new(),
+        }
+    }
+}
+
+impl Rule for HashLife {
+    fn apply(&self, current: &World) -> World {
+        self.run(current, 1)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(self.rule.rulestring())
+    }
+
+    /// число шагов HashLife растёт как логарифм прыжка; на конечных мирах
+    /// дерева нет, и прыжок идёт по поколению
+    fn max_jump(&self, current: &World) -> u64 {
+        if current.topology() == Topology::Plane {
+            MAX_HASHLIFE_JUMP
+        } else {
+            MAX_STEPWISE_JUMP
+        }
+    }
+
+    fn jump(&self, current: &World, generations: u64) -> World {
+        self.run(current, generations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::Preset;
+
+    fn cells(world: &World) -> Vec<(i32, i32)> {
+        let mut cells: Vec<_> = world.iter_active_cells().map(|c| (c.x, c.y)).collect();
+        cells.sort();
+        cells
+    }
+
+    fn naive(rule: &LifeLike, world: &World, generations: u64) -> World {
+        let mut current = world.clone();
+        for _ in 0..generations {
+            current = rule.apply(&current);
+        }
+        current
+    }
+
+    #[test]
+    fn test_empty_world() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        assert_eq!(hashlife.jump(&World::new(), 100).active_cell_count(), 0);
+    }
+
+    #[test]
+    fn test_round_trip_without_steps() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        let world = Preset::gosper_glider_gun().to_world();
+        assert_eq!(cells(&hashlife.jump(&world, 0)), cells(&world));
+    }
+
+    #[test]
+    fn test_apply_matches_life_like() {
+        let rule = LifeLike::conway();
+        let hashlife = HashLife::new(rule.clone());
+        let world = Preset::r_pentomino().to_world();
+
+        let mut current = world.clone();
+        for _ in 0..50 {
+            current = hashlife.apply(&current);
+        }
+        assert_eq!(cells(&current), cells(&naive(&rule, &world, 50)));
+    }
+
+    #[test]
+    fn test_jump_matches_life_like() {
+        let rule = LifeLike::conway();
+        let hashlife = HashLife::new(rule.clone());
+
+        for preset in [
+            Preset::r_pentomino(),
+            Preset::gosper_glider_gun(),
+            Preset::acorn(),
+        ] {
+            let world = preset.to_world();
+            for generations in [1, 7, 64, 300] {
+                assert_eq!(
+                    cells(&hashlife.jump(&world, generations)),
+                    cells(&naive(&rule, &world, generations)),
+                    "{} after {} generations",
+                    preset.name,
+                    generations
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_other_life_like_rules() {
+        let rule = LifeLike::highlife();
+        let hashlife = HashLife::new(rule.clone());
+        let world = Preset::r_pentomino().to_world();
+
+        assert_eq!(
+            cells(&hashlife.jump(&world, 100)),
+            cells(&naive(&rule, &world, 100))
+        );
+    }
+
+    #[test]
+    fn test_glider_long_jump() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        let world = Preset::glider().to_world();
+
+        let after = hashlife.jump(&world, 1_000_000);
+        let expected: Vec<_> = cells(&world)
+            .into_iter()
+            .map(|(x, y)| (x + 250_000, y + 250_000))
+            .collect();
+        assert_eq!(cells(&after), expected);
+    }
+
+    #[test]
+    fn test_gun_long_jump_is_memoised() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        let world = Preset::gosper_glider_gun().to_world();
+
+        // одна пушка выпускает глайдер каждые 30 поколений
+        let after = hashlife.jump(&world, 1 << 20);
+        let gliders = after.active_cell_count() / 5;
+        assert!(gliders > (1 << 20) / 30 - 10);
+        assert!(hashlife.node_count() < 1_000_000);
+    }
+
+    #[test]
+    fn test_name() {
+        let hashlife = HashLife::new(LifeLike::highlife());
+        assert_eq!(hashlife.name(), "B36/S23 (HashLife)");
+        assert_eq!(hashlife.rulestring(), Some("B36/S23"));
+    }
+
+    #[test]
+    fn test_finite_topology_falls_back_to_rule() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        let mut world = Preset::glider().to_world();
+        world.set_topology(Topology::Torus {
+            width: 10,
+            height: 10,
+        });
+
+        let jumped = hashlife.jump(&world, 40);
+        assert_eq!(jumped, world);
+        assert_eq!(jumped.topology(), world.topology());
+    }
+
+    #[test]
+    fn test_max_jump() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        let mut world = Preset::glider().to_world();
+        assert_eq!(hashlife.max_jump(&world), MAX_HASHLIFE_JUMP);
+
+        // глайдер улетает за пределы i32 и пропадает из мира, но прыжок не падает
+        let far = hashlife.jump(&world, MAX_HASHLIFE_JUMP);
+        assert_eq!(far.active_cell_count(), 0);
+
+        world.set_topology(Topology::Torus {
+            width: 10,
+            height: 10,
+        });
+        assert_eq!(hashlife.max_jump(&world), MAX_STEPWISE_JUMP);
+        assert_eq!(LifeLike::conway().max_jump(&world), MAX_STEPWISE_JUMP);
+    }
+
+    #[test]
+    fn test_world_spanning_i32_range() {
+        let hashlife = HashLife::new(LifeLike::conway());
+        let mut world = World::new();
+        world.set_cell(Coord::new(i32::MIN, 0), true);
+        world.set_cell(Coord::new(i32::MAX, 0), true);
+        for x in i32::MAX - 2..=i32::MAX {
+            world.set_cell(Coord::new(x, i32::MIN + 5), true);
+        }
+
+        // одиночные клетки умирают, мигалка у края поворачивается
+        let next = hashlife.apply(&world);
+        assert_eq!(
+            cells(&next),
+            [
+                (i32::MAX - 1, i32::MIN + 4),
+                (i32::MAX - 1, i32::MIN + 5),
+                (i32::MAX - 1, i32::MIN + 6)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_keeps_only_the_root_tree() {
+        let mut universe = Universe::new(LifeLike::conway());
+        let root = universe.build(&Preset::r_pentomino().to_world()).unwrap();
+        let root = universe.advance(root, 1000);
+        let before = universe.to_world(root);
+        let nodes = universe.nodes.len();
+
+        let compacted = Root {
+            node: universe.compact(root.node),
+            ..root
+        };
+        assert!(universe.nodes.len() < nodes);
+        assert!(universe.results.is_empty());
+        assert_eq!(universe.to_world(compacted), before);
+    }
+}
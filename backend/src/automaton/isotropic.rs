@@ -0,0 +1,505 @@
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{step_isotropic, World};
+use std::fmt;
+use std::str::FromStr;
+
+/// буквы Хенселя в каноническом порядке; числу соседей `n` соответствуют
+/// первые `LETTER_COUNTS[n]` букв
+const LETTERS: [char; 13] = [
+    'c', 'e', 'k', 'a', 'i', 'n', 'y', 'q', 'j', 'r', 't', 'w', 'z',
+];
+const LETTER_COUNTS: [usize; 9] = [0, 2, 6, 10, 13, 10, 6, 2, 0];
+
+// биты конфигурации соседей, как в `step_chunk_isotropic`
+const N: u8 = 1;
+const NE: u8 = 1 << 1;
+const E: u8 = 1 << 2;
+const SE: u8 = 1 << 3;
+const S: u8 = 1 << 4;
+const SW: u8 = 1 << 5;
+const W: u8 = 1 << 6;
+const NW: u8 = 1 << 7;
+
+/// представители классов для 1-4 соседей в порядке `LETTERS`;
+/// классы 5-7 соседей - дополнения классов 3-1 с той же буквой
+const REPRESENTATIVES: [&[u8]; 4] = [
+    &[NE, N],
+    &[NE | SE, N | E, N | SE, N | NE, N | S, NE | SW],
+    &[
+        NE | SE | SW,
+        N | E | S,
+        N | E | SW,
+        N | NE | E,
+        N | NE | NW,
+        N | NE | SE,
+        N | SE | SW,
+        N | NE | SW,
+        N | NE | W,
+        N | NE | S,
+    ],
+    &[
+        NE | SE | SW | NW,
+        N | E | S | W,
+        N | NE | SE | W,
+        N | NE | E | SE,
+        N | NE | SE | S,
+        N | NE | SE | NW,
+        N | NE | SE | SW,
+        N | NE | E | SW,
+        N | NE | S | W,
+        N | NE | E | S,
+        N | NE | S | NW,
+        N | NE | SW | W,
+        N | NE | S | SW,
+    ],
+];
+
+/// восемь поворотов и отражений конфигурации
+fn symmetries(mask: u8) -> impl Iterator<Item = u8> {
+    // отражение слева направо: NE <-> NW, E <-> W, SE <-> SW
+    let mirrored = (0..8).fold(0u8, |m, bit| m | (mask >> bit & 1) << ((8 - bit) % 8));
+    (0..4).flat_map(move |turn| [mask.rotate_left(2 * turn), mirrored.rotate_left(2 * turn)])
+}
+
+/// класс Хенселя каждой из 256 конфигураций: (число соседей, индекс буквы)
+fn classes() -> [(u8, usize); 256] {
+    let mut classes = [(0u8, 0usize); 256];
+    for (mask, class) in classes.iter_mut().enumerate() {
+        class.0 = (mask as u8).count_ones() as u8;
+    }
+    for (index, representatives) in REPRESENTATIVES.iter().enumerate() {
+        let count = index as u8 + 1;
+        for (letter, &mask) in representatives.iter().enumerate() {
+            for symmetric in symmetries(mask) {
+                classes[symmetric as usize] = (count, letter);
+                if count < 4 {
+                    classes[!symmetric as usize] = (8 - count, letter);
+                }
+            }
+        }
+    }
+    classes
+}
+
+/// изотропное нетоталистическое правило в нотации Хенселя, например tlife
+/// `B3/S2-i34q`: после числа соседей буквы уточняют их расположение, `-`
+/// перед буквами исключает их; переход клетки берётся из таблиц на 256
+/// конфигураций соседей
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Isotropic {
+    birth: [bool; 256],
+    survival: [bool; 256],
+    rulestring: String,
+}
+
+impl Isotropic {
+    /// правило по таблицам конфигураций; бит `i` индекса - сосед N, NE, E,
+    /// SE, S, SW, W, NW
+    pub fn new(birth: [bool; 256], survival: [bool; 256]) -> Result<Self, RuleParseError> {
+        if birth[0] {
+            return Err(RuleParseError::BirthOnZero);
+        }
+        // таблица должна быть изотропной - одинаковой на всём классе
+        let classes = classes();
+        for table in [&birth, &survival] {
+            for mask in 0..=u8::MAX {
+                if symmetries(mask).any(|m| table[m as usize] != table[mask as usize]) {
+                    let (count, letter) = classes[mask as usize];
+                    return Err(RuleParseError::Malformed(format!(
+                        "table is not isotropic for {}{}",
+                        count, LETTERS[letter]
+                    )));
+                }
+            }
+        }
+
+        let rulestring = format!(
+            "B{}/S{}",
+            format_half(&birth, &classes),
+            format_half(&survival, &classes)
+        );
+        Ok(Self {
+            birth,
+            survival,
+            rulestring,
+        })
+    }
+
+    /// разбирает `B3-cnqy/S23-a4ityz` или `b3s2-i34q`
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim().to_ascii_lowercase();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+
+        let mut birth = None;
+        let mut survival = None;
+        let mut rest = s.as_str();
+        while let Some(section) = rest.chars().next() {
+            rest = &rest[section.len_utf8()..];
+            let end = rest.find(['b', 's', '/']).unwrap_or(rest.len());
+            let (body, tail) = rest.split_at(end);
+            let target = match section {
+                'b' => &mut birth,
+                's' => &mut survival,
+                '/' => continue,
+                other => return Err(RuleParseError::UnexpectedChar(other)),
+            };
+            if target.is_some() {
+                return Err(RuleParseError::Malformed(format!(
+                    "duplicate {} section",
+                    section.to_ascii_uppercase()
+                )));
+            }
+            *target = Some(parse_half(body)?);
+            rest = tail;
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Self::new(birth, survival),
+            _ => Err(RuleParseError::Malformed(
+                "both B and S sections are required".to_string(),
+            )),
+        }
+    }
+
+    /// tlife: выживание при двух соседях, кроме двух напротив друг друга,
+    /// и при четырёх только в конфигурации `q`
+    pub fn tlife() -> Self {
+        Self::parse("B3/S2-i34q").unwrap()
+    }
+
+    /// похожа ли строка на правило в нотации Хенселя: есть буквы или `-`
+    /// после чисел; `b2s34c3` остаётся за Generations
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim().to_ascii_lowercase();
+        let has_letters = s.chars().any(|c| c == '-' || LETTERS.contains(&c));
+        let states_suffix = s
+            .rsplit_once('c')
+            .is_some_and(|(_, tail)| !tail.is_empty() && tail.chars().all(|c| c.is_ascii_digit()));
+        has_letters
+            && !states_suffix
+            && s.contains(['b', 's'])
+            && s.chars()
+                .all(|c| c.is_ascii_digit() || "bs/-".contains(c) || LETTERS.contains(&c))
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    /// рождается ли клетка при конфигурации соседей `neighbors`
+    pub fn births_on(&self, neighbors: u8) -> bool {
+        self.birth[neighbors as usize]
+    }
+
+    pub fn survives_on(&self, neighbors: u8) -> bool {
+        self.survival[neighbors as usize]
+    }
+}
+
+/// одна половина правила (`3-cnqy`, `23-a4ityz`) в таблицу конфигураций
+fn parse_half(body: &str) -> Result<[bool; 256], RuleParseError> {
+    // для каждого числа соседей - выбранные буквы; пустой набор значит «все»
+    let mut groups: Vec<(usize, bool, Vec<usize>)> = Vec::new();
+    for c in body.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            if digit > 8 {
+                return Err(RuleParseError::InvalidCount(c));
+            }
+            groups.push((digit as usize, false, Vec::new()));
+            continue;
+        }
+        let Some((count, negated, letters)) = groups.last_mut() else {
+            return Err(RuleParseError::Malformed(format!(
+                "'{}' must follow a neighbour count",
+                c
+            )));
+        };
+        if c == '-' {
+            if *negated || !letters.is_empty() {
+                return Err(RuleParseError::UnexpectedChar(c));
+            }
+            *negated = true;
+            continue;
+        }
+        let letter = LETTERS[..LETTER_COUNTS[*count]]
+            .iter()
+            .position(|&l| l == c)
+            .ok_or_else(|| {
+                if LETTERS.contains(&c) {
+                    RuleParseError::Malformed(format!("{}{} is not a valid class", count, c))
+                } else {
+                    RuleParseError::UnexpectedChar(c)
+                }
+            })?;
+        letters.push(letter);
+    }
+
+    let classes = classes();
+    let mut table = [false; 256];
+    for (count, negated, letters) in groups {
+        if negated && letters.is_empty() {
+            return Err(RuleParseError::Malformed(format!(
+                "'{}-' needs letters to exclude",
+                count
+            )));
+        }
+        for (mask, &(class_count, letter)) in classes.iter().enumerate() {
+            if class_count as usize == count
+                && (letters.is_empty() || letters.contains(&letter) != negated)
+            {
+                table[mask] = true;
+            }
+        }
+    }
+    Ok(table)
+}
+
+/// каноническая запись половины правила: цифра без букв, если класс
+/// включён целиком, иначе более короткий из вариантов `3aik` и `3-cen...`
+fn format_half(table: &[bool; 256], classes: &[(u8, usize); 256]) -> String {
+    let mut out = String::new();
+    for (count, &letters) in LETTER_COUNTS.iter().enumerate() {
+        let mut present = vec![false; letters.max(1)];
+        for (mask, &(class_count, letter)) in classes.iter().enumerate() {
+            if class_count as usize == count && table[mask] {
+                present[letter] = true;
+            }
+        }
+
+        let total = present.len();
+        let included = present.iter().filter(|&&p| p).count();
+        if included == 0 {
+            continue;
+        }
+        out.push(char::from_digit(count as u32, 10).unwrap_or('?'));
+        if included == total {
+            continue;
+        }
+        let negated = included * 2 > total;
+        if negated {
+            out.push('-');
+        }
+        out.extend(
+            present
+                .iter()
+                .zip(LETTERS)
+                .filter(|&(&p, _)| p != negated)
+                .map(|(_, letter)| letter),
+        );
+    }
+    out
+}
+
+impl FromStr for Isotropic {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Isotropic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for Isotropic {
+    fn apply(&self, current: &World) -> World {
+        step_isotropic(current, &self.birth, &self.survival)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::LifeLike;
+    use crate::world::{soup_world, Coord, Topology};
+
+    fn world_of(cells: &[(i32, i32)]) -> World {
+        let mut world = World::new();
+        for &(x, y) in cells {
+            world.set_cell(Coord::new(x, y), true);
+        }
+        world
+    }
+
+    /// прямой шаг: конфигурация соседей каждой клетки собирается по одной
+    fn naive_step(rule: &Isotropic, world: &World) -> World {
+        let offsets = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+        let mut next = world.empty_like();
+        let Some((min, max)) = world.get_bounds() else {
+            return next;
+        };
+        for y in min.y - 1..=max.y + 1 {
+            for x in min.x - 1..=max.x + 1 {
+                let coord = Coord::new(x, y);
+                let neighbors = offsets
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |mask, (bit, &(dx, dy))| {
+                        let neighbor = world.topology().wrap(Coord::new(x + dx, y + dy));
+                        mask | (neighbor.is_some_and(|n| world.get_cell(n)) as u8) << bit
+                    });
+                let alive = world.get_cell(coord);
+                if (alive && rule.survives_on(neighbors)) || (!alive && rule.births_on(neighbors)) {
+                    next.set_cell(coord, true);
+                }
+            }
+        }
+        next.clip_to_topology();
+        next
+    }
+
+    #[test]
+    fn test_classes_partition_configurations() {
+        let classes = classes();
+        let mut seen = std::collections::HashSet::new();
+        for (mask, &(count, letter)) in classes.iter().enumerate() {
+            assert_eq!(count as u32, (mask as u8).count_ones());
+            assert!(letter < LETTER_COUNTS[count as usize].max(1));
+            // класс замкнут относительно поворотов и отражений
+            for symmetric in symmetries(mask as u8) {
+                assert_eq!(classes[symmetric as usize], (count, letter));
+            }
+            seen.insert((count, letter));
+        }
+        // 51 класс: 1 + 2 + 6 + 10 + 13 + 10 + 6 + 2 + 1
+        assert_eq!(seen.len(), 51);
+    }
+
+    #[test]
+    fn test_parse_canonical_round_trip() {
+        let rule = Isotropic::parse("B3-cnqy/S23-a4ityz").unwrap();
+        assert_eq!(rule.rulestring(), "B3-cnyq/S23-a4iytz");
+        assert_eq!(Isotropic::parse(rule.rulestring()).unwrap(), rule);
+        assert_eq!(Isotropic::parse("b3-yqncs4zyti3-a2").unwrap(), rule);
+
+        assert_eq!(Isotropic::tlife().rulestring(), "B3/S2-i34q");
+        assert_eq!(Isotropic::parse("b3s23").unwrap().rulestring(), "B3/S23");
+        // все буквы - это просто цифра; больше половины - через минус
+        assert_eq!(
+            Isotropic::parse("B2cekain/S1ce").unwrap().rulestring(),
+            "B2/S1"
+        );
+        assert_eq!(
+            Isotropic::parse("B2cek/S4cekainy").unwrap().rulestring(),
+            "B2cek/S4-qjrtwz"
+        );
+        assert_eq!(
+            Isotropic::parse("B2-cek/S").unwrap().rulestring(),
+            "B2ain/S"
+        );
+
+        assert_eq!(Isotropic::parse(""), Err(RuleParseError::Empty));
+        assert_eq!(Isotropic::parse("B0/S"), Err(RuleParseError::BirthOnZero));
+        assert_eq!(
+            Isotropic::parse("B9/S"),
+            Err(RuleParseError::InvalidCount('9'))
+        );
+        assert_eq!(
+            Isotropic::parse("B3/S2x"),
+            Err(RuleParseError::UnexpectedChar('x'))
+        );
+        assert!(Isotropic::parse("B1k/S").is_err());
+        assert!(Isotropic::parse("B8c/S").is_err());
+        assert!(Isotropic::parse("B3/S2-").is_err());
+        assert!(Isotropic::parse("B3/Sc").is_err());
+        assert!(Isotropic::parse("B3-a").is_err());
+        assert!(Isotropic::parse("B3/S2/B3").is_err());
+    }
+
+    #[test]
+    fn test_looks_like() {
+        assert!(Isotropic::looks_like("B3/S2-i34q"));
+        assert!(Isotropic::looks_like("b2c3aik/s"));
+        assert!(!Isotropic::looks_like("B3/S23"));
+        assert!(!Isotropic::looks_like("B2/S/C3"));
+        assert!(!Isotropic::looks_like("b2s34c3"));
+        assert!(!Isotropic::looks_like("B2/S34H"));
+        assert!(!Isotropic::looks_like("RL"));
+    }
+
+    #[test]
+    fn test_totalistic_matches_life_like() {
+        for rulestring in ["B3/S23", "B36/S23", "B2/S"] {
+            let isotropic = Isotropic::parse(rulestring).unwrap();
+            let life = LifeLike::parse(rulestring).unwrap();
+            let mut world = soup_world(Coord::new(-20, -20), 40, 0.3, 0x2545_f491_4f6c_dd1d);
+            for _ in 0..10 {
+                let next = isotropic.apply(&world);
+                assert_eq!(next, life.step(&world), "{}", rulestring);
+                world = next;
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_naive_lookup() {
+        let rules = [
+            Isotropic::tlife(),
+            Isotropic::parse("B3-cnqy/S23-a4ityz").unwrap(),
+        ];
+        for rule in rules {
+            for topology in [
+                Topology::Plane,
+                Topology::Torus {
+                    width: 30,
+                    height: 20,
+                },
+            ] {
+                let mut world = soup_world(Coord::new(-20, -20), 40, 0.3, 7);
+                world.set_topology(topology);
+                for generation in 0..10 {
+                    let expected = naive_step(&rule, &world);
+                    world = rule.apply(&world);
+                    assert_eq!(world, expected, "{} at {}", rule, generation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tlife_patterns() {
+        let tlife = Isotropic::tlife();
+
+        // блок (3a) и улей (2k, 2c) устойчивы, как в Life
+        let block = world_of(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(tlife.apply(&block), block);
+        let beehive = world_of(&[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)]);
+        assert_eq!(tlife.apply(&beehive), beehive);
+
+        // у центра мигалки соседи напротив друг друга (2i) - она гибнет
+        let blinker = world_of(&[(0, -1), (0, 0), (0, 1)]);
+        let next = tlife.apply(&blinker);
+        assert_eq!(next, world_of(&[(-1, 0), (1, 0)]));
+        assert_eq!(tlife.apply(&next).active_cell_count(), 0);
+
+        // из четырёх соседей клетка выживает только при 4q
+        let center = (0, 0);
+        let q = world_of(&[center, (0, -1), (1, -1), (1, 0), (-1, 1)]);
+        assert!(tlife.apply(&q).get_cell(Coord::new(0, 0)));
+        let z = world_of(&[center, (0, -1), (1, -1), (0, 1), (-1, 1)]);
+        assert!(!tlife.apply(&z).get_cell(Coord::new(0, 0)));
+    }
+}
@@ -0,0 +1,347 @@
+use crate::automaton::generations::decay;
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{step_neighborhood, Neighborhood, World};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Larger than Life в нотации Golly `R5,C0,M1,S34..58,B34..45,NM`: окрестность
+/// радиуса `R` (Мур `NM` или фон Нейман `NN`), `M1` - клетка считает и себя,
+/// `S`/`B` - отрезки числа соседей для выживания и рождения, `C` - число
+/// состояний как в Generations (0 и 2 - обычные два)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargerThanLife {
+    neighborhood: Neighborhood,
+    states: u8,
+    include_center: bool,
+    survival: RangeInclusive<usize>,
+    birth: RangeInclusive<usize>,
+    rulestring: String,
+}
+
+impl LargerThanLife {
+    pub fn new(
+        neighborhood: Neighborhood,
+        states: u8,
+        include_center: bool,
+        survival: RangeInclusive<usize>,
+        birth: RangeInclusive<usize>,
+    ) -> Result<Self, RuleParseError> {
+        let kind = match neighborhood {
+            Neighborhood::Moore(_) => 'M',
+            Neighborhood::VonNeumann(_) => 'N',
+            _ => {
+                return Err(RuleParseError::Malformed(
+                    "Larger than Life supports only NM and NN neighbourhoods".to_string(),
+                ))
+            }
+        };
+        neighborhood
+            .validate()
+            .map_err(|e| RuleParseError::Malformed(e.to_string()))?;
+        if states == 1 {
+            return Err(RuleParseError::Malformed(
+                "state count must be 0 or from 2 to 255".to_string(),
+            ));
+        }
+        if *birth.start() == 0 {
+            return Err(RuleParseError::BirthOnZero);
+        }
+
+        let size = neighborhood.size() + include_center as usize;
+        for range in [&survival, &birth] {
+            if *range.end() > size {
+                return Err(RuleParseError::CountExceedsNeighborhood {
+                    count: (*range.end()).min(u8::MAX as usize) as u8,
+                    size,
+                });
+            }
+        }
+
+        let rulestring = format!(
+            "R{},C{},M{},S{}..{},B{}..{},N{}",
+            neighborhood.radius(),
+            if states > 2 { states } else { 0 },
+            include_center as u8,
+            survival.start(),
+            survival.end(),
+            birth.start(),
+            birth.end(),
+            kind
+        );
+        Ok(Self {
+            neighborhood,
+            states: states.max(2),
+            include_center,
+            survival,
+            birth,
+            rulestring,
+        })
+    }
+
+    /// разбирает строку вида `R5,C0,M1,S34..58,B34..45,NM`; `C`, `M` и `N`
+    /// необязательны (по умолчанию два состояния, без центра, Мур)
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+
+        let mut radius = None;
+        let mut states = 0;
+        let mut include_center = false;
+        let mut survival = None;
+        let mut birth = None;
+        let mut kind = 'M';
+        for token in s.split(',').map(str::trim) {
+            let Some(key) = token.chars().next() else {
+                return Err(RuleParseError::Malformed("empty field".to_string()));
+            };
+            let value = &token[key.len_utf8()..];
+            match key.to_ascii_uppercase() {
+                'R' => radius = Some(parse_number(token, value)?),
+                'C' => {
+                    states = u8::try_from(parse_number(token, value)?).map_err(|_| {
+                        RuleParseError::Malformed("state count must be at most 255".to_string())
+                    })?
+                }
+                'M' => match value {
+                    "0" => include_center = false,
+                    "1" => include_center = true,
+                    _ => return Err(malformed(token)),
+                },
+                'S' => survival = Some(parse_range(token, value)?),
+                'B' => birth = Some(parse_range(token, value)?),
+                'N' => {
+                    kind = match value.to_ascii_uppercase().as_str() {
+                        "M" => 'M',
+                        "N" => 'N',
+                        _ => return Err(malformed(token)),
+                    }
+                }
+                c => return Err(RuleParseError::UnexpectedChar(c)),
+            }
+        }
+
+        let radius =
+            radius.ok_or_else(|| RuleParseError::Malformed("missing radius R".to_string()))?;
+        let radius = u32::try_from(radius).map_err(|_| malformed(s))?;
+        let neighborhood = match kind {
+            'N' => Neighborhood::VonNeumann(radius),
+            _ => Neighborhood::Moore(radius),
+        };
+        let survival =
+            survival.ok_or_else(|| RuleParseError::Malformed("missing survival S".to_string()))?;
+        let birth =
+            birth.ok_or_else(|| RuleParseError::Malformed("missing birth B".to_string()))?;
+        Self::new(neighborhood, states, include_center, survival, birth)
+    }
+
+    /// Bosco's Rule - самое известное правило LtL, с планерами-«жуками»
+    pub fn bosco() -> Self {
+        Self::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap()
+    }
+
+    /// похожа ли строка на правило LtL: `R<радиус>,...`
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim();
+        let mut chars = s.chars();
+        matches!(chars.next(), Some('r' | 'R'))
+            && chars.next().is_some_and(|c| c.is_ascii_digit())
+            && s.contains(',')
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// следующее состояние живой (`alive`) или мёртвой клетки по числу соседей
+    fn next_cell(&self, alive: bool, neighbors: usize) -> bool {
+        let count = neighbors + (self.include_center && alive) as usize;
+        if alive {
+            self.survival.contains(&count)
+        } else {
+            self.birth.contains(&count)
+        }
+    }
+}
+
+fn malformed(token: &str) -> RuleParseError {
+    RuleParseError::Malformed(format!("invalid field '{}'", token))
+}
+
+fn parse_number(token: &str, value: &str) -> Result<usize, RuleParseError> {
+    value.parse().map_err(|_| malformed(token))
+}
+
+/// `34..58`; одно число - отрезок из одного значения
+fn parse_range(token: &str, value: &str) -> Result<RangeInclusive<usize>, RuleParseError> {
+    let (min, max) = value.split_once("..").unwrap_or((value, value));
+    let (min, max) = (parse_number(token, min)?, parse_number(token, max)?);
+    if min > max {
+        return Err(malformed(token));
+    }
+    Ok(min..=max)
+}
+
+impl FromStr for LargerThanLife {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for LargerThanLife {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for LargerThanLife {
+    fn apply(&self, current: &World) -> World {
+        let step = |world: &World| {
+            step_neighborhood(world, &self.neighborhood, |alive, neighbors| {
+                self.next_cell(alive, neighbors)
+            })
+        };
+        if self.states == 2 {
+            return step(current);
+        }
+        decay(current, &step(&current.cells_in_state(1)), self.states)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn state_count(&self) -> u16 {
+        u16::from(self.states)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{soup_world, Coord};
+
+    /// прямой подсчёт по смещениям окрестности для двух состояний
+    fn naive_step(rule: &LargerThanLife, world: &World) -> World {
+        let r = rule.neighborhood.radius() as i32;
+        let offsets = rule.neighborhood.offsets();
+        let mut next = world.empty_like();
+        let Some((min, max)) = world.get_bounds() else {
+            return next;
+        };
+        for y in min.y - r..=max.y + r {
+            for x in min.x - r..=max.x + r {
+                let coord = Coord::new(x, y);
+                let neighbors = offsets
+                    .iter()
+                    .filter(|&&o| world.get_cell(coord + o))
+                    .count();
+                if rule.next_cell(world.get_cell(coord), neighbors) {
+                    next.set_cell(coord, true);
+                }
+            }
+        }
+        next
+    }
+
+    #[test]
+    fn test_parse() {
+        let bosco = LargerThanLife::parse("r5,c0,m1,s34..58,b34..45,nm").unwrap();
+        assert_eq!(bosco, LargerThanLife::bosco());
+        assert_eq!(bosco.rulestring(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!(bosco.neighborhood(), Neighborhood::Moore(5));
+        assert_eq!(LargerThanLife::parse(bosco.rulestring()).unwrap(), bosco);
+
+        let rule = LargerThanLife::parse("R2,C3,S2..4,B3,NN").unwrap();
+        assert_eq!(rule.rulestring(), "R2,C3,M0,S2..4,B3..3,NN");
+        assert_eq!(rule.state_count(), 3);
+        assert_eq!(
+            LargerThanLife::parse("R1,C2,M0,S2..3,B3..3")
+                .unwrap()
+                .rulestring(),
+            "R1,C0,M0,S2..3,B3..3,NM"
+        );
+
+        assert_eq!(LargerThanLife::parse(""), Err(RuleParseError::Empty));
+        assert_eq!(
+            LargerThanLife::parse("R1,S2..3,B0..3"),
+            Err(RuleParseError::BirthOnZero)
+        );
+        assert_eq!(
+            LargerThanLife::parse("R1,S2..9,B3"),
+            Err(RuleParseError::CountExceedsNeighborhood { count: 9, size: 8 })
+        );
+        assert!(LargerThanLife::parse("R1,M1,S2..9,B3").is_ok());
+        assert!(LargerThanLife::parse("R0,S1,B1").is_err());
+        assert!(LargerThanLife::parse("R2,S5..3,B3").is_err());
+        assert!(LargerThanLife::parse("R2,B3").is_err());
+        assert!(LargerThanLife::parse("R2,S3,B3,NX").is_err());
+        assert!(LargerThanLife::parse("R2,S3,B3,X1").is_err());
+
+        assert!(LargerThanLife::looks_like("R5,C0,M1,S34..58,B34..45,NM"));
+        assert!(!LargerThanLife::looks_like("RL"));
+        assert!(!LargerThanLife::looks_like("Rule 30"));
+        assert!(!LargerThanLife::looks_like("B3/S23"));
+    }
+
+    #[test]
+    fn test_matches_naive_count() {
+        let rules = [
+            "R5,C0,M1,S34..58,B34..45,NM",
+            "R1,C0,M0,S2..3,B3..3,NM",
+            "R3,C0,M0,S6..14,B7..9,NN",
+            "R2,C0,M1,S5..12,B6..8,NM",
+        ];
+        for rulestring in rules {
+            let rule = LargerThanLife::parse(rulestring).unwrap();
+            let mut world = soup_world(Coord::new(-12, -12), 24, 0.5, 0x9e37_79b9);
+            for generation in 0..5 {
+                let expected = naive_step(&rule, &world);
+                world = rule.apply(&world);
+                assert_eq!(world, expected, "{} at {}", rulestring, generation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_conway_as_larger_than_life() {
+        let ltl = LargerThanLife::parse("R1,C0,M1,S3..4,B3..3,NM").unwrap();
+        let life = crate::automaton::LifeLike::conway();
+        let mut world = soup_world(Coord::new(-10, -10), 20, 0.5, 0x9e37_79b9);
+        for _ in 0..10 {
+            let next = ltl.apply(&world);
+            assert_eq!(next, life.step(&world));
+            world = next;
+        }
+    }
+
+    #[test]
+    fn test_multi_state_decays() {
+        let rule = LargerThanLife::parse("R2,C4,M0,S20..24,B6..8,NM").unwrap();
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 1);
+        world.set_state(Coord::new(5, 5), 3);
+
+        // одинокая клетка не выживает и стареет, последнее состояние угасает
+        let next = rule.apply(&world);
+        assert_eq!(next.get_state(Coord::new(0, 0)), 2);
+        assert_eq!(next.get_state(Coord::new(5, 5)), 0);
+    }
+}
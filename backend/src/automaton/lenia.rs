@@ -0,0 +1,541 @@
+use crate::automaton::fft::{fft2, Complex};
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::{ContinuousRule, Rule};
+use crate::world::{Field, Topology, World, MAX_FIELD_AREA};
+use rayon::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// наибольший радиус ядра Lenia
+pub const MAX_LENIA_RADIUS: u32 = 64;
+
+/// спектр ядра для поля определённого размера
+#[derive(Debug)]
+struct KernelSpectrum {
+    width: usize,
+    height: usize,
+    values: Vec<Complex>,
+}
+
+/// Lenia Берта Чана: непрерывный автомат, где потенциал клетки - свёртка поля
+/// с кольцевым ядром радиуса `R`, а значение растёт на `dt * G(потенциал)`
+/// с колоколом роста `G` с центром `mu` и шириной `sigma`
+#[derive(Debug)]
+pub struct Lenia {
+    radius: u32,
+    time_scale: u32,
+    mu: f64,
+    sigma: f64,
+    peaks: Vec<f64>,
+    /// ненулевые веса нормированного ядра: `(dx, dy, вес)`
+    taps: Vec<(i32, i32, f64)>,
+    /// спектр ядра под последний размер поля, чтобы не считать его каждый шаг
+    spectrum: Mutex<Option<Arc<KernelSpectrum>>>,
+    rulestring: String,
+}
+
+impl Lenia {
+    /// `radius` - радиус ядра в клетках, `time_scale` - число шагов на единицу
+    /// времени (dt = 1 / T), `peaks` - высоты колец ядра от центра наружу
+    pub fn new(
+        radius: u32,
+        time_scale: u32,
+        mu: f64,
+        sigma: f64,
+        peaks: Vec<f64>,
+    ) -> Result<Self, RuleParseError> {
+        if !(1..=MAX_LENIA_RADIUS).contains(&radius) {
+            return Err(RuleParseError::Malformed(format!(
+                "kernel radius must be from 1 to {}",
+                MAX_LENIA_RADIUS
+            )));
+        }
+        if time_scale == 0 {
+            return Err(RuleParseError::Malformed(
+                "time scale T must be at least 1".to_string(),
+            ));
+        }
+        if !(sigma > 0.0 && sigma.is_finite() && mu.is_finite()) {
+            return Err(RuleParseError::Malformed(
+                "growth needs a finite mu and a positive sigma".to_string(),
+            ));
+        }
+        if peaks.is_empty()
+            || peaks.iter().any(|p| !(0.0..=1.0).contains(p))
+            || peaks.iter().all(|&p| p == 0.0)
+        {
+            return Err(RuleParseError::Malformed(
+                "kernel peaks must be from 0 to 1 and not all zero".to_string(),
+            ));
+        }
+
+        let taps = kernel_taps(radius, &peaks);
+        if taps.is_empty() {
+            return Err(RuleParseError::Malformed(format!(
+                "kernel of radius {} with {} rings has no cells",
+                radius,
+                peaks.len()
+            )));
+        }
+        let peak_list: Vec<String> = peaks.iter().map(f64::to_string).collect();
+        let rulestring = format!(
+            "Lenia/R{}/T{}/M{}/S{}/B{}",
+            radius,
+            time_scale,
+            mu,
+            sigma,
+            peak_list.join(",")
+        );
+        Ok(Self {
+            radius,
+            time_scale,
+            mu,
+            sigma,
+            peaks,
+            taps,
+            spectrum: Mutex::new(None),
+            rulestring,
+        })
+    }
+
+    /// разбирает `Lenia/R13/T10/M0.15/S0.015/B1`; кольца ядра `B` через запятую
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let body = strip_marker(s).ok_or_else(|| {
+            RuleParseError::Malformed(
+                "expected notation like Lenia/R13/T10/M0.15/S0.015/B1".to_string(),
+            )
+        })?;
+
+        let mut radius = None;
+        let mut time_scale = None;
+        let mut mu = None;
+        let mut sigma = None;
+        let mut peaks = None;
+        for token in body.split('/').map(str::trim) {
+            let Some(key) = token.chars().next() else {
+                return Err(RuleParseError::Malformed("empty field".to_string()));
+            };
+            let value = &token[key.len_utf8()..];
+            let invalid = || RuleParseError::Malformed(format!("invalid number in '{}'", token));
+            match key.to_ascii_uppercase() {
+                'R' => radius = Some(value.parse::<u32>().map_err(|_| invalid())?),
+                'T' => time_scale = Some(value.parse::<u32>().map_err(|_| invalid())?),
+                'M' => mu = Some(value.parse::<f64>().map_err(|_| invalid())?),
+                'S' => sigma = Some(value.parse::<f64>().map_err(|_| invalid())?),
+                'B' => {
+                    peaks = Some(
+                        value
+                            .split(',')
+                            .map(|peak| peak.trim().parse::<f64>().map_err(|_| invalid()))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                }
+                c => return Err(RuleParseError::UnexpectedChar(c)),
+            }
+        }
+
+        let missing = |field: &str| RuleParseError::Malformed(format!("missing {}", field));
+        Self::new(
+            radius.ok_or_else(|| missing("kernel radius R"))?,
+            time_scale.unwrap_or(10),
+            mu.ok_or_else(|| missing("growth centre M"))?,
+            sigma.ok_or_else(|| missing("growth width S"))?,
+            peaks.unwrap_or_else(|| vec![1.0]),
+        )
+    }
+
+    /// правило Orbium - самого известного «существа» Lenia
+    pub fn orbium() -> Self {
+        Self::parse("Lenia/R13/T10/M0.15/S0.015/B1").unwrap()
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    pub fn dt(&self) -> f64 {
+        1.0 / f64::from(self.time_scale)
+    }
+
+    pub fn peaks(&self) -> &[f64] {
+        &self.peaks
+    }
+
+    /// начинается ли строка с `Lenia/`
+    pub fn looks_like(rulestring: &str) -> bool {
+        strip_marker(rulestring.trim()).is_some()
+    }
+
+    /// колокол роста: 1 при потенциале `mu`, -1 вдали от него
+    pub fn growth(&self, potential: f64) -> f64 {
+        let d = (potential - self.mu) / self.sigma;
+        2.0 * (-d * d / 2.0).exp() - 1.0
+    }
+
+    /// потенциал каждой клетки: свёртка поля с ядром на торе; для сторон
+    /// из степеней двойки через БПФ, иначе напрямую по весам ядра
+    pub fn potential(&self, field: &Field) -> Vec<f64> {
+        let (width, height) = (field.width() as usize, field.height() as usize);
+        if width.is_power_of_two() && height.is_power_of_two() {
+            self.potential_fft(field)
+        } else {
+            self.potential_direct(field)
+        }
+    }
+
+    fn potential_fft(&self, field: &Field) -> Vec<f64> {
+        let (width, height) = (field.width() as usize, field.height() as usize);
+        let spectrum = self.spectrum_for(width, height);
+        let mut data: Vec<Complex> = field
+            .values()
+            .iter()
+            .map(|&v| Complex::new(f64::from(v), 0.0))
+            .collect();
+        fft2(&mut data, width, height, false);
+        for (value, &kernel) in data.iter_mut().zip(&spectrum.values) {
+            *value = *value * kernel;
+        }
+        fft2(&mut data, width, height, true);
+        data.into_iter().map(|value| value.re).collect()
+    }
+
+    fn potential_direct(&self, field: &Field) -> Vec<f64> {
+        let (width, height) = (field.width() as i32, field.height() as i32);
+        let values = field.values();
+        (0..width * height)
+            .into_par_iter()
+            .map(|index| {
+                let (x, y) = (index % width, index / width);
+                self.taps
+                    .iter()
+                    .map(|&(dx, dy, weight)| {
+                        let nx = (x + dx).rem_euclid(width);
+                        let ny = (y + dy).rem_euclid(height);
+                        weight * f64::from(values[(ny * width + nx) as usize])
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn spectrum_for(&self, width: usize, height: usize) -> Arc<KernelSpectrum> {
+        let mut cached = self.spectrum.lock().unwrap();
+        if let Some(spectrum) = cached.as_ref() {
+            if spectrum.width == width && spectrum.height == height {
+                return spectrum.clone();
+            }
+        }
+
+        // ядро кладётся центром в (0, 0), соседи с отрицательными смещениями
+        // заворачиваются к дальним краям; на маленьком поле веса складываются
+        let mut values = vec![Complex::default(); width * height];
+        for &(dx, dy, weight) in &self.taps {
+            let x = dx.rem_euclid(width as i32) as usize;
+            let y = dy.rem_euclid(height as i32) as usize;
+            values[y * width + x].re += weight;
+        }
+        fft2(&mut values, width, height, false);
+        let spectrum = Arc::new(KernelSpectrum {
+            width,
+            height,
+            values,
+        });
+        *cached = Some(spectrum.clone());
+        spectrum
+    }
+}
+
+fn strip_marker(s: &str) -> Option<&str> {
+    let (marker, body) = s.split_once('/')?;
+    marker.trim().eq_ignore_ascii_case("lenia").then_some(body)
+}
+
+/// кольцевое ядро: расстояние `r` в долях радиуса делится на кольца по числу
+/// пиков, внутри кольца гладкий горб `exp(4 - 1 / (r (1 - r)))`; сумма весов - 1
+fn kernel_taps(radius: u32, peaks: &[f64]) -> Vec<(i32, i32, f64)> {
+    let r = radius as i32;
+    let rings = peaks.len() as f64;
+    let mut taps = Vec::new();
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let distance = f64::from(dx * dx + dy * dy).sqrt() / f64::from(radius);
+            if distance >= 1.0 {
+                continue;
+            }
+            let scaled = distance * rings;
+            let ring = scaled.floor();
+            let local = scaled - ring;
+            if local <= 0.0 {
+                continue;
+            }
+            let weight = peaks[ring as usize] * (4.0 - 1.0 / (local * (1.0 - local))).exp();
+            if weight > 0.0 {
+                taps.push((dx, dy, weight));
+            }
+        }
+    }
+    let total: f64 = taps.iter().map(|&(_, _, weight)| weight).sum();
+    for tap in &mut taps {
+        tap.2 /= total;
+    }
+    taps
+}
+
+impl FromStr for Lenia {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Lenia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl ContinuousRule for Lenia {
+    fn step_field(&self, field: &Field) -> Field {
+        let potential = self.potential(field);
+        let dt = self.dt();
+        let mut next = field.clone();
+        next.values_mut()
+            .par_iter_mut()
+            .zip(potential.par_iter())
+            .for_each(|(value, &u)| {
+                let grown = f64::from(*value) + dt * self.growth(u);
+                *value = grown.clamp(0.0, 1.0) as f32;
+            });
+        next
+    }
+}
+
+impl Rule for Lenia {
+    /// шаг по квантованным состояниям мира; `Simulation` вместо этого хранит
+    /// поле между шагами без потерь. Без поля (на бесконечной плоскости) мир
+    /// остаётся как был, но такие миры отсекает `check_topology`
+    fn apply(&self, current: &World) -> World {
+        match Field::from_world(current) {
+            Some(field) => self.step_field(&field).to_world(current.topology()),
+            None => current.clone(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    /// свёртка через БПФ всегда склеивает края поля, поэтому годится только тор
+    fn check_topology(&self, topology: Topology) -> Result<(), String> {
+        match topology {
+            Topology::Torus { width, height }
+                if u64::from(width) * u64::from(height) > MAX_FIELD_AREA =>
+            {
+                Err(format!(
+                    "{} needs a world of at most {} cells, got {}x{}",
+                    self.rulestring, MAX_FIELD_AREA, width, height
+                ))
+            }
+            Topology::Torus { .. } => Ok(()),
+            _ => Err(format!("{} needs a torus world", self.rulestring)),
+        }
+    }
+
+    /// квантованные значения занимают все 256 байтов
+    fn state_count(&self) -> u16 {
+        256
+    }
+
+    fn continuous(&self) -> Option<&dyn ContinuousRule> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::Preset;
+    use crate::world::{Coord, Rng};
+
+    fn random_field(width: u32, height: u32, seed: u64) -> Field {
+        let mut rng = Rng::new(seed);
+        let mut field = Field::new(width, height);
+        for value in field.values_mut() {
+            *value = rng.next_f64() as f32;
+        }
+        field
+    }
+
+    /// центр масс на торе через средние углы по каждой оси
+    fn centroid(field: &Field) -> (f64, f64) {
+        let axis = |size: u32, coordinate: fn(usize, u32) -> u32| {
+            let (mut sin, mut cos) = (0.0, 0.0);
+            for (index, &v) in field.values().iter().enumerate() {
+                let angle =
+                    2.0 * std::f64::consts::PI * f64::from(coordinate(index, field.width()))
+                        / f64::from(size);
+                sin += f64::from(v) * angle.sin();
+                cos += f64::from(v) * angle.cos();
+            }
+            let angle = f64::atan2(sin, cos).rem_euclid(2.0 * std::f64::consts::PI);
+            angle * f64::from(size) / (2.0 * std::f64::consts::PI)
+        };
+        (
+            axis(field.width(), |index, width| index as u32 % width),
+            axis(field.height(), |index, width| index as u32 / width),
+        )
+    }
+
+    #[test]
+    fn test_parse_notation() {
+        let orbium = Lenia::orbium();
+        assert_eq!(orbium.rulestring(), "Lenia/R13/T10/M0.15/S0.015/B1");
+        assert_eq!(orbium.dt(), 0.1);
+
+        let rule = Lenia::parse("lenia/r10/t5/m0.2/s0.02/b1,0.5").unwrap();
+        assert_eq!(rule.rulestring(), "Lenia/R10/T5/M0.2/S0.02/B1,0.5");
+        assert_eq!(rule.peaks(), [1.0, 0.5]);
+        assert!(Lenia::looks_like("LENIA/R13/M0.15/S0.015"));
+        assert!(!Lenia::looks_like("R13/T10/C3/NM"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(Lenia::parse(""), Err(RuleParseError::Empty)));
+        assert!(Lenia::parse("Lenia/R0/M0.15/S0.015").is_err());
+        assert!(Lenia::parse("Lenia/R13/M0.15/S0").is_err());
+        assert!(Lenia::parse("Lenia/R13/M0.15/S0.015/B0").is_err());
+        assert!(Lenia::parse("Lenia/R13/S0.015").is_err());
+        assert!(Lenia::parse("Lenia/R13/M0.15/S0.015/X1").is_err());
+        assert!(Lenia::parse("R13/M0.15/S0.015").is_err());
+        // у ядра радиуса 1 есть только центр, а на нём вес кольца нулевой
+        assert!(Lenia::parse("Lenia/R1/M0.15/S0.015").is_err());
+        assert!(Lenia::parse("Lenia/R2/M0.15/S0.015").is_ok());
+    }
+
+    #[test]
+    fn test_kernel_is_a_normalised_ring() {
+        let taps = kernel_taps(13, &[1.0]);
+        let total: f64 = taps.iter().map(|&(_, _, w)| w).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        assert!(taps.iter().all(|&(dx, dy, _)| dx * dx + dy * dy < 169));
+        assert!(!taps.iter().any(|&(dx, dy, _)| dx == 0 && dy == 0));
+
+        // горб кольца - на середине радиуса
+        let weight = |x: i32| {
+            taps.iter()
+                .find(|&&(dx, dy, _)| dx == x && dy == 0)
+                .map_or(0.0, |&(_, _, w)| w)
+        };
+        assert!(weight(6) > weight(2));
+        assert!(weight(6) > weight(11));
+    }
+
+    #[test]
+    fn test_growth_bell() {
+        let rule = Lenia::orbium();
+        assert!((rule.growth(0.15) - 1.0).abs() < 1e-12);
+        assert!((rule.growth(0.5) + 1.0).abs() < 1e-12);
+        assert!(rule.growth(0.16).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_fft_matches_direct_convolution() {
+        let rule = Lenia::parse("Lenia/R5/M0.15/S0.015/B1,0.3").unwrap();
+        let field = random_field(32, 16, 4);
+        let fast = rule.potential_fft(&field);
+        let direct = rule.potential_direct(&field);
+        assert!(fast.iter().zip(&direct).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        // поле не из степеней двойки считается напрямую
+        let odd = random_field(30, 18, 5);
+        assert_eq!(rule.potential(&odd), rule.potential_direct(&odd));
+    }
+
+    #[test]
+    fn test_orbium_glides() {
+        let preset = Preset::orbium();
+        let rule = Lenia::parse(preset.rule.as_deref().unwrap()).unwrap();
+        let mut field = Field::from_world(&preset.to_world()).unwrap();
+        let (mass, start) = (field.mass(), centroid(&field));
+
+        for _ in 0..100 {
+            field = rule.step_field(&field);
+        }
+
+        // существо держит форму и уезжает на несколько клеток
+        let ratio = field.mass() / mass;
+        assert!((0.8..1.25).contains(&ratio), "mass ratio {}", ratio);
+        let end = centroid(&field);
+        let shift = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+        assert!(shift > 2.0, "shift {}", shift);
+    }
+
+    #[test]
+    fn test_plane_world_is_left_alone() {
+        let mut world = World::new();
+        world.set_state(Coord::new(0, 0), 200);
+        assert_eq!(Lenia::orbium().apply(&world), world);
+    }
+
+    #[test]
+    fn test_rejects_huge_worlds() {
+        let rule = Lenia::orbium();
+        let side = 1 << 11;
+        assert!(rule
+            .check_topology(Topology::Torus {
+                width: side,
+                height: side,
+            })
+            .is_ok());
+        assert!(rule
+            .check_topology(Topology::Torus {
+                width: side * 2,
+                height: side,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_states_cover_every_byte() {
+        let rule = Lenia::orbium();
+        assert_eq!(rule.state_count(), 256);
+        assert!((0..=u8::MAX).all(|state| u16::from(state) < rule.state_count()));
+    }
+
+    #[test]
+    fn test_rejects_worlds_other_than_torus() {
+        let rule = Lenia::orbium();
+        assert!(rule.check_topology(Topology::Plane).is_err());
+        for topology in [
+            Topology::Bounded {
+                width: 64,
+                height: 64,
+            },
+            Topology::Cylinder {
+                width: 64,
+                height: 64,
+            },
+            Topology::KleinBottle {
+                width: 64,
+                height: 64,
+            },
+        ] {
+            assert!(rule.check_topology(topology).is_err(), "{:?}", topology);
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use crate::automaton::Rule;
+use crate::world::{step_neighborhood, step_totalistic_parallel, Neighborhood, World};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// ошибка разбора строки правила
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RuleParseError {
+    #[error("rulestring is empty")]
+    Empty,
+    #[error("invalid neighbour count '{0}': must be a digit from 0 to 8")]
+    InvalidCount(char),
+    #[error("unexpected character '{0}' in rulestring")]
+    UnexpectedChar(char),
+    #[error("malformed rulestring: {0}")]
+    Malformed(String),
+    #[error("B0 rules are not supported on an unbounded world")]
+    BirthOnZero,
+    #[error("neighbour count {count} exceeds the {size} cells of the neighbourhood")]
+    CountExceedsNeighborhood { count: u8, size: usize },
+}
+
+/// Life-like правило: множества соседей для рождения (B) и выживания (S)
+/// в окрестности до 8 клеток - Мура, фон Неймана (`V`) или шестиугольной (`H`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifeLike {
+    birth: [bool; 9],
+    survival: [bool; 9],
+    neighborhood: Neighborhood,
+    rulestring: String,
+}
+
+impl LifeLike {
+    pub fn new(birth: &[u8], survival: &[u8]) -> Result<Self, RuleParseError> {
+        Self::with_neighborhood(birth, survival, Neighborhood::default())
+    }
+
+    pub fn with_neighborhood(
+        birth: &[u8],
+        survival: &[u8],
+        neighborhood: Neighborhood,
+    ) -> Result<Self, RuleParseError> {
+        neighborhood
+            .validate()
+            .map_err(|e| RuleParseError::Malformed(e.to_string()))?;
+        let size = neighborhood.size();
+        if size > 8 {
+            return Err(RuleParseError::Malformed(format!(
+                "Life-like rules need at most 8 neighbours, got {}",
+                size
+            )));
+        }
+        if let Some(&count) = birth.iter().chain(survival).find(|&&c| c as usize > size) {
+            return Err(if count <= 8 {
+                RuleParseError::CountExceedsNeighborhood { count, size }
+            } else {
+                RuleParseError::InvalidCount(digit_char(count))
+            });
+        }
+
+        let mut b = [false; 9];
+        let mut s = [false; 9];
+
+        for &count in birth {
+            *b.get_mut(count as usize)
+                .ok_or(RuleParseError::InvalidCount(digit_char(count)))? = true;
+        }
+        for &count in survival {
+            *s.get_mut(count as usize)
+                .ok_or(RuleParseError::InvalidCount(digit_char(count)))? = true;
+        }
+
+        if b[0] {
+            return Err(RuleParseError::BirthOnZero);
+        }
+
+        let mut rulestring = format_rulestring(&b, &s);
+        rulestring.extend(neighborhood.suffix());
+        Ok(Self {
+            birth: b,
+            survival: s,
+            neighborhood,
+            rulestring,
+        })
+    }
+
+    /// разбирает `B36/S23`, `b3s23` или `23/3` (S/B); буква `H` или `V`
+    /// в конце задаёт шестиугольную окрестность или окрестность фон Неймана
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let (rest, neighborhood) = split_neighborhood(rulestring.trim());
+        let (birth, survival) = parse_counts(rest)?;
+        Self::with_neighborhood(&birth, &survival, neighborhood)
+    }
+
+    pub fn conway() -> Self {
+        Self::parse("B3/S23").unwrap()
+    }
+
+    pub fn highlife() -> Self {
+        Self::parse("B36/S23").unwrap()
+    }
+
+    pub fn day_and_night() -> Self {
+        Self::parse("B3678/S34678").unwrap()
+    }
+
+    pub fn seeds() -> Self {
+        Self::parse("B2/S").unwrap()
+    }
+
+    pub fn replicator() -> Self {
+        Self::parse("B1357/S1357").unwrap()
+    }
+
+    /// каноническая запись в виде `B.../S...`
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn birth_counts(&self) -> Vec<u8> {
+        set_counts(&self.birth)
+    }
+
+    pub fn survival_counts(&self) -> Vec<u8> {
+        set_counts(&self.survival)
+    }
+
+    pub fn births_on(&self, neighbors: u8) -> bool {
+        self.birth.get(neighbors as usize).copied().unwrap_or(false)
+    }
+
+    pub fn survives_on(&self, neighbors: u8) -> bool {
+        self.survival.get(neighbors as usize).copied().unwrap_or(false)
+    }
+
+    /// маска рождений для побитового шага: бит n - рождение при n соседях
+    pub fn birth_mask(&self) -> u16 {
+        counts_mask(&self.birth)
+    }
+
+    pub fn survival_mask(&self) -> u16 {
+        counts_mask(&self.survival)
+    }
+
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighborhood
+    }
+
+    /// одно поколение: побитовый шаг для Мура, иначе общий по окрестности
+    pub fn step(&self, world: &World) -> World {
+        if self.neighborhood.is_moore() {
+            return step_totalistic_parallel(world, self.birth_mask(), self.survival_mask());
+        }
+        step_neighborhood(world, &self.neighborhood, |alive, count| {
+            if alive {
+                self.survives_on(count as u8)
+            } else {
+                self.births_on(count as u8)
+            }
+        })
+    }
+}
+
+impl Default for LifeLike {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+impl FromStr for LifeLike {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for LifeLike {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for LifeLike {
+    fn apply(&self, current: &World) -> World {
+        self.step(current)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood.clone()
+    }
+}
+
+/// отделяет букву окрестности в конце строки правила
+pub(crate) fn split_neighborhood(rulestring: &str) -> (&str, Neighborhood) {
+    match rulestring.char_indices().last() {
+        Some((index, c)) if c.eq_ignore_ascii_case(&'h') || c.eq_ignore_ascii_case(&'v') => (
+            &rulestring[..index],
+            Neighborhood::from_suffix(c).unwrap_or_default(),
+        ),
+        _ => (rulestring, Neighborhood::default()),
+    }
+}
+
+fn parse_counts(rulestring: &str) -> Result<(Vec<u8>, Vec<u8>), RuleParseError> {
+    let s = rulestring.trim().to_ascii_lowercase();
+    if s.is_empty() {
+        return Err(RuleParseError::Empty);
+    }
+
+    if s.contains(['b', 's']) {
+        parse_bs_notation(&s)
+    } else {
+        parse_sb_notation(&s)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Birth,
+    Survival,
+}
+
+/// `B36/S23`, `b3s23`, `S23/B3`
+fn parse_bs_notation(s: &str) -> Result<(Vec<u8>, Vec<u8>), RuleParseError> {
+    let mut birth = Vec::new();
+    let mut survival = Vec::new();
+    let mut section: Option<Section> = None;
+    let mut seen_birth = false;
+    let mut seen_survival = false;
+
+    for c in s.chars() {
+        match c {
+            'b' => {
+                if seen_birth {
+                    return Err(RuleParseError::Malformed("duplicate B section".to_string()));
+                }
+                seen_birth = true;
+                section = Some(Section::Birth);
+            }
+            's' => {
+                if seen_survival {
+                    return Err(RuleParseError::Malformed("duplicate S section".to_string()));
+                }
+                seen_survival = true;
+                section = Some(Section::Survival);
+            }
+            '/' => {
+                if section.is_none() {
+                    return Err(RuleParseError::Malformed(
+                        "separator before any section".to_string(),
+                    ));
+                }
+            }
+            c if c.is_ascii_digit() => {
+                let count = parse_count(c)?;
+                match section {
+                    Some(Section::Birth) => birth.push(count),
+                    Some(Section::Survival) => survival.push(count),
+                    None => {
+                        return Err(RuleParseError::Malformed(
+                            "neighbour count outside of B/S section".to_string(),
+                        ))
+                    }
+                }
+            }
+            other => return Err(RuleParseError::UnexpectedChar(other)),
+        }
+    }
+
+    if !seen_birth || !seen_survival {
+        return Err(RuleParseError::Malformed(
+            "both B and S sections are required".to_string(),
+        ));
+    }
+
+    Ok((birth, survival))
+}
+
+/// классическая запись `23/3`: сначала выживание, потом рождение
+fn parse_sb_notation(s: &str) -> Result<(Vec<u8>, Vec<u8>), RuleParseError> {
+    let (survival_part, birth_part) = s.split_once('/').ok_or_else(|| {
+        RuleParseError::Malformed("expected S/B notation like 23/3".to_string())
+    })?;
+
+    let parse_part = |part: &str| -> Result<Vec<u8>, RuleParseError> {
+        part.chars()
+            .map(|c| {
+                if c.is_ascii_digit() {
+                    parse_count(c)
+                } else {
+                    Err(RuleParseError::UnexpectedChar(c))
+                }
+            })
+            .collect()
+    };
+
+    Ok((parse_part(birth_part)?, parse_part(survival_part)?))
+}
+
+fn parse_count(c: char) -> Result<u8, RuleParseError> {
+    match c.to_digit(10) {
+        Some(d) if d <= 8 => Ok(d as u8),
+        _ => Err(RuleParseError::InvalidCount(c)),
+    }
+}
+
+fn digit_char(count: u8) -> char {
+    char::from_digit(count as u32, 10).unwrap_or('?')
+}
+
+fn set_counts(counts: &[bool; 9]) -> Vec<u8> {
+    (0..9u8).filter(|&n| counts[n as usize]).collect()
+}
+
+fn counts_mask(counts: &[bool; 9]) -> u16 {
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, &on)| on)
+        .fold(0, |mask, (count, _)| mask | 1 << count)
+}
+
+fn format_rulestring(birth: &[bool; 9], survival: &[bool; 9]) -> String {
+    let digits =
+        |set: &[bool; 9]| -> String { set_counts(set).into_iter().map(digit_char).collect() };
+    format!("B{}/S{}", digits(birth), digits(survival))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::GameOfLife;
+    use crate::world::{soup_world, Coord};
+
+    #[test]
+    fn test_parse_bs_notation() {
+        let rule = LifeLike::parse("B36/S23").unwrap();
+        assert_eq!(rule.rulestring(), "B36/S23");
+        assert!(rule.births_on(3));
+        assert!(rule.births_on(6));
+        assert!(!rule.births_on(2));
+        assert!(rule.survives_on(2));
+        assert!(rule.survives_on(3));
+        assert!(!rule.survives_on(4));
+    }
+
+    #[test]
+    fn test_parse_variants_are_equivalent() {
+        let canonical = LifeLike::parse("B3/S23").unwrap();
+        assert_eq!(LifeLike::parse("b3s23").unwrap(), canonical);
+        assert_eq!(LifeLike::parse("23/3").unwrap(), canonical);
+        assert_eq!(LifeLike::parse("S23/B3").unwrap(), canonical);
+        assert_eq!(LifeLike::parse("  b3/s32 ").unwrap(), canonical);
+    }
+
+    #[test]
+    fn test_parse_empty_sections() {
+        let seeds = LifeLike::parse("B2/S").unwrap();
+        assert_eq!(seeds, LifeLike::seeds());
+        assert_eq!(LifeLike::parse("/2").unwrap(), seeds);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(LifeLike::parse(""), Err(RuleParseError::Empty));
+        assert_eq!(LifeLike::parse("B39/S23"), Err(RuleParseError::InvalidCount('9')));
+        assert_eq!(LifeLike::parse("B3/S2x"), Err(RuleParseError::UnexpectedChar('x')));
+        assert_eq!(LifeLike::parse("B0/S8"), Err(RuleParseError::BirthOnZero));
+        assert!(matches!(LifeLike::parse("B3"), Err(RuleParseError::Malformed(_))));
+        assert!(matches!(LifeLike::parse("B3/B3/S23"), Err(RuleParseError::Malformed(_))));
+        assert!(matches!(LifeLike::parse("233"), Err(RuleParseError::Malformed(_))));
+        assert!("B3/S23".parse::<LifeLike>().is_ok());
+    }
+
+    #[test]
+    fn test_conway_matches_game_of_life() {
+        let life = LifeLike::conway();
+        let gol = GameOfLife::new();
+
+        let mut world = World::new();
+        for &(x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (10, 10), (11, 10), (12, 10)] {
+            world.set_cell(Coord::new(x, y), true);
+        }
+
+        let mut a = world.clone();
+        let mut b = world;
+        for _ in 0..8 {
+            a = life.apply(&a);
+            b = gol.apply(&b);
+        }
+
+        let mut cells_a: Vec<_> = a.iter_active_cells().map(|c| (c.x, c.y)).collect();
+        let mut cells_b: Vec<_> = b.iter_active_cells().map(|c| (c.x, c.y)).collect();
+        cells_a.sort();
+        cells_b.sort();
+        assert_eq!(cells_a, cells_b);
+    }
+
+    #[test]
+    fn test_highlife_birth_on_six() {
+        let rule = LifeLike::highlife();
+        let mut world = World::new();
+
+        for &(x, y) in &[(-1, -1), (0, -1), (1, -1), (-1, 1), (0, 1), (1, 1)] {
+            world.set_cell(Coord::new(x, y), true);
+        }
+
+        let next = rule.apply(&world);
+        assert!(next.get_cell(Coord::new(0, 0)));
+
+        let next_life = LifeLike::conway().apply(&world);
+        assert!(!next_life.get_cell(Coord::new(0, 0)));
+    }
+
+    #[test]
+    fn test_seeds_nothing_survives() {
+        let rule = LifeLike::seeds();
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(1, 0), true);
+
+        let next = rule.apply(&world);
+        assert!(!next.get_cell(Coord::new(0, 0)));
+        assert!(!next.get_cell(Coord::new(1, 0)));
+        assert_eq!(next.active_cell_count(), 4);
+    }
+
+    #[test]
+    fn test_name_is_canonical_rulestring() {
+        let rule = LifeLike::parse("23/36").unwrap();
+        assert_eq!(rule.name(), "B36/S23");
+        assert_eq!(rule.to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn test_parse_neighborhood_suffix() {
+        let hex = LifeLike::parse("b2s34h").unwrap();
+        assert_eq!(hex.rulestring(), "B2/S34H");
+        assert_eq!(hex.neighborhood(), &Neighborhood::Hexagonal);
+        assert_eq!(LifeLike::parse("34/2H").unwrap(), hex);
+
+        let von_neumann = LifeLike::parse("B2/S013V").unwrap();
+        assert_eq!(von_neumann.rulestring(), "B2/S013V");
+        assert_eq!(von_neumann.neighborhood(), &Neighborhood::VonNeumann(1));
+        assert_eq!(
+            LifeLike::parse(von_neumann.rulestring()).unwrap(),
+            von_neumann
+        );
+
+        assert_eq!(
+            LifeLike::parse("B5/S23V"),
+            Err(RuleParseError::CountExceedsNeighborhood { count: 5, size: 4 })
+        );
+        assert_eq!(
+            LifeLike::parse("B2/S7H"),
+            Err(RuleParseError::CountExceedsNeighborhood { count: 7, size: 6 })
+        );
+    }
+
+    #[test]
+    fn test_single_cell_births_follow_neighborhood() {
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+
+        let von_neumann = LifeLike::parse("B1/SV").unwrap().apply(&world);
+        let mut cells: Vec<_> = von_neumann
+            .iter_active_cells()
+            .map(|c| (c.x, c.y))
+            .collect();
+        cells.sort();
+        assert_eq!(cells, vec![(-1, 0), (0, -1), (0, 1), (1, 0)]);
+
+        let hex = LifeLike::parse("B1/SH").unwrap().apply(&world);
+        assert_eq!(hex.active_cell_count(), 6);
+        assert!(hex.get_cell(Coord::new(1, 1)));
+        assert!(!hex.get_cell(Coord::new(1, -1)));
+    }
+
+    #[test]
+    fn test_neighborhood_rules_match_naive() {
+        let world = soup_world(Coord::new(-20, -20), 40, 0.3, 0x9e37_79b9_7f4a_7c15);
+
+        for rulestring in ["B2/S34H", "B2/S013V", "B3/S23"] {
+            let rule = LifeLike::parse(rulestring).unwrap();
+            let neighborhood = rule.neighborhood().clone();
+            let mut expected = World::new();
+            for y in -22..22 {
+                for x in -22..22 {
+                    let coord = Coord::new(x, y);
+                    let count = world.count_neighbors_in(coord, &neighborhood) as u8;
+                    let alive = if world.get_cell(coord) {
+                        rule.survives_on(count)
+                    } else {
+                        rule.births_on(count)
+                    };
+                    expected.set_cell(coord, alive);
+                }
+            }
+            assert_eq!(rule.apply(&world), expected, "{}", rulestring);
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{Coord, Topology, World};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// полный блок 2x2
+const FULL: u8 = 15;
+
+/// наибольшая площадь конечного мира для таблиц, где пустой блок становится
+/// полным: такой мир шагается целиком, блок за блоком
+pub const MAX_B0_AREA: u64 = 1 << 22;
+
+/// блочное правило Margolus: мир режется на блоки 2x2, каждый блок целиком
+/// заменяется по таблице из 16 значений; на нечётных поколениях разбиение
+/// сдвинуто на (1, 1). Биты блока как в MCell/Golly: левая верхняя клетка - 1,
+/// правая верхняя - 2, левая нижняя - 4, правая нижняя - 8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Margolus {
+    table: [u8; 16],
+    /// таблицы, которые применяются к хранимому миру на чётных и нечётных поколениях
+    even: [u8; 16],
+    odd: [u8; 16],
+    rulestring: String,
+}
+
+impl Margolus {
+    /// таблица должна быть перестановкой 0..16; если пустой блок становится
+    /// полным, на нечётных поколениях плоскость хранится инвертированной, как
+    /// Golly хранит правила с B0, чтобы её фон оставался пустым
+    pub fn new(table: [u8; 16]) -> Result<Self, RuleParseError> {
+        let mut seen = [false; 16];
+        for &value in &table {
+            match seen.get_mut(value as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => {
+                    return Err(RuleParseError::Malformed(
+                        "block table must be a permutation of 0..15".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let (even, odd) = match (table[0], table[FULL as usize]) {
+            (0, _) => (table, table),
+            (FULL, 0) => (
+                table.map(|value| FULL - value),
+                std::array::from_fn(|block| table[FULL as usize - block]),
+            ),
+            _ => return Err(RuleParseError::BirthOnZero),
+        };
+
+        let values: Vec<String> = table.iter().map(u8::to_string).collect();
+        Ok(Self {
+            table,
+            even,
+            odd,
+            rulestring: format!("MS,D{}", values.join(";")),
+        })
+    }
+
+    /// разбирает нотацию MCell `MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0`
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim().to_ascii_uppercase();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let body = s.strip_prefix("MS,D").ok_or_else(|| {
+            RuleParseError::Malformed("expected Margolus notation like MS,D0;8;4;...".to_string())
+        })?;
+
+        let values = body
+            .split(';')
+            .map(|value| {
+                value.trim().parse::<u8>().map_err(|_| {
+                    RuleParseError::Malformed(format!("invalid block value '{}'", value))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let table: [u8; 16] = values.try_into().map_err(|values: Vec<u8>| {
+            RuleParseError::Malformed(format!("block table needs 16 values, got {}", values.len()))
+        })?;
+        Self::new(table)
+    }
+
+    /// Critters: блок с двумя живыми клетками не меняется, остальные
+    /// инвертируются, а блок из трёх живых ещё и поворачивается на 180°
+    pub fn critters() -> Self {
+        Self::new([15, 14, 13, 3, 11, 5, 6, 1, 7, 9, 10, 2, 12, 4, 8, 0]).unwrap()
+    }
+
+    /// бильярдный компьютер Фредкина-Марголуса: одиночные шары летят
+    /// по диагонали, встречные отражаются
+    pub fn billiard_ball() -> Self {
+        Self::new([0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15]).unwrap()
+    }
+
+    /// Tron: пустой и полный блоки инвертируются, остальные не меняются
+    pub fn tron() -> Self {
+        Self::new([15, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 0]).unwrap()
+    }
+
+    pub fn table(&self) -> &[u8; 16] {
+        &self.table
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    /// похожа ли строка на правило в нотации MCell `MS,D...`
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim().to_ascii_lowercase();
+        s.starts_with("ms,d")
+    }
+
+    /// левые верхние углы всех блоков конечного мира; блок через шов
+    /// приводится к клетке мира, как в `occupied_blocks`
+    fn all_blocks(topology: Topology, phase: i32) -> HashSet<Coord> {
+        let Some((width, height)) = topology.size() else {
+            return HashSet::new();
+        };
+        (-phase..height as i32)
+            .step_by(2)
+            .flat_map(|y| {
+                (-phase..width as i32)
+                    .step_by(2)
+                    .map(move |x| Coord::new(x, y))
+            })
+            .map(|origin| topology.wrap(origin).unwrap_or(origin))
+            .collect()
+    }
+
+    /// мир после замены блоков `blocks` по таблице `table`
+    fn replace_blocks(current: &World, blocks: HashSet<Coord>, table: &[u8; 16]) -> World {
+        let mut next = current.empty_like();
+        for origin in blocks {
+            let cells = block_cells(origin);
+            let block = cells
+                .iter()
+                .enumerate()
+                .filter(|&(_, &cell)| current.get_cell(cell))
+                .fold(0, |block, (bit, _)| block | 1 << bit);
+            let replaced = table[block];
+            for (bit, &cell) in cells.iter().enumerate() {
+                if replaced & 1 << bit != 0 {
+                    next.set_cell(cell, true);
+                }
+            }
+        }
+        next
+    }
+
+    /// левые верхние углы блоков с живыми клетками; на торе угол приводится
+    /// к клетке мира, чтобы блок через шов не обработался дважды
+    fn occupied_blocks(current: &World, phase: i32) -> HashSet<Coord> {
+        let topology = current.topology();
+        current
+            .iter_active_cells()
+            .map(|cell| {
+                let origin = Coord::new(
+                    (cell.x - phase).div_euclid(2) * 2 + phase,
+                    (cell.y - phase).div_euclid(2) * 2 + phase,
+                );
+                topology.wrap(origin).unwrap_or(origin)
+            })
+            .collect()
+    }
+}
+
+/// клетки блока в порядке битов
+fn block_cells(origin: Coord) -> [Coord; 4] {
+    [
+        origin,
+        Coord::new(origin.x + 1, origin.y),
+        Coord::new(origin.x, origin.y + 1),
+        Coord::new(origin.x + 1, origin.y + 1),
+    ]
+}
+
+impl FromStr for Margolus {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Margolus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for Margolus {
+    fn apply(&self, current: &World) -> World {
+        self.apply_at(current, 0)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    /// на склеенном мире нечётная сторона сдвигает разбиение у шва, и блок
+    /// через шов накрывает блок у нуля
+    fn check_topology(&self, topology: Topology) -> Result<(), String> {
+        match topology {
+            Topology::Torus { width, height }
+            | Topology::Cylinder { width, height }
+            | Topology::KleinBottle { width, height }
+                if !width.is_multiple_of(2) || !height.is_multiple_of(2) =>
+            {
+                Err(format!(
+                    "{} needs even sides on a wrapped world, got {}x{}",
+                    self.rulestring, width, height
+                ))
+            }
+            Topology::Bounded { width, height }
+            | Topology::Torus { width, height }
+            | Topology::Cylinder { width, height }
+            | Topology::KleinBottle { width, height }
+                if self.table[0] != 0 && u64::from(width) * u64::from(height) > MAX_B0_AREA =>
+            {
+                Err(format!(
+                    "{} turns empty blocks into full ones and steps the whole world, \
+                     which must have at most {} cells, got {}x{}",
+                    self.rulestring, MAX_B0_AREA, width, height
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn jump(&self, current: &World, generations: u64) -> World {
+        self.jump_at(current, 0, generations)
+    }
+
+    fn apply_at(&self, current: &World, generation: u64) -> World {
+        let phase = (generation % 2) as i32;
+        let topology = current.topology();
+        // конечный мир хранится как есть: пустые блоки тоже меняются,
+        // поэтому шагаются все блоки
+        if topology.is_finite() && self.table[0] != 0 {
+            return Self::replace_blocks(current, Self::all_blocks(topology, phase), &self.table);
+        }
+
+        // пустые блоки остаются пустыми, поэтому достаточно блоков с живыми клетками
+        let table = if phase == 0 { &self.even } else { &self.odd };
+        Self::replace_blocks(current, Self::occupied_blocks(current, phase), table)
+    }
+
+    fn jump_at(&self, current: &World, generation: u64, generations: u64) -> World {
+        let mut world = current.clone();
+        for offset in 0..generations {
+            world = self.apply_at(&world, generation + offset);
+        }
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::soup_world;
+
+    #[test]
+    fn test_parse_notation() {
+        let critters = Margolus::critters();
+        assert_eq!(
+            critters.rulestring(),
+            "MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0"
+        );
+        assert_eq!(
+            Margolus::parse("ms,d15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0").unwrap(),
+            critters
+        );
+        assert!(Margolus::looks_like(
+            "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15"
+        ));
+        assert!(!Margolus::looks_like("B3/S23"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Margolus::parse(""), Err(RuleParseError::Empty));
+        assert!(Margolus::parse("MS,D0;1;2").is_err());
+        assert!(Margolus::parse("MS,D0;1;2;3;4;5;6;7;8;9;10;11;12;13;14;16").is_err());
+        // не перестановка: 1 встречается дважды
+        assert!(Margolus::parse("MS,D0;1;1;3;4;5;6;7;8;9;10;11;12;13;14;15").is_err());
+        // пустой блок не может стать частично заполненным на бесконечной плоскости
+        assert_eq!(
+            Margolus::parse("MS,D1;0;2;3;4;5;6;7;8;9;10;11;12;13;14;15"),
+            Err(RuleParseError::BirthOnZero)
+        );
+    }
+
+    #[test]
+    fn test_partition_alternates_with_generation() {
+        // шар бильярдного компьютера летит по диагонали только при смене разбиений
+        let rule = Margolus::billiard_ball();
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+
+        let world = rule.jump_at(&world, 0, 6);
+        assert_eq!(
+            world.iter_active_cells().collect::<Vec<_>>(),
+            [Coord::new(6, 6)]
+        );
+
+        // без сдвига разбиения шар прыгал бы внутри одного блока
+        let mut ball = World::new();
+        ball.set_cell(Coord::new(0, 0), true);
+        let same_phase = rule.apply_at(&rule.apply_at(&ball, 0), 0);
+        assert!(same_phase.get_cell(Coord::new(0, 0)));
+    }
+
+    #[test]
+    fn test_critters_conserves_cells() {
+        // без мигания фона Critters сохраняет число живых клеток
+        let rule = Margolus::critters();
+        let mut world = soup_world(Coord::new(0, 0), 24, 0.25, 7);
+        let count = world.active_cell_count();
+        for generation in 0..60 {
+            world = rule.apply_at(&world, generation);
+            assert_eq!(world.active_cell_count(), count);
+        }
+    }
+
+    #[test]
+    fn test_finite_world_is_stepped_directly() {
+        // на конечном торе фон хранится как есть и совпадает с определением Critters
+        let topology = Topology::Torus {
+            width: 8,
+            height: 8,
+        };
+        let rule = Margolus::critters();
+        let mut world = soup_world(Coord::new(0, 0), 8, 0.25, 3);
+        world.set_topology(topology);
+        let mut direct = world.clone();
+
+        for generation in 0..10u64 {
+            world = rule.apply_at(&world, generation);
+
+            let phase = (generation % 2) as i32;
+            let mut next = direct.empty_like();
+            for by in 0..4 {
+                for bx in 0..4 {
+                    let cells = block_cells(Coord::new(bx * 2 + phase, by * 2 + phase));
+                    let block = (0..4)
+                        .filter(|&bit| direct.get_cell(cells[bit]))
+                        .fold(0, |block, bit| block | 1 << bit);
+                    let replaced = rule.table()[block];
+                    for (bit, &cell) in cells.iter().enumerate() {
+                        next.set_cell(cell, replaced & 1 << bit != 0);
+                    }
+                }
+            }
+            direct = next;
+            assert_eq!(world, direct, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn test_plane_stores_inverted_background() {
+        // на плоскости нечётные поколения хранятся инверсией настоящих
+        let rule = Margolus::critters();
+        let plane = soup_world(Coord::new(12, 12), 8, 0.25, 3);
+        let mut torus = plane.clone();
+        torus.set_topology(Topology::Torus {
+            width: 32,
+            height: 32,
+        });
+        let (mut plane, mut torus) = (plane, torus);
+
+        for generation in 0..6u64 {
+            plane = rule.apply_at(&plane, generation);
+            torus = rule.apply_at(&torus, generation);
+            let inverted = generation % 2 == 0;
+            for y in 0..32 {
+                for x in 0..32 {
+                    let cell = Coord::new(x, y);
+                    assert_eq!(plane.get_cell(cell) != inverted, torus.get_cell(cell));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_torus_blocks_across_seam() {
+        let rule = Margolus::billiard_ball();
+        let mut world = World::with_topology(Topology::Torus {
+            width: 4,
+            height: 4,
+        });
+        world.set_cell(Coord::new(3, 3), true);
+
+        // нечётное разбиение склеивает блок из углов тора
+        let next = rule.apply_at(&world, 1);
+        assert_eq!(
+            next.iter_active_cells().collect::<Vec<_>>(),
+            [Coord::new(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_rejects_odd_wrapped_sides() {
+        let rule = Margolus::billiard_ball();
+        let odd = [
+            Topology::Torus {
+                width: 5,
+                height: 5,
+            },
+            Topology::Cylinder {
+                width: 4,
+                height: 5,
+            },
+            Topology::KleinBottle {
+                width: 5,
+                height: 4,
+            },
+        ];
+        for topology in odd {
+            assert!(rule.check_topology(topology).is_err());
+        }
+        let allowed = [
+            Topology::Plane,
+            Topology::Bounded {
+                width: 5,
+                height: 5,
+            },
+            Topology::Torus {
+                width: 6,
+                height: 4,
+            },
+        ];
+        for topology in allowed {
+            assert!(rule.check_topology(topology).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_b0_tables_limit_finite_area() {
+        let big = Topology::Bounded {
+            width: 1 << 12,
+            height: 1 << 12,
+        };
+        assert!(Margolus::critters().check_topology(big).is_err());
+        assert!(Margolus::billiard_ball().check_topology(big).is_ok());
+        assert!(Margolus::critters().check_topology(Topology::Plane).is_ok());
+    }
+}
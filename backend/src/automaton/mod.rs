@@ -1,3 +1,35 @@
+pub mod cyclic;
+pub mod elementary;
+mod fft;
+pub mod generations;
+pub mod hashlife;
+pub mod isotropic;
+pub mod larger_than_life;
+pub mod lenia;
+pub mod life_like;
+pub mod margolus;
 pub mod rule;
+pub mod rule_table;
+pub mod second_order;
+pub mod stochastic;
+pub mod turmite;
+pub mod wireworld;
 
-pub use rule::{GameOfLife, Rule};
+pub use cyclic::{Cyclic, GreenbergHastings};
+pub use elementary::Elementary;
+pub use generations::Generations;
+pub use hashlife::{HashLife, MAX_HASHLIFE_JUMP};
+pub use isotropic::Isotropic;
+pub use larger_than_life::LargerThanLife;
+pub use lenia::{Lenia, MAX_LENIA_RADIUS};
+pub use life_like::{LifeLike, RuleParseError};
+pub use margolus::Margolus;
+pub use rule::{
+    parse_hashlife_rule, parse_rule, ContinuousRule, GameOfLife, Rule, SecondOrderRule,
+    MAX_STEPWISE_JUMP,
+};
+pub use rule_table::{RuleTable, RuleTableError, TableNeighborhood};
+pub use second_order::SecondOrder;
+pub use stochastic::{StochasticLife, MAX_NOISE_FLIPS};
+pub use turmite::{Turmite, Turn};
+pub use wireworld::WireWorld;
@@ -1,10 +1,172 @@
-use crate::world::{Coord, World};
-use std::collections::HashSet;
+use crate::automaton::cyclic::{Cyclic, GreenbergHastings};
+use crate::automaton::elementary::Elementary;
+use crate::automaton::generations::Generations;
+use crate::automaton::hashlife::HashLife;
+use crate::automaton::isotropic::Isotropic;
+use crate::automaton::larger_than_life::LargerThanLife;
+use crate::automaton::lenia::Lenia;
+use crate::automaton::life_like::{LifeLike, RuleParseError};
+use crate::automaton::margolus::Margolus;
+use crate::automaton::second_order::SecondOrder;
+use crate::automaton::stochastic::StochasticLife;
+use crate::automaton::turmite::Turmite;
+use crate::automaton::wireworld::WireWorld;
+use crate::world::{step_totalistic_parallel, Field, Neighborhood, Rng, Topology, World};
+use std::sync::Arc;
+
+/// наибольший прыжок для движков, которые считают поколение за поколением
+pub const MAX_STEPWISE_JUMP: u64 = 10_000;
 
 pub trait Rule: Send + Sync {
     fn apply(&self, current: &World) -> World;
 
     fn name(&self) -> &str;
+
+    /// строка правила для экспорта шаблонов, если правило её имеет
+    fn rulestring(&self) -> Option<&str> {
+        None
+    }
+
+    /// число состояний клетки, включая мёртвое; фронтенд раскрашивает по нему клетки.
+    /// Шире `u8`, потому что правило может занимать все 256 значений байта
+    fn state_count(&self) -> u16 {
+        2
+    }
+
+    /// окрестность, по которой правило считает соседей
+    fn neighborhood(&self) -> Neighborhood {
+        Neighborhood::default()
+    }
+
+    /// двигает ли правило муравьёв мира; остальные правила оставляют их на месте
+    fn moves_ants(&self) -> bool {
+        false
+    }
+
+    /// наибольшее число поколений в одном прыжке из мира `current`: `jump` по
+    /// умолчанию идёт по одному поколению и на большом числе занял бы поток
+    /// симуляции навсегда
+    fn max_jump(&self, _current: &World) -> u64 {
+        MAX_STEPWISE_JUMP
+    }
+
+    /// может ли правило работать на мире формы `topology`; ошибка объясняет, почему нет
+    fn check_topology(&self, _topology: Topology) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// мир через `generations` поколений; движки вроде HashLife делают это быстрее
+    fn jump(&self, current: &World, generations: u64) -> World {
+        let mut world = current.clone();
+        for _ in 0..generations {
+            world = self.apply(&world);
+        }
+        world
+    }
+
+    /// следующее поколение после поколения номер `generation`; нужно правилам,
+    /// шаг которых зависит от чётности поколения, остальные просто делают `apply`
+    fn apply_at(&self, current: &World, _generation: u64) -> World {
+        self.apply(current)
+    }
+
+    /// `jump`, начиная с поколения номер `generation`
+    fn jump_at(&self, current: &World, _generation: u64, generations: u64) -> World {
+        self.jump(current, generations)
+    }
+
+    /// шаг с генератором случайных чисел симуляции; вероятностные правила
+    /// берут числа только из него, поэтому прогон повторяется при том же зерне
+    fn apply_seeded(&self, current: &World, generation: u64, _rng: &mut Rng) -> World {
+        self.apply_at(current, generation)
+    }
+
+    /// `jump_at` с генератором случайных чисел симуляции
+    fn jump_seeded(
+        &self,
+        current: &World,
+        generation: u64,
+        generations: u64,
+        _rng: &mut Rng,
+    ) -> World {
+        self.jump_at(current, generation, generations)
+    }
+
+    /// непрерывная часть правила, если оно считает поле вещественных значений;
+    /// `Simulation` тогда хранит поле между шагами, а мир лишь квантует его
+    fn continuous(&self) -> Option<&dyn ContinuousRule> {
+        None
+    }
+
+    /// часть правила второго порядка, если следующее поколение зависит и от
+    /// предыдущего; такие правила `Simulation` умеет прокручивать назад
+    fn second_order(&self) -> Option<&dyn SecondOrderRule> {
+        None
+    }
+}
+
+/// правило над непрерывным полем вроде Lenia
+pub trait ContinuousRule: Send + Sync {
+    fn step_field(&self, field: &Field) -> Field;
+}
+
+/// обратимое правило второго порядка
+pub trait SecondOrderRule: Send + Sync {
+    /// поколение после `current`, если перед ним было `previous`;
+    /// `advance(next, current)` возвращает `previous`
+    fn advance(&self, previous: &World, current: &World) -> World;
+}
+
+/// разбирает строку правила в правило, готовое для `Simulation`
+pub fn parse_rule(rulestring: &str) -> Result<Arc<dyn Rule>, RuleParseError> {
+    if SecondOrder::looks_like(rulestring) {
+        return Ok(Arc::new(SecondOrder::parse(rulestring)?));
+    }
+    if Lenia::looks_like(rulestring) {
+        return Ok(Arc::new(Lenia::parse(rulestring)?));
+    }
+    if WireWorld::matches(rulestring) {
+        return Ok(Arc::new(WireWorld::new()));
+    }
+    if Margolus::looks_like(rulestring) {
+        return Ok(Arc::new(Margolus::parse(rulestring)?));
+    }
+    if LargerThanLife::looks_like(rulestring) {
+        return Ok(Arc::new(LargerThanLife::parse(rulestring)?));
+    }
+    if GreenbergHastings::looks_like(rulestring) {
+        return Ok(Arc::new(GreenbergHastings::parse(rulestring)?));
+    }
+    if Cyclic::looks_like(rulestring) {
+        return Ok(Arc::new(Cyclic::parse(rulestring)?));
+    }
+    if StochasticLife::looks_like(rulestring) {
+        return Ok(Arc::new(StochasticLife::parse(rulestring)?));
+    }
+    if Elementary::looks_like(rulestring) {
+        return Ok(Arc::new(Elementary::parse(rulestring)?));
+    }
+    if Turmite::looks_like(rulestring) {
+        return Ok(Arc::new(Turmite::parse(rulestring)?));
+    }
+    if Isotropic::looks_like(rulestring) {
+        return Ok(Arc::new(Isotropic::parse(rulestring)?));
+    }
+    if Generations::looks_like(rulestring) {
+        return Ok(Arc::new(Generations::parse(rulestring)?));
+    }
+    Ok(Arc::new(LifeLike::parse(rulestring)?))
+}
+
+/// то же правило, но на движке HashLife (только Life-like правила)
+pub fn parse_hashlife_rule(rulestring: &str) -> Result<Arc<dyn Rule>, RuleParseError> {
+    let life = LifeLike::parse(rulestring)?;
+    if !life.neighborhood().is_moore() {
+        return Err(RuleParseError::Malformed(
+            "HashLife supports only the Moore neighbourhood".to_string(),
+        ));
+    }
+    Ok(Arc::new(HashLife::new(life)))
 }
 
 #[derive(Debug, Clone)]
@@ -24,43 +186,22 @@ impl Default for GameOfLife {
 
 impl Rule for GameOfLife {
     fn apply(&self, current: &World) -> World {
-        let mut next = World::new();
-        let mut candidates = HashSet::new();
-        
-        for cell in current.iter_active_cells() {
-            candidates.insert(cell);
-            for neighbor in cell.neighbors() {
-                candidates.insert(neighbor);
-            }
-        }
-
-        for coord in candidates {
-            let alive = current.get_cell(coord);
-            let neighbors = current.count_neighbors(coord);
-
-            let next_state = match (alive, neighbors) {
-                
-                (true, 2) | (true, 3) => true,
-                
-                (false, 3) => true,
-                
-                _ => false,
-            };
-
-            if next_state {
-                next.set_cell(coord, true);
-            }
-        }
-        next
+        // B3/S23
+        step_totalistic_parallel(current, 1 << 3, 1 << 2 | 1 << 3)
     }
     fn name(&self) -> &str {
         "Conway's Game of Life"
     }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some("B3/S23")
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::world::Coord;
 
     #[test]
     fn test_game_of_life_name() {
@@ -193,4 +334,62 @@ mod tests {
 
         assert!(next.get_cell(center));
     }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = parse_rule("b36s23").unwrap();
+        assert_eq!(rule.name(), "B36/S23");
+
+        assert!(parse_rule("B9/S23").is_err());
+
+        let rule = parse_rule("345/2/4").unwrap();
+        assert_eq!(rule.name(), "B2/S345/C4");
+        assert_eq!(rule.state_count(), 4);
+        assert_eq!(parse_rule("B3/S23").unwrap().state_count(), 2);
+        assert_eq!(parse_rule("wireworld").unwrap().name(), "WireWorld");
+        assert!(parse_rule("rlr").unwrap().moves_ants());
+        assert_eq!(parse_rule("Rule 30").unwrap().name(), "W30");
+        assert_eq!(parse_rule("b3s2-i34q").unwrap().name(), "B3/S2-i34q");
+        assert_eq!(parse_rule("b3/s23/n0.01").unwrap().name(), "B3/S23/N0.01");
+        assert_eq!(parse_rule("r3/t5/c3/nm").unwrap().state_count(), 3);
+        assert_eq!(parse_rule("gh/r1/t1/c5/nn").unwrap().name(), "GH/R1/T1/C5/NN");
+        let lenia = parse_rule("lenia/r13/m0.15/s0.015").unwrap();
+        assert_eq!(lenia.name(), "Lenia/R13/T10/M0.15/S0.015/B1");
+        assert!(lenia.continuous().is_some());
+        assert!(parse_rule("B3/S23").unwrap().continuous().is_none());
+        let reversible = parse_rule("2nd/B3/S23").unwrap();
+        assert_eq!(reversible.name(), "2nd/B3/S23");
+        assert!(reversible.second_order().is_some());
+        assert!(parse_rule("B3/S23").unwrap().second_order().is_none());
+        assert_eq!(
+            parse_rule("ms,d0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15").unwrap().name(),
+            "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15"
+        );
+        assert_eq!(
+            parse_rule("b2s34h").unwrap().neighborhood(),
+            Neighborhood::Hexagonal
+        );
+    }
+
+    #[test]
+    fn test_parse_hashlife_rule() {
+        let rule = parse_hashlife_rule("B3/S23").unwrap();
+        assert_eq!(rule.name(), "B3/S23 (HashLife)");
+        assert_eq!(rule.rulestring(), Some("B3/S23"));
+        assert!(parse_hashlife_rule("B2/S34H").is_err());
+    }
+
+    #[test]
+    fn test_default_jump_repeats_apply() {
+        let gol = GameOfLife::new();
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, -1), true);
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(0, 1), true);
+
+        let next = gol.jump(&world, 3);
+        assert!(next.get_cell(Coord::new(-1, 0)));
+        assert!(next.get_cell(Coord::new(1, 0)));
+        assert!(!next.get_cell(Coord::new(0, 1)));
+    }
 }
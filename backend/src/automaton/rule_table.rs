@@ -0,0 +1,833 @@
+use crate::automaton::Rule;
+use crate::world::{Coord, Neighborhood, World};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use thiserror::Error;
+
+/// ошибка загрузки файла `.rule`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RuleTableError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("missing @RULE header")]
+    MissingRule,
+    #[error("missing @TABLE section")]
+    MissingTable,
+    #[error("@TABLE has no n_states line")]
+    MissingStates,
+}
+
+impl RuleTableError {
+    pub fn syntax(line: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// окрестности таблиц Golly; соседи перечисляются по часовой стрелке
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableNeighborhood {
+    /// N, NE, E, SE, S, SW, W, NW
+    Moore,
+    /// N, E, S, W
+    VonNeumann,
+    /// N, E, SE, S, W, NW - шестиугольная сетка на квадратной
+    Hexagonal,
+    /// W, E
+    OneDimensional,
+}
+
+impl TableNeighborhood {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "moore" => Some(Self::Moore),
+            "vonneumann" => Some(Self::VonNeumann),
+            "hexagonal" => Some(Self::Hexagonal),
+            "onedimensional" => Some(Self::OneDimensional),
+            _ => None,
+        }
+    }
+
+    pub fn offsets(self) -> &'static [(i32, i32)] {
+        match self {
+            Self::Moore => &[
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+                (-1, -1),
+            ],
+            Self::VonNeumann => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Self::Hexagonal => &[(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)],
+            Self::OneDimensional => &[(-1, 0), (1, 0)],
+        }
+    }
+
+    /// перестановки соседей для симметрии `name`: поворот на `step` позиций
+    /// по кругу соседей и, если нужно, зеркальное отражение
+    fn symmetries(self, name: &str) -> Option<Vec<Vec<usize>>> {
+        let (step, reflect) = match (self, name) {
+            (_, "none") => (0, false),
+            (Self::Moore, "rotate4") => (2, false),
+            (Self::Moore, "rotate8") => (1, false),
+            (Self::Moore, "reflect_horizontal") => (0, true),
+            (Self::Moore, "rotate4reflect") => (2, true),
+            (Self::Moore, "rotate8reflect") => (1, true),
+            (Self::VonNeumann, "rotate4") => (1, false),
+            (Self::VonNeumann, "reflect_horizontal") => (0, true),
+            (Self::VonNeumann, "rotate4reflect") => (1, true),
+            (Self::Hexagonal, "rotate2") => (3, false),
+            (Self::Hexagonal, "rotate3") => (2, false),
+            (Self::Hexagonal, "rotate6") => (1, false),
+            (Self::Hexagonal, "rotate6reflect") => (1, true),
+            (Self::OneDimensional, "reflect") => (0, true),
+            _ => return None,
+        };
+
+        let size = self.offsets().len();
+        let mirror = |i: usize| match self {
+            Self::OneDimensional => 1 - i,
+            _ => (size - i) % size,
+        };
+        let rotations = size.checked_div(step).unwrap_or(1);
+        let mut permutations = Vec::new();
+        for turn in 0..rotations {
+            let rotated: Vec<usize> = (0..size).map(|i| (i + turn * step) % size).collect();
+            if reflect {
+                permutations.push(rotated.iter().map(|&i| mirror(i)).collect());
+            }
+            permutations.push(rotated);
+        }
+        Some(permutations)
+    }
+
+    fn to_neighborhood(self) -> Neighborhood {
+        match self {
+            Self::Moore => Neighborhood::Moore(1),
+            Self::VonNeumann => Neighborhood::VonNeumann(1),
+            Self::Hexagonal => Neighborhood::Hexagonal,
+            Self::OneDimensional => Neighborhood::Custom(vec![Coord::new(-1, 0), Coord::new(1, 0)]),
+        }
+    }
+}
+
+/// элемент перехода: конкретное состояние или переменная (индекс в `variables`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Term {
+    State(u8),
+    Variable(usize),
+}
+
+/// переход таблицы: состояние клетки, соседи и новое состояние
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transition {
+    /// строка файла - для сообщений об ошибках
+    line: usize,
+    center: Term,
+    /// варианты соседей для всех симметрий, без повторов
+    variants: Vec<Vec<Term>>,
+    output: Term,
+}
+
+/// правило из файла Golly `.rule` с секцией `@TABLE`: переходы проверяются
+/// по порядку, первый подошедший задаёт новое состояние, без совпадений клетка
+/// не меняется; переменная, встреченная в переходе несколько раз, везде
+/// принимает одно значение
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+    name: String,
+    description: String,
+    states: u8,
+    neighborhood: TableNeighborhood,
+    /// симметрия `permute`: соседи сопоставляются в любом порядке
+    permute: bool,
+    variables: Vec<Vec<bool>>,
+    transitions: Vec<Transition>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl RuleTable {
+    /// разбирает файл `.rule`; секции кроме `@RULE` и `@TABLE` (`@COLORS`,
+    /// `@ICONS` и другие) сохраняются как метаданные
+    pub fn parse(source: &str) -> Result<Self, RuleTableError> {
+        let mut name = None;
+        // секции: заголовок без `@` и строки с номерами
+        let mut sections: Vec<(String, Vec<(usize, &str)>)> = Vec::new();
+
+        for (index, raw) in source.lines().enumerate() {
+            let line = index + 1;
+            let trimmed = raw.trim();
+            if let Some(header) = trimmed.strip_prefix('@') {
+                let (kind, rest) = header
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((header, ""));
+                let kind = kind.to_ascii_uppercase();
+                if kind == "RULE" {
+                    if name.is_some() {
+                        return Err(RuleTableError::syntax(line, "duplicate @RULE header"));
+                    }
+                    let rule_name = rest.trim();
+                    if rule_name.is_empty() {
+                        return Err(RuleTableError::syntax(line, "@RULE needs a name"));
+                    }
+                    name = Some(rule_name.to_string());
+                } else if name.is_none() {
+                    return Err(RuleTableError::syntax(line, "sections must follow @RULE"));
+                }
+                sections.push((kind, Vec::new()));
+            } else if let Some((_, lines)) = sections.last_mut() {
+                lines.push((line, raw));
+            } else if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                return Err(RuleTableError::syntax(line, "text before @RULE"));
+            }
+        }
+
+        let name = name.ok_or(RuleTableError::MissingRule)?;
+        let text = |lines: &[(usize, &str)]| {
+            let lines: Vec<&str> = lines.iter().map(|&(_, line)| line).collect();
+            lines.join("\n").trim().to_string()
+        };
+        let mut table = None;
+        let mut description = String::new();
+        let mut metadata = BTreeMap::new();
+        for (kind, lines) in sections {
+            match kind.as_str() {
+                "RULE" => description = text(&lines),
+                "TABLE" => table = Some(lines),
+                _ => {
+                    metadata.insert(kind, text(&lines));
+                }
+            }
+        }
+
+        let lines = table.ok_or(RuleTableError::MissingTable)?;
+        let mut rule = TableParser::default().parse(&lines)?;
+        rule.name = name;
+        rule.description = description;
+        rule.metadata = metadata;
+        Ok(rule)
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn table_neighborhood(&self) -> TableNeighborhood {
+        self.neighborhood
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// сохранённые секции вроде `COLORS` и `ICONS` - имя без `@` и текст
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// новое состояние клетки `center` с соседями `neighbors` в порядке окрестности
+    pub fn next_state(&self, center: u8, neighbors: &[u8]) -> u8 {
+        self.first_match(center, neighbors)
+            .map_or(center, |(_, state)| state)
+    }
+
+    /// первый подходящий переход и новое состояние по нему
+    fn first_match(&self, center: u8, neighbors: &[u8]) -> Option<(&Transition, u8)> {
+        let mut bindings = vec![None; self.variables.len()];
+        for transition in &self.transitions {
+            for variant in &transition.variants {
+                bindings.fill(None);
+                if !self.bind(transition.center, center, &mut bindings) {
+                    continue;
+                }
+                let matched = if self.permute {
+                    self.match_any_order(variant, neighbors, 0, &mut bindings)
+                } else {
+                    variant
+                        .iter()
+                        .zip(neighbors)
+                        .all(|(&term, &value)| self.bind(term, value, &mut bindings))
+                };
+                if matched {
+                    let state = match transition.output {
+                        Term::State(state) => state,
+                        Term::Variable(id) => bindings[id].unwrap_or(center),
+                    };
+                    return Some((transition, state));
+                }
+            }
+        }
+        None
+    }
+
+    /// сопоставляет значение с элементом перехода, связывая переменную при первом появлении
+    fn bind(&self, term: Term, value: u8, bindings: &mut [Option<u8>]) -> bool {
+        match term {
+            Term::State(state) => state == value,
+            Term::Variable(id) => match bindings[id] {
+                Some(bound) => bound == value,
+                None if self.variables[id][value as usize] => {
+                    bindings[id] = Some(value);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// `permute`: перебор соответствий элементов перехода свободным соседям
+    /// (`used` - битовая маска уже занятых)
+    fn match_any_order(
+        &self,
+        terms: &[Term],
+        neighbors: &[u8],
+        used: u32,
+        bindings: &mut [Option<u8>],
+    ) -> bool {
+        let Some((&term, rest)) = terms.split_first() else {
+            return true;
+        };
+        for (index, &value) in neighbors.iter().enumerate() {
+            if used >> index & 1 == 1 {
+                continue;
+            }
+            let fresh = matches!(term, Term::Variable(id) if bindings[id].is_none());
+            if self.bind(term, value, bindings) {
+                if self.match_any_order(rest, neighbors, used | 1 << index, bindings) {
+                    return true;
+                }
+                if let (true, Term::Variable(id)) = (fresh, term) {
+                    bindings[id] = None;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// разбор строк секции `@TABLE`
+#[derive(Default)]
+struct TableParser {
+    states: Option<u8>,
+    neighborhood: Option<TableNeighborhood>,
+    /// строка и имя симметрии
+    symmetries: Option<(usize, String)>,
+    names: HashMap<String, usize>,
+    variables: Vec<Vec<bool>>,
+    transitions: Vec<Transition>,
+}
+
+impl TableParser {
+    fn parse(mut self, lines: &[(usize, &str)]) -> Result<RuleTable, RuleTableError> {
+        for &(line, raw) in lines {
+            let text = raw.split('#').next().unwrap_or("").trim();
+            if text.is_empty() {
+                continue;
+            }
+            if let Some((key, value)) = text.split_once(':') {
+                self.setting(line, key.trim(), value.trim())?;
+            } else if let Some(definition) = text.strip_prefix("var ") {
+                self.variable(line, definition)?;
+            } else {
+                self.transition(line, text)?;
+            }
+        }
+
+        let states = self.states.ok_or(RuleTableError::MissingStates)?;
+        let permute = self.permutations()?.is_none();
+        let rule = RuleTable {
+            name: String::new(),
+            description: String::new(),
+            states,
+            neighborhood: self.neighborhood(),
+            permute,
+            variables: self.variables,
+            transitions: self.transitions,
+            metadata: BTreeMap::new(),
+        };
+
+        // мёртвый фон должен оставаться мёртвым, иначе бесконечный мир заполнится
+        let zeros = vec![0; rule.neighborhood.offsets().len()];
+        if let Some((transition, state)) = rule.first_match(0, &zeros) {
+            if state != 0 {
+                return Err(RuleTableError::syntax(
+                    transition.line,
+                    "state 0 with all-zero neighbours must stay 0",
+                ));
+            }
+        }
+        Ok(rule)
+    }
+
+    fn neighborhood(&self) -> TableNeighborhood {
+        self.neighborhood.unwrap_or(TableNeighborhood::Moore)
+    }
+
+    /// перестановки соседей для заданной симметрии; `None` для `permute`
+    fn permutations(&self) -> Result<Option<Vec<Vec<usize>>>, RuleTableError> {
+        let size = self.neighborhood().offsets().len();
+        match &self.symmetries {
+            None => Ok(Some(vec![(0..size).collect()])),
+            Some((_, name)) if name == "permute" => Ok(None),
+            Some((line, name)) => self
+                .neighborhood()
+                .symmetries(name)
+                .map(Some)
+                .ok_or_else(|| {
+                    RuleTableError::syntax(
+                        *line,
+                        format!(
+                            "symmetries '{}' are not supported for this neighborhood",
+                            name
+                        ),
+                    )
+                }),
+        }
+    }
+
+    fn setting(&mut self, line: usize, key: &str, value: &str) -> Result<(), RuleTableError> {
+        if !self.transitions.is_empty() || !self.variables.is_empty() {
+            return Err(RuleTableError::syntax(
+                line,
+                format!("'{}' must come before variables and transitions", key),
+            ));
+        }
+        match key {
+            "n_states" => {
+                let states = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&n| n >= 2)
+                    .ok_or_else(|| {
+                        RuleTableError::syntax(line, "n_states must be from 2 to 255")
+                    })?;
+                self.states = Some(states);
+            }
+            "neighborhood" => {
+                let neighborhood = TableNeighborhood::parse(value).ok_or_else(|| {
+                    RuleTableError::syntax(line, format!("unsupported neighborhood '{}'", value))
+                })?;
+                self.neighborhood = Some(neighborhood);
+            }
+            "symmetries" => self.symmetries = Some((line, value.to_string())),
+            _ => {
+                return Err(RuleTableError::syntax(
+                    line,
+                    format!("unknown setting '{}'", key),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn states(&self, line: usize) -> Result<u8, RuleTableError> {
+        self.states
+            .ok_or_else(|| RuleTableError::syntax(line, "n_states must come first"))
+    }
+
+    /// `var a={0,1,2}`; элементы - состояния или ранее объявленные переменные
+    fn variable(&mut self, line: usize, definition: &str) -> Result<(), RuleTableError> {
+        let states = self.states(line)?;
+        let (name, values) = definition
+            .split_once('=')
+            .ok_or_else(|| RuleTableError::syntax(line, "expected var name={...}"))?;
+        let name = name.trim();
+        if name.is_empty() || name.parse::<u32>().is_ok() {
+            return Err(RuleTableError::syntax(
+                line,
+                format!("invalid variable name '{}'", name),
+            ));
+        }
+        let values = values
+            .trim()
+            .strip_prefix('{')
+            .and_then(|v| v.strip_suffix('}'))
+            .ok_or_else(|| RuleTableError::syntax(line, "variable values must be in {...}"))?;
+
+        let mut set = vec![false; 256];
+        for item in values.split(',').map(str::trim) {
+            match self.term(line, item, states)? {
+                Term::State(state) => set[state as usize] = true,
+                Term::Variable(id) => {
+                    for (value, &included) in self.variables[id].iter().enumerate() {
+                        set[value] |= included;
+                    }
+                }
+            }
+        }
+        self.names.insert(name.to_string(), self.variables.len());
+        self.variables.push(set);
+        Ok(())
+    }
+
+    fn term(&self, line: usize, token: &str, states: u8) -> Result<Term, RuleTableError> {
+        if let Ok(state) = token.parse::<u32>() {
+            return match u8::try_from(state) {
+                Ok(state) if state < states => Ok(Term::State(state)),
+                _ => Err(RuleTableError::syntax(
+                    line,
+                    format!("state {} is out of range: n_states is {}", state, states),
+                )),
+            };
+        }
+        self.names
+            .get(token)
+            .map(|&id| Term::Variable(id))
+            .ok_or_else(|| RuleTableError::syntax(line, format!("unknown variable '{}'", token)))
+    }
+
+    /// `0,1,1,1,0,0,0,0,0,1` или без запятых при n_states <= 10
+    fn transition(&mut self, line: usize, text: &str) -> Result<(), RuleTableError> {
+        let states = self.states(line)?;
+        let size = self.neighborhood().offsets().len();
+
+        let tokens: Vec<String> = if text.contains(',') {
+            text.split(',').map(|t| t.trim().to_string()).collect()
+        } else if states <= 10 && text.chars().all(|c| c.is_ascii_digit()) {
+            text.chars().map(String::from).collect()
+        } else {
+            return Err(RuleTableError::syntax(
+                line,
+                format!("unrecognised line '{}'", text),
+            ));
+        };
+        if tokens.len() != size + 2 {
+            return Err(RuleTableError::syntax(
+                line,
+                format!(
+                    "expected {} states in a transition, got {}",
+                    size + 2,
+                    tokens.len()
+                ),
+            ));
+        }
+
+        let terms = tokens
+            .iter()
+            .map(|token| self.term(line, token, states))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (center, rest) = terms.split_first().unwrap_or((&Term::State(0), &[]));
+        let (output, neighbors) = rest.split_last().unwrap_or((&Term::State(0), &[]));
+        if let Term::Variable(id) = output {
+            if !terms[..=size].contains(output) {
+                let name = self.names.iter().find(|&(_, &v)| v == *id).map(|(n, _)| n);
+                return Err(RuleTableError::syntax(
+                    line,
+                    format!(
+                        "output variable '{}' is not bound by the inputs",
+                        name.map_or("?", |n| n)
+                    ),
+                ));
+            }
+        }
+
+        // при `permute` порядок соседей неважен, хватает исходного варианта
+        let permutations = self
+            .permutations()?
+            .unwrap_or_else(|| vec![(0..size).collect()]);
+        let mut seen = HashSet::new();
+        let variants = permutations
+            .iter()
+            .map(|perm| {
+                // вариант смотрит на соседа `perm[i]` там, где исходный переход - на `i`
+                let mut variant = vec![Term::State(0); size];
+                for (i, &term) in neighbors.iter().enumerate() {
+                    variant[perm[i]] = term;
+                }
+                variant
+            })
+            .filter(|variant| seen.insert(variant.clone()))
+            .collect();
+
+        self.transitions.push(Transition {
+            line,
+            center: *center,
+            variants,
+            output: *output,
+        });
+        Ok(())
+    }
+}
+
+impl Rule for RuleTable {
+    fn apply(&self, current: &World) -> World {
+        let topology = current.topology();
+        let offsets = self.neighborhood.offsets();
+        let neighbor = |coord: Coord, (dx, dy): (i32, i32)| {
+            topology.wrap(Coord::new(coord.x + dx, coord.y + dy))
+        };
+
+        // изменить состояние могут только непустые клетки и их соседи
+        let mut candidates = HashSet::new();
+        for (coord, _) in current.iter_cell_states() {
+            candidates.insert(coord);
+            candidates.extend(offsets.iter().filter_map(|&offset| neighbor(coord, offset)));
+        }
+
+        let mut cache: HashMap<Vec<u8>, u8> = HashMap::new();
+        let mut next = current.empty_like();
+        for coord in candidates {
+            let mut key = Vec::with_capacity(offsets.len() + 1);
+            key.push(current.get_state(coord));
+            key.extend(
+                offsets
+                    .iter()
+                    .map(|&offset| neighbor(coord, offset).map_or(0, |n| current.get_state(n))),
+            );
+            let state = *cache
+                .entry(key)
+                .or_insert_with_key(|key| self.next_state(key[0], &key[1..]));
+            if state != 0 {
+                next.set_state(coord, state);
+            }
+        }
+        next
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn state_count(&self) -> u16 {
+        u16::from(self.states)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood.to_neighborhood()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::{LifeLike, WireWorld};
+    use crate::presets::Preset;
+    use crate::world::soup_world;
+
+    const LIFE: &str = "\
+@RULE LifeTable
+Conway's Life as a rule table
+
+@TABLE
+n_states:2
+neighborhood:Moore
+symmetries:permute
+var a={0,1}
+var b={a}
+var c={a}
+var d={a}
+var e={a}
+var f={a}
+var g={a}
+var h={a}
+0,1,1,1,0,0,0,0,0,1
+1,1,1,0,0,0,0,0,0,1
+1,1,1,1,0,0,0,0,0,1
+1,a,b,c,d,e,f,g,h,0
+
+@COLORS
+0 48 48 48
+1 255 255 255
+
+@ICONS
+XPM
+";
+
+    const WIREWORLD: &str = "\
+@RULE WireWorldTable
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:rotate8
+var a={0,1,2,3}
+var b={a}
+var c={a}
+var d={a}
+var e={a}
+var f={a}
+var g={a}
+var h={a}
+var i={0,2,3}
+var j={i}
+var k={i}
+var l={i}
+var m={i}
+var n={i}
+var o={i}
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# одна или две головы рядом с проводником
+3,1,i,j,k,l,m,n,o,1
+3,1,1,i,j,k,l,m,n,1
+3,1,i,1,j,k,l,m,n,1
+3,1,i,j,1,k,l,m,n,1
+3,1,i,j,k,1,l,m,n,1
+";
+
+    fn table(body: &str) -> Result<RuleTable, RuleTableError> {
+        RuleTable::parse(&format!("@RULE Test\n@TABLE\n{}", body))
+    }
+
+    #[test]
+    fn test_life_table_matches_life_like() {
+        let rule = RuleTable::parse(LIFE).unwrap();
+        assert_eq!(rule.name(), "LifeTable");
+        assert_eq!(rule.description(), "Conway's Life as a rule table");
+        assert_eq!(rule.state_count(), 2);
+        assert_eq!(rule.transition_count(), 4);
+
+        let life = LifeLike::conway();
+        let mut world = soup_world(Coord::new(0, 0), 30, 0.3, 0x1234_5678);
+        for generation in 0..20 {
+            let next = rule.apply(&world);
+            assert_eq!(next, life.step(&world), "generation {}", generation);
+            world = next;
+        }
+    }
+
+    #[test]
+    fn test_wireworld_table_matches_wireworld() {
+        let rule = RuleTable::parse(WIREWORLD).unwrap();
+        let wireworld = WireWorld::new();
+        for preset in [Preset::wireworld_clock(), Preset::wireworld_or_gate()] {
+            let mut world = preset.to_world();
+            for _ in 0..30 {
+                let next = rule.apply(&world);
+                assert_eq!(next, wireworld.apply(&world), "{}", preset.name);
+                world = next;
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata_sections_are_kept() {
+        let rule = RuleTable::parse(LIFE).unwrap();
+        assert_eq!(
+            rule.metadata().get("COLORS").map(String::as_str),
+            Some("0 48 48 48\n1 255 255 255")
+        );
+        assert_eq!(
+            rule.metadata().get("ICONS").map(String::as_str),
+            Some("XPM")
+        );
+        assert!(rule.rulestring().is_none());
+    }
+
+    #[test]
+    fn test_symmetries_rotate_transitions() {
+        // клетка оживает, если жив только сосед сверху
+        let body = |symmetries: &str| {
+            format!(
+                "n_states:2\nneighborhood:vonNeumann\nsymmetries:{}\n0,1,0,0,0,1",
+                symmetries
+            )
+        };
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+
+        let none = table(&body("none")).unwrap().apply(&world);
+        let mut cells: Vec<_> = none.iter_active_cells().map(|c| (c.x, c.y)).collect();
+        cells.sort();
+        assert_eq!(cells, vec![(0, 0), (0, 1)]);
+
+        let rotated = table(&body("rotate4")).unwrap().apply(&world);
+        assert_eq!(rotated.active_cell_count(), 5);
+        assert_eq!(
+            rotated.get_bounds(),
+            Some((Coord::new(-1, -1), Coord::new(1, 1)))
+        );
+
+        let reflected = table(&body("reflect_horizontal")).unwrap().apply(&world);
+        assert_eq!(reflected.active_cell_count(), 2);
+    }
+
+    #[test]
+    fn test_variables_are_bound_within_a_transition() {
+        // рождение, только если соседи сверху и снизу в одном состоянии
+        let rule = table("n_states:3\nneighborhood:vonNeumann\nvar a={1,2}\n0,a,0,a,0,a").unwrap();
+        assert_eq!(rule.next_state(0, &[2, 0, 2, 0]), 2);
+        assert_eq!(rule.next_state(0, &[1, 0, 2, 0]), 0);
+        assert_eq!(rule.next_state(1, &[1, 0, 1, 0]), 1);
+    }
+
+    #[test]
+    fn test_compact_transitions_and_first_match() {
+        let error = table("n_states:3\nneighborhood:oneDimensional\n010 2").unwrap_err();
+        assert_eq!(
+            error,
+            RuleTableError::syntax(5, "unrecognised line '010 2'")
+        );
+
+        let rule = table("n_states:3\nneighborhood:oneDimensional\n0102\n0101\n1002").unwrap();
+        assert_eq!(rule.next_state(0, &[1, 0]), 2);
+        assert_eq!(rule.next_state(1, &[0, 0]), 2);
+        assert_eq!(rule.next_state(2, &[1, 1]), 2);
+    }
+
+    #[test]
+    fn test_errors_have_line_numbers() {
+        let line = |result: Result<RuleTable, RuleTableError>| match result {
+            Err(RuleTableError::Syntax { line, .. }) => line,
+            other => panic!("expected a syntax error, got {:?}", other),
+        };
+
+        assert_eq!(line(table("n_states:2\n0,x,0,0,0,0,0,0,0,1")), 4);
+        assert_eq!(line(table("n_states:2\n\n# comment\n0,1,1,0")), 6);
+        assert_eq!(line(table("n_states:2\n0,5,0,0,0,0,0,0,0,1")), 4);
+        assert_eq!(
+            line(table("n_states:2\nsymmetries:rotate6\n0,1,0,0,0,0,0,0,0,1")),
+            4
+        );
+        assert_eq!(
+            line(table("n_states:2\nvar a={0,1}\n0,1,0,0,0,0,0,0,0,a")),
+            5
+        );
+        assert_eq!(line(table("0,1,0,0,0,0,0,0,0,1")), 3);
+        assert_eq!(line(table("n_states:1")), 3);
+        assert_eq!(line(table("n_states:2\nneighborhood:triangular")), 4);
+        // фон из нулей оживает - бесконечный мир так не посчитать
+        assert_eq!(
+            line(table("n_states:2\nvar a={0,1}\n0,a,a,a,a,a,a,a,a,1")),
+            5
+        );
+        // но более ранний переход может это перекрыть
+        assert!(table("n_states:2\nvar a={0,1}\n0,0,0,0,0,0,0,0,0,0\n0,a,a,a,a,a,a,a,a,1").is_ok());
+
+        assert_eq!(
+            RuleTable::parse("@TABLE\nn_states:2"),
+            Err(RuleTableError::syntax(1, "sections must follow @RULE"))
+        );
+        assert_eq!(RuleTable::parse(""), Err(RuleTableError::MissingRule));
+        assert_eq!(
+            RuleTable::parse("@RULE X\n@TREE\nnum_states=2"),
+            Err(RuleTableError::MissingTable)
+        );
+        assert_eq!(
+            table("neighborhood:Moore"),
+            Err(RuleTableError::MissingStates)
+        );
+    }
+
+    #[test]
+    fn test_respects_topology() {
+        let rule = RuleTable::parse(LIFE).unwrap();
+        let mut world = World::with_topology(crate::world::Topology::Torus {
+            width: 5,
+            height: 5,
+        });
+        for &(x, y) in &[(0, 4), (0, 0), (0, 1)] {
+            world.set_cell(Coord::new(x, y), true);
+        }
+        let next = rule.apply(&world);
+        assert_eq!(next, LifeLike::conway().step(&world));
+        assert!(next.get_cell(Coord::new(4, 0)));
+    }
+}
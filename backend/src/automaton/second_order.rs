@@ -0,0 +1,181 @@
+use crate::automaton::life_like::{LifeLike, RuleParseError};
+use crate::automaton::{Rule, SecondOrderRule};
+use crate::world::{Neighborhood, World};
+use std::fmt;
+use std::str::FromStr;
+
+/// правило второго порядка Фредкина: следующее поколение - шаг Life-like
+/// правила над текущим, сложенный по XOR с предыдущим. Та же формула по
+/// `(следующее, текущее)` возвращает предыдущее, поэтому ход обратим
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecondOrder {
+    life: LifeLike,
+    rulestring: String,
+}
+
+impl SecondOrder {
+    pub fn new(life: LifeLike) -> Self {
+        let rulestring = format!("2nd/{}", life.rulestring());
+        Self { life, rulestring }
+    }
+
+    /// разбирает `2nd/B3/S23`: после метки - любое Life-like правило
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let life = strip_marker(s).ok_or_else(|| {
+            RuleParseError::Malformed("expected notation like 2nd/B3/S23".to_string())
+        })?;
+        Ok(Self::new(LifeLike::parse(life)?))
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    /// правило первого порядка внутри
+    pub fn life(&self) -> &LifeLike {
+        &self.life
+    }
+
+    /// начинается ли строка с `2nd/`
+    pub fn looks_like(rulestring: &str) -> bool {
+        strip_marker(rulestring.trim()).is_some()
+    }
+}
+
+fn strip_marker(s: &str) -> Option<&str> {
+    let (marker, life) = s.split_once('/')?;
+    marker.trim().eq_ignore_ascii_case("2nd").then_some(life)
+}
+
+impl FromStr for SecondOrder {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for SecondOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl SecondOrderRule for SecondOrder {
+    fn advance(&self, previous: &World, current: &World) -> World {
+        let mut next = self.life.step(current);
+        for (coord, _) in previous.iter_cell_states() {
+            let alive = next.get_cell(coord);
+            next.set_cell(coord, !alive);
+        }
+        next
+    }
+}
+
+impl Rule for SecondOrder {
+    /// шаг от пустого предыдущего поколения; `Simulation` помнит настоящее
+    fn apply(&self, current: &World) -> World {
+        self.advance(&World::with_topology(current.topology()), current)
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        self.life.neighborhood().clone()
+    }
+
+    /// прогон от пустого предыдущего поколения, с парой миров на каждом шаге
+    fn jump(&self, current: &World, generations: u64) -> World {
+        let mut previous = World::with_topology(current.topology());
+        let mut world = current.clone();
+        for _ in 0..generations {
+            let next = self.advance(&previous, &world);
+            previous = std::mem::replace(&mut world, next);
+        }
+        world
+    }
+
+    fn second_order(&self) -> Option<&dyn SecondOrderRule> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{soup_world, Coord, Topology};
+
+    #[test]
+    fn test_parse() {
+        let rule = SecondOrder::parse("2nd/b36s23").unwrap();
+        assert_eq!(rule.rulestring(), "2nd/B36/S23");
+        assert_eq!(rule.life(), &LifeLike::highlife());
+        assert_eq!(
+            SecondOrder::parse("2ND/B2/S34H").unwrap().neighborhood(),
+            Neighborhood::Hexagonal
+        );
+        assert!(SecondOrder::looks_like("2nd/B3/S23"));
+        assert!(!SecondOrder::looks_like("B3/S23"));
+        assert!(SecondOrder::parse("2nd/").is_err());
+        assert!(SecondOrder::parse("B3/S23").is_err());
+    }
+
+    #[test]
+    fn test_xor_with_previous() {
+        let rule = SecondOrder::new(LifeLike::conway());
+        let mut blinker = World::new();
+        for y in -1..=1 {
+            blinker.set_cell(Coord::new(0, y), true);
+        }
+        let mut previous = World::new();
+        previous.set_cell(Coord::new(0, 0), true);
+        previous.set_cell(Coord::new(5, 5), true);
+
+        // центр вычитается, далёкая клетка прибавляется
+        let next = rule.advance(&previous, &blinker);
+        assert!(!next.get_cell(Coord::new(0, 0)));
+        assert!(next.get_cell(Coord::new(-1, 0)));
+        assert!(next.get_cell(Coord::new(1, 0)));
+        assert!(next.get_cell(Coord::new(5, 5)));
+        assert_eq!(next.active_cell_count(), 3);
+
+        assert_eq!(rule.apply(&blinker), LifeLike::conway().step(&blinker));
+    }
+
+    #[test]
+    fn test_runs_backwards_to_the_start() {
+        let topology = Topology::Torus {
+            width: 24,
+            height: 24,
+        };
+        let rule = SecondOrder::parse("2nd/B3/S23").unwrap();
+        let mut soup = soup_world(Coord::new(0, 0), 24, 0.4, 7);
+        soup.set_topology(topology);
+        let start = (World::with_topology(topology), soup);
+
+        let (mut previous, mut current) = start.clone();
+        for _ in 0..50 {
+            let next = rule.advance(&previous, &current);
+            previous = std::mem::replace(&mut current, next);
+        }
+        assert_ne!(current, start.1);
+        assert_eq!(current, rule.jump(&start.1, 50));
+
+        // назад той же формулой с переставленной парой
+        for _ in 0..50 {
+            let before = rule.advance(&current, &previous);
+            current = std::mem::replace(&mut previous, before);
+        }
+        assert_eq!((previous, current), start);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{Coord, Neighborhood, Rng, Topology, World};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// наибольшее ожидаемое число переворотов шума за поколение в конечном мире
+pub const MAX_NOISE_FLIPS: u64 = 1 << 22;
+
+/// вероятностное Life-like правило: мёртвая клетка с `n` соседями рождается
+/// с вероятностью `birth[n]`, живая выживает с вероятностью `survival[n]`,
+/// а шум затем переворачивает клетку с вероятностью `noise`: любую в конечном
+/// мире и живые клетки с соседями на плоскости, иначе шум заполнял бы бесконечность
+#[derive(Debug, Clone, PartialEq)]
+pub struct StochasticLife {
+    birth: [f64; 9],
+    survival: [f64; 9],
+    noise: f64,
+    rulestring: String,
+}
+
+impl StochasticLife {
+    pub fn new(birth: [f64; 9], survival: [f64; 9], noise: f64) -> Result<Self, RuleParseError> {
+        if let Some(&p) = birth
+            .iter()
+            .chain(&survival)
+            .chain([&noise])
+            .find(|p| !(0.0..=1.0).contains(*p))
+        {
+            return Err(RuleParseError::Malformed(format!(
+                "probability {} is outside of 0..1",
+                p
+            )));
+        }
+        if birth[0] > 0.0 {
+            return Err(RuleParseError::BirthOnZero);
+        }
+
+        let mut rulestring = format!("B{}/S{}", format_counts(&birth), format_counts(&survival));
+        if noise > 0.0 {
+            rulestring.push_str(&format!("/N{}", noise));
+        }
+        Ok(Self {
+            birth,
+            survival,
+            noise,
+            rulestring,
+        })
+    }
+
+    /// разбирает `B3,6:0.05/S23/N0.001`: цифры без `:` срабатывают всегда,
+    /// `:p` задаёт вероятность для цифр перед ним, `N` - вероятность шума
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim().to_ascii_lowercase();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+
+        let mut birth = None;
+        let mut survival = None;
+        let mut noise = None;
+        for part in s.split('/') {
+            let mut chars = part.chars();
+            let (slot, body) = match chars.next() {
+                Some('b') => (&mut birth, chars.as_str()),
+                Some('s') => (&mut survival, chars.as_str()),
+                Some('n') => {
+                    let value = parse_probability(chars.as_str())?;
+                    if noise.replace(value).is_some() {
+                        return Err(RuleParseError::Malformed("duplicate N section".to_string()));
+                    }
+                    continue;
+                }
+                Some(other) => return Err(RuleParseError::UnexpectedChar(other)),
+                None => return Err(RuleParseError::Malformed("empty section".to_string())),
+            };
+            if slot.replace(parse_counts(body)?).is_some() {
+                return Err(RuleParseError::Malformed(format!(
+                    "duplicate {} section",
+                    part[..1].to_ascii_uppercase()
+                )));
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Self::new(birth, survival, noise.unwrap_or(0.0)),
+            _ => Err(RuleParseError::Malformed(
+                "both B and S sections are required".to_string(),
+            )),
+        }
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn noise(&self) -> f64 {
+        self.noise
+    }
+
+    /// похожа ли строка на вероятностное правило: есть `:p` или секция шума
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim().to_ascii_lowercase();
+        s.contains(':')
+            || s.split('/').any(|part| {
+                part.strip_prefix('n')
+                    .is_some_and(|value| value.parse::<f64>().is_ok())
+            })
+    }
+
+    /// клетки конечного мира, которые шум переворачивает за поколение; между
+    /// переворотами геометрически распределённый пропуск, поэтому работа растёт
+    /// с числом переворотов, а не с площадью мира
+    fn noise_flips(&self, width: u32, height: u32, rng: &mut Rng) -> HashSet<Coord> {
+        let area = u64::from(width) * u64::from(height);
+        let log_miss = (1.0 - self.noise).ln();
+        let mut flips = HashSet::new();
+        let mut index = 0u64;
+        loop {
+            // при шуме 1 `log_miss` бесконечен и пропусков нет
+            let skip = if log_miss.is_finite() {
+                (1.0 - rng.next_f64()).ln() / log_miss
+            } else {
+                0.0
+            };
+            index = index.saturating_add(skip as u64);
+            if index >= area {
+                return flips;
+            }
+            let width = u64::from(width);
+            flips.insert(Coord::new((index % width) as i32, (index / width) as i32));
+            index += 1;
+        }
+    }
+}
+/// вероятности по числу соседей из записи `23,6:0.5`
+fn parse_counts(body: &str) -> Result<[f64; 9], RuleParseError> {
+    let mut probabilities = [0.0; 9];
+    let mut seen = [false; 9];
+    for item in body.split(',').filter(|item| !item.is_empty()) {
+        let (digits, probability) = match item.split_once(':') {
+            Some((digits, probability)) => (digits, parse_probability(probability)?),
+            None => (item, 1.0),
+        };
+        if digits.is_empty() {
+            return Err(RuleParseError::Malformed(format!(
+                "probability without neighbour counts in '{}'",
+                item
+            )));
+        }
+        for c in digits.chars() {
+            let count = match c.to_digit(10) {
+                Some(d) if d <= 8 => d as usize,
+                _ if c.is_ascii_digit() => return Err(RuleParseError::InvalidCount(c)),
+                _ => return Err(RuleParseError::UnexpectedChar(c)),
+            };
+            if std::mem::replace(&mut seen[count], true) {
+                return Err(RuleParseError::Malformed(format!(
+                    "neighbour count {} is given twice",
+                    count
+                )));
+            }
+            probabilities[count] = probability;
+        }
+    }
+    Ok(probabilities)
+}
+
+fn parse_probability(value: &str) -> Result<f64, RuleParseError> {
+    value
+        .parse::<f64>()
+        .map_err(|_| RuleParseError::Malformed(format!("invalid probability '{}'", value)))
+}
+
+/// сначала цифры с вероятностью 1, потом остальные по одной: `23,6:0.5`
+fn format_counts(probabilities: &[f64; 9]) -> String {
+    let mut items = Vec::new();
+    let certain: String = (0..9)
+        .filter(|&count| probabilities[count] == 1.0)
+        .map(|count| char::from(b'0' + count as u8))
+        .collect();
+    if !certain.is_empty() {
+        items.push(certain);
+    }
+    for (count, &p) in probabilities.iter().enumerate() {
+        if p > 0.0 && p < 1.0 {
+            items.push(format!("{}:{}", count, p));
+        }
+    }
+    items.join(",")
+}
+
+impl FromStr for StochasticLife {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for StochasticLife {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for StochasticLife {
+    /// без генератора симуляции шаг берёт зерно 0
+    fn apply(&self, current: &World) -> World {
+        self.apply_seeded(current, 0, &mut Rng::new(0))
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn check_topology(&self, topology: Topology) -> Result<(), String> {
+        let Some((width, height)) = topology.size() else {
+            return Ok(());
+        };
+        let flips = (u64::from(width) * u64::from(height)) as f64 * self.noise;
+        if flips > MAX_NOISE_FLIPS as f64 {
+            return Err(format!(
+                "noise {} flips about {:.0} cells per generation on a {}x{} world, more than {}",
+                self.noise, flips, width, height, MAX_NOISE_FLIPS
+            ));
+        }
+        Ok(())
+    }
+
+    fn jump(&self, current: &World, generations: u64) -> World {
+        self.jump_seeded(current, 0, generations, &mut Rng::new(0))
+    }
+
+    fn apply_seeded(&self, current: &World, _generation: u64, rng: &mut Rng) -> World {
+        // одно число из генератора на поколение, дальше клетки берут
+        // свои случайные числа из него и координаты в любом порядке
+        let key = rng.next_u64();
+        let noise_key = rng.next_u64();
+        let topology = current.topology();
+        let neighborhood = Neighborhood::default();
+
+        let mut candidates: HashSet<Coord> = HashSet::new();
+        for cell in current.iter_active_cells() {
+            candidates.insert(cell);
+            candidates.extend(neighborhood.around(cell, topology));
+        }
+        let flips = match topology.size() {
+            Some((width, height)) if self.noise > 0.0 => {
+                Some(self.noise_flips(width, height, &mut Rng::new(noise_key)))
+            }
+            _ => None,
+        };
+        let flipped = |coord: Coord| match &flips {
+            Some(flips) => flips.contains(&coord),
+            None => Rng::cell_unit(noise_key, coord) < self.noise,
+        };
+
+        let alive: Vec<Coord> = candidates
+            .par_iter()
+            .copied()
+            .filter(|&coord| {
+                let neighbors = current.count_neighbors(coord) as usize;
+                let p = if current.get_cell(coord) {
+                    self.survival[neighbors]
+                } else {
+                    self.birth[neighbors]
+                };
+                let next = Rng::cell_unit(key, coord) < p;
+                next != flipped(coord)
+            })
+            .collect();
+
+        // вдали от живых клеток правило ничего не рождает, остаётся только шум
+        let mut next = current.empty_like();
+        for coord in alive {
+            next.set_cell(coord, true);
+        }
+        for &coord in flips.iter().flatten() {
+            if !candidates.contains(&coord) {
+                next.set_cell(coord, true);
+            }
+        }
+        next
+    }
+
+    fn jump_seeded(
+        &self,
+        current: &World,
+        generation: u64,
+        generations: u64,
+        rng: &mut Rng,
+    ) -> World {
+        let mut world = current.clone();
+        for offset in 0..generations {
+            world = self.apply_seeded(&world, generation + offset, rng);
+        }
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::LifeLike;
+    use crate::presets::Preset;
+
+    #[test]
+    fn test_parse_notation() {
+        let rule = StochasticLife::parse("b3,6:0.05/s32/n0.001").unwrap();
+        assert_eq!(rule.rulestring(), "B3,6:0.05/S23/N0.001");
+        assert_eq!(rule.noise(), 0.001);
+        assert_eq!(StochasticLife::parse(rule.rulestring()).unwrap(), rule);
+
+        let mixed = StochasticLife::parse("B3/S2:0.5,3").unwrap();
+        assert_eq!(mixed.rulestring(), "B3/S3,2:0.5");
+        assert_eq!(
+            StochasticLife::parse("B3:0.5/S").unwrap().rulestring(),
+            "B3:0.5/S"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(StochasticLife::parse(""), Err(RuleParseError::Empty));
+        assert!(StochasticLife::parse("B3:1.5/S23").is_err());
+        assert!(StochasticLife::parse("B3/S23/N-0.1").is_err());
+        assert!(StochasticLife::parse("B3:0.5").is_err());
+        assert!(StochasticLife::parse("B3,3:0.5/S23").is_err());
+        assert!(StochasticLife::parse("B:0.5/S23").is_err());
+        assert_eq!(
+            StochasticLife::parse("B9:0.5/S"),
+            Err(RuleParseError::InvalidCount('9'))
+        );
+        assert_eq!(
+            StochasticLife::parse("B0:0.1/S23"),
+            Err(RuleParseError::BirthOnZero)
+        );
+    }
+
+    #[test]
+    fn test_looks_like() {
+        assert!(StochasticLife::looks_like("B3:0.5/S23"));
+        assert!(StochasticLife::looks_like("B3/S23/N0.01"));
+        assert!(!StochasticLife::looks_like("B3/S23"));
+        assert!(!StochasticLife::looks_like("B3/S23/C4"));
+        assert!(!StochasticLife::looks_like("B2n3/S23"));
+    }
+
+    #[test]
+    fn test_certain_rule_is_life() {
+        let rule = StochasticLife::parse("B3/S23").unwrap();
+        let life = LifeLike::conway();
+        let mut world = Preset::r_pentomino().to_world();
+        let mut expected = world.clone();
+        let mut rng = Rng::new(1);
+        for generation in 0..30 {
+            world = rule.apply_seeded(&world, generation, &mut rng);
+            expected = life.apply(&expected);
+        }
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn test_same_seed_reproduces_run() {
+        let rule = StochasticLife::parse("B3,2:0.1/S23,4:0.3/N0.001").unwrap();
+        let world = Preset::acorn().to_world();
+
+        let run = |seed| rule.jump_seeded(&world, 0, 40, &mut Rng::new(seed));
+        assert_eq!(run(11), run(11));
+        assert_ne!(run(11), run(12));
+    }
+
+    #[test]
+    fn test_birth_probability() {
+        // у каждой клетки под длинным рядом по три соседа
+        let rule = StochasticLife::parse("B3:0.5/S").unwrap();
+        let mut world = World::new();
+        for x in -1..=1000 {
+            world.set_cell(Coord::new(x, -1), true);
+        }
+        let next = rule.apply_seeded(&world, 0, &mut Rng::new(3));
+        let born = (0..1000)
+            .filter(|&x| next.get_cell(Coord::new(x, 0)))
+            .count();
+        assert!((400..600).contains(&born), "born {}", born);
+    }
+
+    #[test]
+    fn test_noise_fills_finite_world() {
+        let rule = StochasticLife::parse("B3/S23/N0.5").unwrap();
+        let world = World::with_topology(Topology::Torus {
+            width: 20,
+            height: 20,
+        });
+        let next = rule.apply_seeded(&world, 0, &mut Rng::new(5));
+        assert!((100..300).contains(&next.active_cell_count()));
+
+        // на пустой плоскости шуму негде сработать
+        assert_eq!(rule.apply(&World::new()).active_cell_count(), 0);
+    }
+
+    #[test]
+    fn test_noise_on_huge_world() {
+        // работа зависит от числа переворотов, а не от площади в 2^40 клеток
+        let topology = Topology::Torus {
+            width: 1 << 20,
+            height: 1 << 20,
+        };
+        let rule = StochasticLife::parse("B3/S23/N0.000000001").unwrap();
+        assert!(rule.check_topology(topology).is_ok());
+        let next = rule.apply_seeded(&World::with_topology(topology), 0, &mut Rng::new(9));
+        assert!((900..1300).contains(&next.active_cell_count()));
+
+        let noisy = StochasticLife::parse("B3/S23/N0.01").unwrap();
+        assert!(noisy.check_topology(topology).is_err());
+        assert!(noisy.check_topology(Topology::Plane).is_ok());
+    }
+}
@@ -0,0 +1,271 @@
+use crate::automaton::life_like::RuleParseError;
+use crate::automaton::Rule;
+use crate::world::{Ant, Topology, World};
+use std::fmt;
+use std::str::FromStr;
+
+/// поворот муравья на клетке заданного цвета
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+    /// без поворота
+    None,
+    /// разворот назад
+    UTurn,
+}
+
+impl Turn {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'L' => Some(Self::Left),
+            'R' => Some(Self::Right),
+            'N' => Some(Self::None),
+            'U' => Some(Self::UTurn),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Left => 'L',
+            Self::Right => 'R',
+            Self::None => 'N',
+            Self::UTurn => 'U',
+        }
+    }
+
+    /// поворот в четвертях по часовой стрелке
+    pub fn quarter_turns(self) -> i32 {
+        match self {
+            Self::Left => -1,
+            Self::Right => 1,
+            Self::None => 0,
+            Self::UTurn => 2,
+        }
+    }
+}
+
+/// тьюрмит по строке поворотов вроде `RL` (муравей Лэнгтона), `RLR`, `LLRR`:
+/// муравей на клетке цвета `i` поворачивает по `i`-й букве, перекрашивает
+/// клетку в следующий цвет и делает шаг вперёд
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turmite {
+    turns: Vec<Turn>,
+    rulestring: String,
+}
+
+impl Turmite {
+    pub fn new(turns: Vec<Turn>) -> Result<Self, RuleParseError> {
+        if !(2..=u8::MAX as usize).contains(&turns.len()) {
+            return Err(RuleParseError::Malformed(
+                "turn string must have from 2 to 255 letters".to_string(),
+            ));
+        }
+        let rulestring = turns.iter().map(|turn| turn.as_char()).collect();
+        Ok(Self { turns, rulestring })
+    }
+
+    /// разбирает строку поворотов из букв `L`, `R`, `N`, `U`
+    pub fn parse(rulestring: &str) -> Result<Self, RuleParseError> {
+        let s = rulestring.trim();
+        if s.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+        let turns = s
+            .chars()
+            .map(|c| Turn::from_char(c).ok_or(RuleParseError::UnexpectedChar(c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(turns)
+    }
+
+    pub fn langtons_ant() -> Self {
+        Self::parse("RL").unwrap()
+    }
+
+    pub fn rulestring(&self) -> &str {
+        &self.rulestring
+    }
+
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    /// похожа ли строка на строку поворотов тьюрмита
+    pub fn looks_like(rulestring: &str) -> bool {
+        let s = rulestring.trim();
+        !s.is_empty() && s.chars().all(|c| Turn::from_char(c).is_some())
+    }
+
+    /// один шаг муравья; клетку под ним уже перекрасил вызывающий
+    fn advance(ant: Ant, topology: Topology) -> Ant {
+        match ant.ahead().and_then(|ahead| topology.wrap(ahead)) {
+            Some(position) => Ant::new(position, ant.heading),
+            // у мёртвой границы и у края координат муравей разворачивается на месте
+            None => Ant::new(ant.position, ant.heading.rotated(2)),
+        }
+    }
+}
+
+impl FromStr for Turmite {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Turmite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rulestring)
+    }
+}
+
+impl Rule for Turmite {
+    fn apply(&self, current: &World) -> World {
+        let colors = self.turns.len();
+        let mut next = current.clone();
+        let mut ants = Vec::with_capacity(current.ants().len());
+
+        // муравьи ходят по очереди, каждый видит перекраски предыдущих
+        for &ant in current.ants() {
+            let color = next.get_state(ant.position) as usize % colors;
+            let turned = Ant::new(
+                ant.position,
+                ant.heading.rotated(self.turns[color].quarter_turns()),
+            );
+            next.set_state(ant.position, ((color + 1) % colors) as u8);
+            ants.push(Self::advance(turned, current.topology()));
+        }
+        next.set_ants(ants);
+        next
+    }
+
+    fn name(&self) -> &str {
+        &self.rulestring
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(&self.rulestring)
+    }
+
+    fn state_count(&self) -> u16 {
+        self.turns.len() as u16
+    }
+
+    fn moves_ants(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{Coord, Heading};
+
+    fn with_ant(world: World, x: i32, y: i32, heading: Heading) -> World {
+        let mut world = world;
+        world.add_ant(Ant::new(Coord::new(x, y), heading));
+        world
+    }
+
+    #[test]
+    fn test_parse() {
+        let ant = Turmite::parse("rl").unwrap();
+        assert_eq!(ant, Turmite::langtons_ant());
+        assert_eq!(ant.rulestring(), "RL");
+        assert_eq!(Turmite::parse("LLRR").unwrap().state_count(), 4);
+        assert_eq!(Turmite::parse("RNUL").unwrap().turns()[2], Turn::UTurn);
+
+        assert_eq!(Turmite::parse(""), Err(RuleParseError::Empty));
+        assert_eq!(
+            Turmite::parse("RLX"),
+            Err(RuleParseError::UnexpectedChar('X'))
+        );
+        assert!(Turmite::parse("R").is_err());
+
+        assert!(Turmite::looks_like("RLR"));
+        assert!(!Turmite::looks_like("B3/S23"));
+        assert!(!Turmite::looks_like("WireWorld"));
+    }
+
+    #[test]
+    fn test_langtons_ant_first_steps() {
+        let rule = Turmite::langtons_ant();
+        let mut world = with_ant(World::new(), 0, 0, Heading::North);
+
+        // четыре правых поворота по белым клеткам замыкают квадрат 2x2
+        for _ in 0..4 {
+            world = rule.apply(&world);
+        }
+        assert_eq!(world.active_cell_count(), 4);
+        assert_eq!(world.ants(), &[Ant::new(Coord::new(0, 0), Heading::North)]);
+
+        // на чёрной клетке - поворот налево с перекраской обратно в белый
+        world = rule.apply(&world);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 0);
+        assert_eq!(world.ants(), &[Ant::new(Coord::new(-1, 0), Heading::West)]);
+    }
+
+    #[test]
+    fn test_langtons_ant_builds_highway() {
+        let rule = Turmite::langtons_ant();
+        let mut world = with_ant(World::new(), 0, 0, Heading::North);
+        for _ in 0..11_000 {
+            world = rule.apply(&world);
+        }
+
+        // магистраль: период 104 шага, сдвиг на 2 клетки по диагонали, +12 клеток
+        let before = world.ants()[0].position;
+        let cells = world.active_cell_count();
+        world = rule.jump(&world, 104);
+        let after = world.ants()[0].position;
+        assert_eq!(((after.x - before.x).abs(), (after.y - before.y).abs()), (2, 2));
+        assert_eq!(world.active_cell_count(), cells + 12);
+    }
+
+    #[test]
+    fn test_multi_color_turmite() {
+        let rule = Turmite::parse("LLRR").unwrap();
+        let mut world = with_ant(World::new(), 0, 0, Heading::North);
+        for _ in 0..1000 {
+            world = rule.apply(&world);
+        }
+        assert!(world.iter_cell_states().all(|(_, state)| state < 4));
+        assert!(world.iter_cell_states().any(|(_, state)| state == 3));
+    }
+
+    #[test]
+    fn test_ant_turns_back_at_dead_border() {
+        let bounded = World::with_topology(Topology::Bounded {
+            width: 3,
+            height: 3,
+        });
+        let world = with_ant(bounded, 1, 0, Heading::West);
+        // на белой клетке поворот направо - лицом к границе
+        let next = Turmite::langtons_ant().apply(&world);
+        assert_eq!(next.ants(), &[Ant::new(Coord::new(1, 0), Heading::South)]);
+    }
+
+    #[test]
+    fn test_ant_turns_back_at_coordinate_limit() {
+        let world = with_ant(World::new(), i32::MAX, 0, Heading::North);
+        // поворот направо смотрит за `i32::MAX`
+        let next = Turmite::langtons_ant().apply(&world);
+        assert_eq!(
+            next.ants(),
+            &[Ant::new(Coord::new(i32::MAX, 0), Heading::West)]
+        );
+    }
+
+    #[test]
+    fn test_ant_wraps_around_torus() {
+        let torus = World::with_topology(Topology::Torus {
+            width: 4,
+            height: 4,
+        });
+        let world = with_ant(torus, 3, 0, Heading::North);
+        let next = Turmite::langtons_ant().apply(&world);
+        assert_eq!(next.ants(), &[Ant::new(Coord::new(0, 0), Heading::East)]);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::automaton::Rule;
+use crate::world::{step_totalistic_parallel, World};
+
+/// пустая клетка
+pub const EMPTY: u8 = 0;
+/// голова электрона
+pub const HEAD: u8 = 1;
+/// хвост электрона
+pub const TAIL: u8 = 2;
+/// проводник
+pub const CONDUCTOR: u8 = 3;
+
+/// WireWorld: голова -> хвост -> проводник, проводник становится головой,
+/// если рядом одна или две головы; нумерация состояний как в Golly
+#[derive(Debug, Clone, Default)]
+pub struct WireWorld;
+
+impl WireWorld {
+    pub const RULESTRING: &'static str = "WireWorld";
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `WireWorld` без учёта регистра
+    pub fn matches(rulestring: &str) -> bool {
+        rulestring.trim().eq_ignore_ascii_case(Self::RULESTRING)
+    }
+}
+
+impl Rule for WireWorld {
+    fn apply(&self, current: &World) -> World {
+        // проводники с одной-двумя головами рядом - это "рождения" B12 на мире из голов
+        let heads = current.cells_in_state(HEAD);
+        let excited = step_totalistic_parallel(&heads, 1 << 1 | 1 << 2, 0);
+
+        let mut next = current.empty_like();
+        for (coord, state) in current.iter_cell_states() {
+            let state = match state {
+                HEAD => TAIL,
+                TAIL => CONDUCTOR,
+                CONDUCTOR if excited.get_cell(coord) => HEAD,
+                other => other,
+            };
+            next.set_state(coord, state);
+        }
+        next
+    }
+
+    fn name(&self) -> &str {
+        Self::RULESTRING
+    }
+
+    fn rulestring(&self) -> Option<&str> {
+        Some(Self::RULESTRING)
+    }
+
+    fn state_count(&self) -> u16 {
+        4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::Preset;
+    use crate::world::Coord;
+
+    fn wire(length: i32) -> World {
+        let mut world = World::new();
+        for x in 0..length {
+            world.set_state(Coord::new(x, 0), CONDUCTOR);
+        }
+        world
+    }
+
+    #[test]
+    fn test_electron_travels_along_wire() {
+        let mut world = wire(10);
+        world.set_state(Coord::new(0, 0), TAIL);
+        world.set_state(Coord::new(1, 0), HEAD);
+
+        let rule = WireWorld::new();
+        for _ in 0..5 {
+            world = rule.apply(&world);
+        }
+        assert_eq!(world.get_state(Coord::new(6, 0)), HEAD);
+        assert_eq!(world.get_state(Coord::new(5, 0)), TAIL);
+        assert_eq!(world.get_state(Coord::new(0, 0)), CONDUCTOR);
+        assert_eq!(world.cells_in_state(HEAD).active_cell_count(), 1);
+    }
+
+    #[test]
+    fn test_three_heads_do_not_excite() {
+        let mut world = World::new();
+        world.set_state(Coord::new(1, 1), CONDUCTOR);
+        for x in 0..3 {
+            world.set_state(Coord::new(x, 0), HEAD);
+        }
+        let next = WireWorld::new().apply(&world);
+        assert_eq!(next.get_state(Coord::new(1, 1)), CONDUCTOR);
+    }
+
+    /// поколения, на которых в `probe` появлялась голова
+    fn heads_at(mut world: World, probe: Coord, generations: usize) -> Vec<usize> {
+        let rule = WireWorld::new();
+        let mut hits = Vec::new();
+        for generation in 1..=generations {
+            world = rule.apply(&world);
+            if world.get_state(probe) == HEAD {
+                hits.push(generation);
+            }
+        }
+        hits
+    }
+
+    /// схема шаблона без электронов
+    fn unpowered(preset: &Preset) -> World {
+        let mut world = preset.to_world();
+        for (coord, state) in world.clone().iter_cell_states() {
+            if state != CONDUCTOR {
+                world.set_state(coord, CONDUCTOR);
+            }
+        }
+        world
+    }
+
+    fn inject(world: &mut World, tail: (i32, i32), head: (i32, i32)) {
+        world.set_state(Coord::new(tail.0, tail.1), TAIL);
+        world.set_state(Coord::new(head.0, head.1), HEAD);
+    }
+
+    #[test]
+    fn test_diode_passes_forward() {
+        let preset = Preset::wireworld_diode();
+        assert_eq!(preset.rule.as_deref(), Some(WireWorld::RULESTRING));
+        assert!(!heads_at(preset.to_world(), Coord::new(9, 1), 20).is_empty());
+    }
+
+    #[test]
+    fn test_diode_blocks_backward() {
+        let mut world = unpowered(&Preset::wireworld_diode());
+        inject(&mut world, (9, 1), (8, 1));
+        assert!(heads_at(world.clone(), Coord::new(0, 1), 30).is_empty());
+        // электрон гаснет, и схема возвращается в покой
+        let rule = WireWorld::new();
+        for _ in 0..30 {
+            world = rule.apply(&world);
+        }
+        assert_eq!(world.cells_in_state(HEAD).active_cell_count(), 0);
+    }
+
+    #[test]
+    fn test_or_gate() {
+        let output = Coord::new(8, 2);
+        let (input_a, input_b) = (((0, 0), (1, 0)), ((0, 4), (1, 4)));
+
+        for (active, other) in [(input_a, input_b), (input_b, input_a)] {
+            let mut world = unpowered(&Preset::wireworld_or_gate());
+            inject(&mut world, active.0, active.1);
+            assert_eq!(heads_at(world.clone(), output, 20).len(), 1);
+            // сигнал не уходит обратно во второй вход
+            let back = Coord::new(other.0 .0, other.0 .1);
+            assert!(heads_at(world, back, 20).is_empty());
+        }
+
+        let mut world = unpowered(&Preset::wireworld_or_gate());
+        inject(&mut world, input_a.0, input_a.1);
+        inject(&mut world, input_b.0, input_b.1);
+        assert_eq!(heads_at(world, output, 20).len(), 1);
+
+        assert!(heads_at(unpowered(&Preset::wireworld_or_gate()), output, 20).is_empty());
+    }
+
+    #[test]
+    fn test_clock_emits_periodic_pulses() {
+        let hits = heads_at(Preset::wireworld_clock().to_world(), Coord::new(9, 1), 60);
+        assert!(hits.len() >= 5);
+        assert!(hits.windows(2).all(|pair| pair[1] - pair[0] == 10));
+    }
+
+    #[test]
+    fn test_matches_rulestring() {
+        assert!(WireWorld::matches("wireworld"));
+        assert!(WireWorld::matches(" WireWorld "));
+        assert!(!WireWorld::matches("B3/S23"));
+        assert_eq!(WireWorld::new().state_count(), 4);
+    }
+}
@@ -15,6 +15,10 @@ pub enum ApiError {
     SimulationNotRunning,
     InvalidRequest(String),
     InvalidTps(u32),
+    InvalidRule(String),
+    InvalidPattern(String),
+    InvalidTopology(String),
+    InvalidSoup(String),
 }
 
 impl IntoResponse for ApiError {
@@ -38,6 +42,18 @@ impl IntoResponse for ApiError {
                 StatusCode::BAD_REQUEST,
                 format!("Invalid TPS value: {}. Must be between 1 and 1000", tps),
             ),
+            ApiError::InvalidRule(msg) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid rule: {}", msg))
+            }
+            ApiError::InvalidPattern(msg) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid pattern: {}", msg))
+            }
+            ApiError::InvalidTopology(msg) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid topology: {}", msg))
+            }
+            ApiError::InvalidSoup(msg) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid soup: {}", msg))
+            }
         };
 
         (status, Json(json!({ "error": message }))).into_response()
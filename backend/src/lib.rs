@@ -2,5 +2,6 @@ pub mod world;
 pub mod automaton;
 pub mod simulation;
 pub mod presets;
+pub mod pattern;
 pub mod errors;
 pub mod api;
@@ -1,10 +1,3 @@
-mod api;
-mod automaton;
-mod errors;
-mod presets;
-mod simulation;
-mod world;
-
 use std::sync::Arc;
 
 use axum::{
@@ -15,8 +8,8 @@ use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::{
-    api::{control, world as world_api, AppState},
+use cellular_automata_backend::{
+    api::{control, world as world_api, ws, AppState},
     automaton::GameOfLife,
     presets::Preset,
     simulation::Simulation,
@@ -67,15 +60,32 @@ async fn main() {
         .route("/api/control/pause", post(control::pause_simulation))
         .route("/api/control/resume", post(control::resume_simulation))
         .route("/api/control/step", post(control::step_simulation))
+        .route("/api/control/jump", post(control::jump_simulation))
+        .route("/api/control/step_back", post(control::step_back_simulation))
         .route("/api/control/speed", post(control::set_speed))
         .route("/api/control/status", get(control::get_status))
+        .route("/api/control/rule", get(control::get_rule).post(control::set_rule))
+        .route("/api/control/rule/table", post(control::load_rule_table))
+        .route("/api/control/seed", post(control::set_seed))
         // World API
         .route("/api/world/region", get(world_api::get_region))
         .route("/api/world/all", get(world_api::get_all_cells))
+        .route("/api/world/diff", get(world_api::get_diff))
+        .route("/api/world/field", get(world_api::get_field_region))
         .route("/api/world/cell", post(world_api::set_cell))
         .route("/api/world/presets", get(world_api::get_presets))
         .route("/api/world/preset", post(world_api::load_preset))
         .route("/api/world/clear", post(world_api::clear_world))
+        .route(
+            "/api/world/topology",
+            get(world_api::get_topology).post(world_api::set_topology),
+        )
+        .route("/api/world/ants", get(world_api::get_ants).post(world_api::add_ant))
+        .route("/api/world/random", post(world_api::random_soup))
+        .route("/api/world/import", post(world_api::import_pattern))
+        .route("/api/world/export", get(world_api::export_pattern))
+        // Live updates
+        .route("/api/ws", get(ws::ws_handler))
 
         .with_state(app_state)
         .layer(cors);
@@ -90,14 +100,30 @@ async fn main() {
     info!("   POST /api/control/pause    - Pause simulation");
     info!("   POST /api/control/resume   - Resume simulation");
     info!("   POST /api/control/step     - Execute one step");
+    info!("   POST /api/control/jump     - Advance N generations at once");
+    info!("   POST /api/control/step_back - Step back one generation (second-order rules)");
     info!("   POST /api/control/speed    - Set speed (TPS)");
     info!("   GET  /api/control/status   - Get simulation status");
+    info!("   GET  /api/control/rule     - Get active rule");
+    info!("   POST /api/control/rule     - Set active rule (rulestring)");
+    info!("   POST /api/control/rule/table - Load a Golly @RULE/@TABLE file");
+    info!("   POST /api/control/seed     - Set the random seed for stochastic rules");
     info!("   GET  /api/world/region     - Get cells in region");
     info!("   GET  /api/world/all        - Get all active cells");
+    info!("   GET  /api/world/diff       - Get changes since a tick");
+    info!("   GET  /api/world/field      - Get continuous field values in region (Lenia)");
     info!("   POST /api/world/cell       - Set cell state");
     info!("   GET  /api/world/presets    - List all presets");
     info!("   POST /api/world/preset     - Load preset");
     info!("   POST /api/world/clear      - Clear world");
+    info!("   GET  /api/world/topology   - Get world topology");
+    info!("   POST /api/world/topology   - Set topology (plane, bounded, torus, cylinder, klein_bottle)");
+    info!("   GET  /api/world/ants       - List turmite ants");
+    info!("   POST /api/world/ants       - Place an ant (x, y, heading)");
+    info!("   POST /api/world/random     - Fill a rectangle with a seeded random soup");
+    info!("   POST /api/world/import     - Import pattern (RLE, .cells, Life 1.06)");
+    info!("   GET  /api/world/export     - Export world (format=rle|cells|life106)");
+    info!("   GET  /api/ws               - WebSocket stream of world updates");
 
     // Запускаем сервер
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
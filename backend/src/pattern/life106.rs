@@ -0,0 +1,136 @@
+use super::PatternError;
+use crate::presets::Preset;
+use crate::world::{Coord, World};
+
+pub const HEADER: &str = "#Life 1.06";
+
+/// строка заголовка Life 1.06; этой же проверкой формат определяет `detect`
+pub fn is_header(line: &str) -> bool {
+    line.trim() == HEADER
+}
+
+/// разбирает Life 1.06: заголовок `#Life 1.06` и пары координат `x y`
+pub fn parse(input: &str) -> Result<Preset, PatternError> {
+    let mut lines = input.lines().enumerate();
+
+    // пустые строки перед заголовком пропускаются, как и в `detect`
+    match lines.find(|(_, line)| !line.trim().is_empty()) {
+        Some((_, first)) if is_header(first) => {}
+        found => {
+            let line_no = found.map_or(1, |(index, _)| index + 1);
+            return Err(PatternError::syntax(
+                line_no,
+                format!("expected '{}' header", HEADER),
+            ));
+        }
+    }
+
+    let mut comments = Vec::new();
+    let mut cells = Vec::new();
+
+    for (index, raw_line) in lines {
+        let line_no = index + 1;
+        let line = raw_line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            // #D и прочие расширения Life 1.05 встречаются и в файлах 1.06
+            let comment = comment.strip_prefix('D').unwrap_or(comment).trim();
+            if !comment.is_empty() {
+                comments.push(comment.to_string());
+            }
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let coord = match (parts.next(), parts.next(), parts.next()) {
+            (Some(x), Some(y), None) => x.parse::<i32>().ok().zip(y.parse::<i32>().ok()),
+            _ => None,
+        };
+        let (x, y) = coord.ok_or_else(|| {
+            PatternError::syntax(
+                line_no,
+                format!("expected 'x y' coordinates, got '{}'", line),
+            )
+        })?;
+        cells.push((x, y));
+    }
+
+    let description = if comments.is_empty() {
+        "Imported Life 1.06 pattern".to_string()
+    } else {
+        comments.join("\n")
+    };
+
+    Ok(Preset::new("Imported", description, cells))
+}
+
+/// сериализует живые клетки прямоугольника `[min; max]` в Life 1.06
+pub fn write_region(world: &World, min: Coord, max: Coord) -> String {
+    let mut cells: Vec<Coord> = world
+        .iter_active_cells()
+        .filter(|c| c.x >= min.x && c.x <= max.x && c.y >= min.y && c.y <= max.y)
+        .collect();
+    cells.sort_by_key(|c| (c.y, c.x));
+
+    let mut out = format!("{}\n", HEADER);
+    for cell in cells {
+        out.push_str(&format!("{} {}\n", cell.x, cell.y));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let preset = parse("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!(preset.cells.len(), 5);
+        assert!(preset.cells.contains(&(-1, 1)));
+        assert!(preset.cells.contains(&(0, -1)));
+    }
+
+    #[test]
+    fn test_parse_skips_leading_blank_lines() {
+        let preset = parse("\n  \n#Life 1.06\n0 0\n1 0\n").unwrap();
+        assert_eq!(preset.cells, vec![(0, 0), (1, 0)]);
+        assert_eq!(
+            parse("\n\n0 0\n").err(),
+            Some(PatternError::syntax(3, "expected '#Life 1.06' header"))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("0 0\n").err(),
+            Some(PatternError::syntax(1, "expected '#Life 1.06' header"))
+        );
+        assert!(matches!(
+            parse("#Life 1.06\n0 0\n1 two\n"),
+            Err(PatternError::Syntax { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse("#Life 1.06\n1 2 3\n"),
+            Err(PatternError::Syntax { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_round_trip_keeps_coordinates() {
+        let mut world = World::new();
+        world.set_cell(Coord::new(-100, 7), true);
+        world.set_cell(Coord::new(3, -2), true);
+        world.set_cell(Coord::new(500, 500), true);
+
+        let text = write_region(&world, Coord::new(-200, -200), Coord::new(200, 200));
+        assert_eq!(text, "#Life 1.06\n3 -2\n-100 7\n");
+
+        let parsed = parse(&text).unwrap();
+        assert_eq!(parsed.cells, vec![(3, -2), (-100, 7)]);
+    }
+}
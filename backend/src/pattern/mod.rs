@@ -0,0 +1,279 @@
+pub mod life106;
+pub mod plaintext;
+pub mod rle;
+
+use crate::presets::Preset;
+use crate::world::{Coord, World};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// наибольшее число клеток в загружаемом шаблоне
+pub const MAX_PATTERN_CELLS: usize = 1 << 22;
+
+/// наибольшая ширина и высота загружаемого шаблона
+pub const MAX_PATTERN_SIZE: u32 = 1 << 20;
+
+/// ошибка разбора файла с шаблоном
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PatternError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("missing RLE header line (x = .., y = ..)")]
+    MissingHeader,
+    #[error("could not detect pattern format")]
+    UnknownFormat,
+    #[error("unsupported pattern format: {0}")]
+    UnsupportedFormat(String),
+    #[error("pattern is too large: {0}")]
+    TooLarge(String),
+}
+
+impl PatternError {
+    pub fn syntax(line: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// поддерживаемые форматы файлов с шаблонами
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternFormat {
+    Rle,
+    Plaintext,
+    Life106,
+}
+
+impl PatternFormat {
+    /// определяет формат по содержимому
+    pub fn detect(input: &str) -> Result<Self, PatternError> {
+        let mut lines = input.lines().map(str::trim).filter(|l| !l.is_empty());
+        let first = lines.next().ok_or(PatternError::UnknownFormat)?;
+
+        if life106::is_header(first) {
+            return Ok(Self::Life106);
+        }
+        if first.starts_with("#Life") {
+            return Err(PatternError::UnsupportedFormat(first.to_string()));
+        }
+        if first.starts_with('#') || is_rle_header(first) {
+            return Ok(Self::Rle);
+        }
+        if first.starts_with('!') || is_plaintext_row(first) {
+            return Ok(Self::Plaintext);
+        }
+
+        Err(PatternError::UnknownFormat)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rle => "rle",
+            Self::Plaintext => "cells",
+            Self::Life106 => "life106",
+        }
+    }
+}
+
+impl FromStr for PatternFormat {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rle" => Ok(Self::Rle),
+            "cells" | "plaintext" => Ok(Self::Plaintext),
+            "life106" | "lif" | "life" => Ok(Self::Life106),
+            other => Err(PatternError::UnsupportedFormat(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for PatternFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// разбирает шаблон, определяя формат автоматически
+pub fn parse(input: &str) -> Result<Preset, PatternError> {
+    parse_as(PatternFormat::detect(input)?, input)
+}
+
+pub fn parse_as(format: PatternFormat, input: &str) -> Result<Preset, PatternError> {
+    let preset = match format {
+        PatternFormat::Rle => rle::parse(input),
+        PatternFormat::Plaintext => plaintext::parse(input),
+        PatternFormat::Life106 => life106::parse(input),
+    }?;
+    check_limits(&preset)?;
+    Ok(preset)
+}
+
+/// шаблон любого формата не больше `MAX_PATTERN_CELLS` клеток
+/// и `MAX_PATTERN_SIZE` в ширину и высоту
+fn check_limits(preset: &Preset) -> Result<(), PatternError> {
+    if preset.cells.len() > MAX_PATTERN_CELLS {
+        return Err(PatternError::TooLarge(format!(
+            "more than {} cells",
+            MAX_PATTERN_CELLS
+        )));
+    }
+    let span = |axis: fn(&(i32, i32)) -> i32| {
+        let min = preset.cells.iter().map(axis).min().unwrap_or(0);
+        let max = preset.cells.iter().map(axis).max().unwrap_or(0);
+        i64::from(max) - i64::from(min) + 1
+    };
+    let (width, height) = (span(|c| c.0), span(|c| c.1));
+    if width.max(height) > i64::from(MAX_PATTERN_SIZE) {
+        return Err(PatternError::TooLarge(format!(
+            "{}x{} is larger than {} cells across",
+            width, height, MAX_PATTERN_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// сериализует мир целиком или прямоугольник `region` в выбранный формат
+pub fn write(
+    format: PatternFormat,
+    world: &World,
+    region: Option<(Coord, Coord)>,
+    rule: Option<&str>,
+) -> String {
+    let Some((min, max)) = region.or_else(|| world.get_bounds()) else {
+        return match format {
+            PatternFormat::Rle => rle::write_world(world, rule),
+            PatternFormat::Plaintext => String::new(),
+            PatternFormat::Life106 => format!("{}\n", life106::HEADER),
+        };
+    };
+
+    match format {
+        PatternFormat::Rle => rle::write_region(world, min, max, rule),
+        PatternFormat::Plaintext => plaintext::write_region(world, min, max, None),
+        PatternFormat::Life106 => life106::write_region(world, min, max),
+    }
+}
+
+/// сериализует шаблон в выбранный формат
+pub fn write_preset(format: PatternFormat, preset: &Preset) -> String {
+    let world = preset.to_world();
+    match (format, world.get_bounds()) {
+        (PatternFormat::Plaintext, Some((min, max))) => {
+            plaintext::write_region(&world, min, max, Some(&preset.name))
+        }
+        _ => write(format, &world, None, preset.rule.as_deref()),
+    }
+}
+
+fn is_rle_header(line: &str) -> bool {
+    line.split_once('=')
+        .is_some_and(|(key, _)| key.trim() == "x")
+}
+
+fn is_plaintext_row(line: &str) -> bool {
+    line.chars().all(|c| matches!(c, '.' | 'O' | '*'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            PatternFormat::detect("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"),
+            Ok(PatternFormat::Rle)
+        );
+        assert_eq!(
+            PatternFormat::detect("x = 1, y = 1\no!"),
+            Ok(PatternFormat::Rle)
+        );
+        assert_eq!(
+            PatternFormat::detect("!Name: Glider\n.O.\n..O\nOOO\n"),
+            Ok(PatternFormat::Plaintext)
+        );
+        assert_eq!(
+            PatternFormat::detect("\n.O.\n"),
+            Ok(PatternFormat::Plaintext)
+        );
+        assert_eq!(
+            PatternFormat::detect("#Life 1.06\n0 0\n"),
+            Ok(PatternFormat::Life106)
+        );
+        assert!(matches!(
+            PatternFormat::detect("#Life 1.05\n#P 0 0\n*\n"),
+            Err(PatternError::UnsupportedFormat(_))
+        ));
+        assert_eq!(
+            PatternFormat::detect("hello"),
+            Err(PatternError::UnknownFormat)
+        );
+        assert_eq!(PatternFormat::detect(""), Err(PatternError::UnknownFormat));
+    }
+
+    #[test]
+    fn test_detect_and_parse_agree_on_life106_header() {
+        let input = "#Life 1.06 extended\n0 0\n";
+        assert!(matches!(
+            PatternFormat::detect(input),
+            Err(PatternError::UnsupportedFormat(_))
+        ));
+        assert!(life106::parse(input).is_err());
+        assert_eq!(
+            PatternFormat::detect("  #Life 1.06  \n0 0\n"),
+            Ok(PatternFormat::Life106)
+        );
+    }
+
+    #[test]
+    fn test_limits_apply_to_every_format() {
+        let far = MAX_PATTERN_SIZE as i32;
+        let life106 = format!("#Life 1.06\n0 0\n{} 0\n", far);
+        assert!(matches!(
+            parse_as(PatternFormat::Life106, &life106),
+            Err(PatternError::TooLarge(_))
+        ));
+        let extreme = format!("#Life 1.06\n{} 0\n{} 0\n", i32::MIN, i32::MAX);
+        assert!(matches!(parse(&extreme), Err(PatternError::TooLarge(_))));
+
+        let plaintext = format!("O{}O\n", ".".repeat(MAX_PATTERN_SIZE as usize));
+        assert!(matches!(
+            parse_as(PatternFormat::Plaintext, &plaintext),
+            Err(PatternError::TooLarge(_))
+        ));
+
+        let widest = format!("#Life 1.06\n0 0\n{} 0\n", far - 1);
+        assert_eq!(parse(&widest).unwrap().cells.len(), 2);
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("RLE".parse(), Ok(PatternFormat::Rle));
+        assert_eq!("cells".parse(), Ok(PatternFormat::Plaintext));
+        assert_eq!("life106".parse(), Ok(PatternFormat::Life106));
+        assert!("mcell".parse::<PatternFormat>().is_err());
+    }
+
+    #[test]
+    fn test_all_formats_round_trip() {
+        let preset = Preset::gosper_glider_gun();
+        let mut expected = preset.cells.clone();
+        expected.sort();
+
+        for format in [
+            PatternFormat::Rle,
+            PatternFormat::Plaintext,
+            PatternFormat::Life106,
+        ] {
+            let text = write_preset(format, &preset);
+            assert_eq!(PatternFormat::detect(&text), Ok(format));
+
+            let mut cells = parse(&text).unwrap().cells;
+            cells.sort();
+            assert_eq!(cells, expected, "round trip through {}", format);
+        }
+    }
+}
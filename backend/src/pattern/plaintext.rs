@@ -0,0 +1,133 @@
+use super::PatternError;
+use crate::presets::Preset;
+use crate::world::{Coord, World};
+
+/// разбирает формат `.cells`: строки-комментарии `!`, сетка из `.` и `O`
+pub fn parse(input: &str) -> Result<Preset, PatternError> {
+    let mut name: Option<String> = None;
+    let mut comments: Vec<String> = Vec::new();
+    let mut cells = Vec::new();
+    let mut y = 0i32;
+
+    for (index, raw_line) in input.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw_line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            match comment.strip_prefix("Name:") {
+                Some(value) => name = Some(value.trim().to_string()),
+                None => comments.push(comment.to_string()),
+            }
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | 'o' | '*' => cells.push((x as i32, y)),
+                other => {
+                    return Err(PatternError::syntax(
+                        line_no,
+                        format!("unexpected character '{}' in plaintext grid", other),
+                    ))
+                }
+            }
+        }
+        y += 1;
+    }
+
+    let description = comments
+        .into_iter()
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = if description.is_empty() {
+        "Imported plaintext pattern".to_string()
+    } else {
+        description
+    };
+
+    Ok(Preset::new(
+        name.unwrap_or_else(|| "Imported".to_string()),
+        description,
+        cells,
+    ))
+}
+
+/// сериализует прямоугольник `[min; max]` мира в `.cells`
+pub fn write_region(world: &World, min: Coord, max: Coord, name: Option<&str>) -> String {
+    let mut out = String::new();
+    if let Some(name) = name {
+        out.push_str(&format!("!Name: {}\n", name));
+    }
+
+    for y in min.y..=max.y {
+        let row: String = (min.x..=max.x)
+            .map(|x| {
+                if world.get_cell(Coord::new(x, y)) {
+                    'O'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        out.push_str(&row);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_parse_glider() {
+        let input = "!Name: Glider\n!The first spaceship.\n.O.\n..O\nOOO\n";
+        let preset = parse(input).unwrap();
+
+        assert_eq!(preset.name, "Glider");
+        assert_eq!(preset.description, "The first spaceship.");
+        assert_eq!(
+            sorted(preset.cells),
+            sorted(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+        );
+    }
+
+    #[test]
+    fn test_parse_blank_rows() {
+        let preset = parse("O\n\n..O\n").unwrap();
+        assert_eq!(sorted(preset.cells), vec![(0, 0), (2, 2)]);
+    }
+
+    #[test]
+    fn test_parse_error_has_line_number() {
+        assert_eq!(
+            parse("!Name: Bad\n.O.\n.X.\n").err(),
+            Some(PatternError::syntax(
+                3,
+                "unexpected character 'X' in plaintext grid"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let preset = Preset::beacon();
+        let world = preset.to_world();
+        let (min, max) = world.get_bounds().unwrap();
+
+        let text = write_region(&world, min, max, Some("Beacon"));
+        assert!(text.starts_with("!Name: Beacon\nOO..\nOO..\n..OO\n..OO\n"));
+
+        let parsed = parse(&text).unwrap();
+        assert_eq!(parsed.name, "Beacon");
+        assert_eq!(sorted(parsed.cells), sorted(preset.cells));
+    }
+}
@@ -0,0 +1,484 @@
+use super::{PatternError, MAX_PATTERN_CELLS, MAX_PATTERN_SIZE};
+use crate::presets::Preset;
+use crate::world::{Coord, World};
+
+/// максимальная длина строки тела RLE при экспорте
+const LINE_WIDTH: usize = 70;
+
+/// разбирает RLE (`#N/#C/#O` комментарии, заголовок `x = , y = , rule =`, тело до `!`)
+pub fn parse(input: &str) -> Result<Preset, PatternError> {
+    let mut name: Option<String> = None;
+    let mut comments: Vec<String> = Vec::new();
+    let mut author: Option<String> = None;
+    let mut rule: Option<String> = None;
+    let mut origin = Coord::new(0, 0);
+    let mut header_seen = false;
+
+    let mut cells = Vec::new();
+    let mut x = 0i32;
+    let mut y = 0i32;
+    let mut run: Option<u32> = None;
+    // префикс `p`..`y` многосостоянийной записи: состояния 25 и выше
+    let mut prefix: Option<char> = None;
+    let mut finished = false;
+
+    for (index, raw_line) in input.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw_line.trim();
+
+        if finished {
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let text = chars.as_str().trim();
+            match kind {
+                Some('N') => name = Some(text.to_string()),
+                Some('C') | Some('c') => {
+                    if let Some(pos) = text.strip_prefix("XRLE") {
+                        origin = parse_cxrle_pos(pos).unwrap_or(origin);
+                    } else {
+                        comments.push(text.to_string());
+                    }
+                }
+                Some('O') => author = Some(text.to_string()),
+                Some('P') | Some('R') => origin = parse_offset(text, line_no)?,
+                _ => {}
+            }
+            continue;
+        }
+
+        if !header_seen {
+            rule = parse_header(line, line_no)?;
+            header_seen = true;
+            continue;
+        }
+
+        for c in line.chars() {
+            if let Some(p) = prefix {
+                if !c.is_ascii_uppercase() || c > 'X' {
+                    return Err(PatternError::syntax(
+                        line_no,
+                        format!("expected state letter after '{}'", p),
+                    ));
+                }
+            }
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap();
+                    let value = run
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(digit));
+                    run = Some(
+                        value
+                            .ok_or_else(|| PatternError::syntax(line_no, "run count too large"))?,
+                    );
+                }
+                'b' | '.' => {
+                    x = advance(x, take_run(&mut run), line_no)?;
+                }
+                'p'..='y' => prefix = Some(c),
+                'o' | 'A'..='X' => {
+                    let state = match (c, prefix.take()) {
+                        ('o', _) => 1,
+                        (letter, p) => {
+                            let high = p.map_or(0, |p| (p as u32 - 'p' as u32 + 1) * 24);
+                            let state = high + (letter as u32 - 'A' as u32) + 1;
+                            u8::try_from(state).map_err(|_| {
+                                PatternError::syntax(line_no, format!("state {} is too large", state))
+                            })?
+                        }
+                    };
+                    let count = take_run(&mut run);
+                    if cells.len() + count as usize > MAX_PATTERN_CELLS {
+                        return Err(PatternError::syntax(
+                            line_no,
+                            format!("pattern has more than {} cells", MAX_PATTERN_CELLS),
+                        ));
+                    }
+                    let end = advance(x, count, line_no)?;
+                    if origin.x.checked_add(end).is_none() || origin.y.checked_add(y).is_none() {
+                        return Err(PatternError::syntax(
+                            line_no,
+                            "pattern position is out of range",
+                        ));
+                    }
+                    for cell_x in x..end {
+                        cells.push((origin.x + cell_x, origin.y + y, state));
+                    }
+                    x = end;
+                }
+                '$' => {
+                    y = advance(y, take_run(&mut run), line_no)?;
+                    x = 0;
+                }
+                '!' => {
+                    finished = true;
+                    break;
+                }
+                c if c.is_whitespace() => {}
+                other => {
+                    return Err(PatternError::syntax(
+                        line_no,
+                        format!("unexpected character '{}' in RLE body", other),
+                    ))
+                }
+            }
+        }
+    }
+
+    if !header_seen {
+        return Err(PatternError::MissingHeader);
+    }
+
+    let mut description = comments.join("\n");
+    if let Some(author) = author {
+        if !description.is_empty() {
+            description.push('\n');
+        }
+        description.push_str(&format!("Author: {}", author));
+    }
+    if description.is_empty() {
+        description = "Imported RLE pattern".to_string();
+    }
+
+    let mut preset = Preset::multi_state(
+        name.unwrap_or_else(|| "Imported".to_string()),
+        description,
+        cells,
+    );
+    preset.rule = rule;
+    Ok(preset)
+}
+
+/// сериализует весь мир в RLE
+pub fn write_world(world: &World, rule: Option<&str>) -> String {
+    match world.get_bounds() {
+        Some((min, max)) => write_region(world, min, max, rule),
+        None => write_header(0, 0, rule) + "!\n",
+    }
+}
+
+/// сериализует прямоугольник `[min; max]` мира в RLE
+pub fn write_region(world: &World, min: Coord, max: Coord, rule: Option<&str>) -> String {
+    let width = (i64::from(max.x) - i64::from(min.x) + 1).clamp(0, i64::from(u32::MAX)) as u32;
+    let height = (i64::from(max.y) - i64::from(min.y) + 1).clamp(0, i64::from(u32::MAX)) as u32;
+
+    let mut out = format!("#CXRLE Pos={},{}\n", min.x, min.y);
+    out.push_str(&write_header(width, height, rule));
+
+    // миры с состояниями больше 1 пишутся многосостоянийной записью `.`/`A`..
+    let multi_state = world.iter_cell_states().any(|(_, state)| state > 1);
+    let dead = if multi_state { "." } else { "b" };
+
+    let mut tokens: Vec<String> = Vec::new();
+    let mut pending_rows = 0u32;
+
+    for y in min.y..=max.y {
+        let mut row: Vec<(String, u32)> = Vec::new();
+        for x in min.x..=max.x {
+            let tag = state_tag(world.get_state(Coord::new(x, y)), multi_state);
+            match row.last_mut() {
+                Some((last, count)) if *last == tag => *count += 1,
+                _ => row.push((tag, 1)),
+            }
+        }
+        // хвост из мёртвых клеток в конце строки не записывается
+        if row.last().is_some_and(|(tag, _)| tag == dead) {
+            row.pop();
+        }
+
+        if row.is_empty() {
+            pending_rows += 1;
+            continue;
+        }
+
+        if !tokens.is_empty() || pending_rows > 0 {
+            let rows = if tokens.is_empty() {
+                pending_rows
+            } else {
+                pending_rows + 1
+            };
+            if rows > 0 {
+                tokens.push(run_token("$", rows));
+            }
+        }
+        pending_rows = 0;
+
+        tokens.extend(row.into_iter().map(|(tag, count)| run_token(&tag, count)));
+    }
+    tokens.push("!".to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if line.len() + token.len() > LINE_WIDTH {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+fn write_header(width: u32, height: u32, rule: Option<&str>) -> String {
+    match rule {
+        Some(rule) => format!("x = {}, y = {}, rule = {}\n", width, height, rule),
+        None => format!("x = {}, y = {}\n", width, height),
+    }
+}
+
+fn run_token(tag: &str, count: u32) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", count, tag)
+    }
+}
+
+/// `b`/`o` для двух состояний, иначе `.`, `A`..`X`, `pA`..`yO`
+fn state_tag(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => "b".to_string(),
+        (_, false) => "o".to_string(),
+        (0, true) => ".".to_string(),
+        (state, true) => {
+            let index = state as u32 - 1;
+            let letter = char::from(b'A' + (index % 24) as u8);
+            match index / 24 {
+                0 => letter.to_string(),
+                high => format!("{}{}", char::from(b'o' + high as u8), letter),
+            }
+        }
+    }
+}
+
+fn take_run(run: &mut Option<u32>) -> u32 {
+    run.take().unwrap_or(1)
+}
+
+/// позиция в теле RLE после `run` клеток или строк; шаблон не шире `MAX_PATTERN_SIZE`
+fn advance(position: i32, run: u32, line_no: usize) -> Result<i32, PatternError> {
+    let next = i64::from(position) + i64::from(run);
+    if next > i64::from(MAX_PATTERN_SIZE) {
+        return Err(PatternError::syntax(
+            line_no,
+            format!("pattern is larger than {} cells across", MAX_PATTERN_SIZE),
+        ));
+    }
+    Ok(next as i32)
+}
+
+/// `x = 3, y = 3, rule = B3/S23`; возвращает правило, если оно указано
+fn parse_header(line: &str, line_no: usize) -> Result<Option<String>, PatternError> {
+    let mut rule = None;
+    let mut has_x = false;
+    let mut has_y = false;
+
+    for part in line.split(',') {
+        let (key, value) = part.split_once('=').ok_or_else(|| {
+            PatternError::syntax(line_no, format!("malformed header field '{}'", part.trim()))
+        })?;
+        let (key, value) = (key.trim(), value.trim());
+
+        match key {
+            "x" | "y" => {
+                value.parse::<u32>().map_err(|_| {
+                    PatternError::syntax(line_no, format!("invalid pattern size '{}'", value))
+                })?;
+                if key == "x" {
+                    has_x = true;
+                } else {
+                    has_y = true;
+                }
+            }
+            "rule" => rule = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    if !has_x || !has_y {
+        return Err(PatternError::syntax(
+            line_no,
+            "RLE header must contain x and y",
+        ));
+    }
+
+    Ok(rule)
+}
+
+fn parse_offset(text: &str, line_no: usize) -> Result<Coord, PatternError> {
+    let mut parts = text.split_whitespace().map(|p| p.parse::<i32>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(x)), Some(Ok(y))) => Ok(Coord::new(x, y)),
+        _ => Err(PatternError::syntax(line_no, "expected '#P x y'")),
+    }
+}
+
+/// `#CXRLE Pos=-3,5 Gen=0` - расширение Golly для позиции шаблона
+fn parse_cxrle_pos(text: &str) -> Option<Coord> {
+    let pos = text
+        .split_whitespace()
+        .find_map(|p| p.strip_prefix("Pos="))?;
+    let (x, y) = pos.split_once(',')?;
+    Some(Coord::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#N Glider\n\
+                          #O Richard K. Guy\n\
+                          #C The smallest, most common, and first discovered spaceship.\n\
+                          x = 3, y = 3, rule = B3/S23\n\
+                          bob$2bo$3o!\n";
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_parse_glider() {
+        let preset = parse(GLIDER).unwrap();
+        assert_eq!(preset.name, "Glider");
+        assert_eq!(preset.rule.as_deref(), Some("B3/S23"));
+        assert!(preset.description.contains("first discovered spaceship"));
+        assert!(preset.description.contains("Richard K. Guy"));
+        assert_eq!(
+            sorted(preset.cells),
+            sorted(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+        );
+    }
+
+    #[test]
+    fn test_parse_run_counts_and_blank_rows() {
+        let preset = parse("x = 5, y = 4\n2o3b$2$5o!").unwrap();
+        assert_eq!(preset.name, "Imported");
+        assert_eq!(preset.rule, None);
+        assert_eq!(
+            sorted(preset.cells),
+            vec![(0, 0), (0, 3), (1, 0), (1, 3), (2, 3), (3, 3), (4, 3)]
+        );
+    }
+
+    #[test]
+    fn test_parse_multiline_body_and_trailing_text() {
+        let preset = parse("x = 3, y = 1\no\nbo! this is ignored\n").unwrap();
+        assert_eq!(sorted(preset.cells), vec![(0, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            parse("#N Nothing\n"),
+            Err(PatternError::MissingHeader)
+        ));
+        assert!(matches!(
+            parse("x = 3, y = 3\nbo$2bz!"),
+            Err(PatternError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("x = three, y = 3\no!"),
+            Err(PatternError::Syntax { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_huge_runs() {
+        for body in [
+            "2147483647b2147483647bo!",
+            "2147483647$2147483647$o!",
+            "4294967295o!",
+            "1048576bo!",
+        ] {
+            assert!(
+                matches!(
+                    parse(&format!("x = 1, y = 1\n{}", body)),
+                    Err(PatternError::Syntax { line: 2, .. })
+                ),
+                "{}",
+                body
+            );
+        }
+        // несколько длинных строк упираются в общий предел клеток
+        let rows = "1000000o$".repeat(MAX_PATTERN_CELLS / 1000000 + 1);
+        assert!(parse(&format!("x = 1000000, y = 5\n{}!", rows)).is_err());
+        assert!(parse("#P 2147483647 0\nx = 2, y = 1\n2o!").is_err());
+        assert_eq!(parse("x = 1, y = 1\n1048575bo!").unwrap().cells.len(), 1);
+    }
+
+    #[test]
+    fn test_write_world_round_trip() {
+        let preset = Preset::gosper_glider_gun();
+        let world = preset.to_world();
+
+        let rle = write_world(&world, Some("B3/S23"));
+        assert!(rle.contains("x = 36, y = 9, rule = B3/S23"));
+        assert!(rle.lines().all(|l| l.len() <= LINE_WIDTH));
+
+        let parsed = parse(&rle).unwrap();
+        assert_eq!(sorted(parsed.cells), sorted(preset.cells));
+    }
+
+    #[test]
+    fn test_write_region_keeps_position() {
+        let mut world = World::new();
+        world.set_cell(Coord::new(-5, 10), true);
+        world.set_cell(Coord::new(-3, 12), true);
+        world.set_cell(Coord::new(50, 50), true);
+
+        let rle = write_region(&world, Coord::new(-5, 10), Coord::new(-3, 12), None);
+        assert!(rle.starts_with("#CXRLE Pos=-5,10\nx = 3, y = 3\n"));
+        assert!(rle.contains("o2$2bo!"));
+
+        let parsed = parse(&rle).unwrap();
+        assert_eq!(sorted(parsed.cells), vec![(-5, 10), (-3, 12)]);
+    }
+
+    #[test]
+    fn test_multi_state_round_trip() {
+        let preset = parse("x = 4, y = 2, rule = B2/S345/C4\n.AB$C2.pA!").unwrap();
+        let mut cells: Vec<_> = preset.cell_states().collect();
+        cells.sort();
+        assert_eq!(
+            cells,
+            vec![((0, 1), 3), ((1, 0), 1), ((2, 0), 2), ((3, 1), 25)]
+        );
+
+        let mut world = preset.to_world();
+        world.set_state(Coord::new(5, 1), 255);
+        let rle = write_world(&world, preset.rule.as_deref());
+        assert!(rle.contains(".AB$C2.pA.yO!"));
+        assert_eq!(parse(&rle).unwrap().to_world(), world);
+
+        assert!(matches!(
+            parse("x = 1, y = 1\npo!"),
+            Err(PatternError::Syntax { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_region_header_spans_full_range() {
+        let world = World::new();
+        let min = Coord::new(-2_000_000_000, 0);
+        let max = Coord::new(2_000_000_000, -1);
+        assert!(write_region(&world, min, max, None).contains("x = 4000000001, y = 0"));
+    }
+
+    #[test]
+    fn test_write_empty_world() {
+        let rle = write_world(&World::new(), None);
+        assert_eq!(rle, "x = 0, y = 0\n!\n");
+        assert!(parse(&rle).unwrap().cells.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::world::{Coord, World};
+use crate::world::{Ant, Coord, Heading, Rng, Soup, Symmetry, Topology, World};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +9,22 @@ pub struct Preset {
     pub description: String,
     
     pub cells: Vec<(i32, i32)>,
+
+    /// состояния клеток из `cells` по порядку; пусто - все клетки в состоянии 1
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub states: Vec<u8>,
+
+    /// правило, для которого задуман шаблон (например, из заголовка RLE)
+    #[serde(default)]
+    pub rule: Option<String>,
+
+    /// топология, в которой ставится опыт; `None` - оставить текущую
+    #[serde(default)]
+    pub topology: Option<Topology>,
+
+    /// муравьи тьюрмитов: `(x, y, heading)`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ants: Vec<(i32, i32, Heading)>,
 }
 
 impl Preset {
@@ -17,21 +33,74 @@ impl Preset {
             name: name.into(),
             description: description.into(),
             cells,
+            states: Vec::new(),
+            rule: None,
+            topology: None,
+            ants: Vec::new(),
+        }
+    }
+
+    /// шаблон с клетками в разных состояниях: `(x, y, state)`
+    pub fn multi_state(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        cells: Vec<(i32, i32, u8)>,
+    ) -> Self {
+        let mut preset = Self::new(
+            name,
+            description,
+            cells.iter().map(|&(x, y, _)| (x, y)).collect(),
+        );
+        if cells.iter().any(|&(_, _, state)| state != 1) {
+            preset.states = cells.into_iter().map(|(_, _, state)| state).collect();
         }
+        preset
+    }
+
+    /// клетки вместе с состояниями
+    pub fn cell_states(&self) -> impl Iterator<Item = ((i32, i32), u8)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, &cell)| (cell, self.states.get(index).copied().unwrap_or(1)))
+    }
+
+    pub fn with_rule(mut self, rule: impl Into<String>) -> Self {
+        self.rule = Some(rule.into());
+        self
     }
     
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+
+    pub fn with_ant(mut self, x: i32, y: i32, heading: Heading) -> Self {
+        self.ants.push((x, y, heading));
+        self
+    }
+
     pub fn load_into(&self, world: &mut World, offset: Coord) {
         world.clear();
-        for &(x, y) in &self.cells {
+        if let Some(topology) = self.topology {
+            world.set_topology(topology);
+        }
+        for ((x, y), state) in self.cell_states() {
             let coord = Coord::new(offset.x + x, offset.y + y);
-            world.set_cell(coord, true);
+            world.set_state(coord, state);
+        }
+        for &(x, y, heading) in &self.ants {
+            world.add_ant(Ant::new(Coord::new(offset.x + x, offset.y + y), heading));
         }
     }
     
     pub fn to_world(&self) -> World {
-        let mut world = World::new();
-        for &(x, y) in &self.cells {
-            world.set_cell(Coord::new(x, y), true);
+        let mut world = World::with_topology(self.topology.unwrap_or_default());
+        for ((x, y), state) in self.cell_states() {
+            world.set_state(Coord::new(x, y), state);
+        }
+        for &(x, y, heading) in &self.ants {
+            world.add_ant(Ant::new(Coord::new(x, y), heading));
         }
         world
     }
@@ -57,6 +126,25 @@ impl Preset {
             Self::block(),
             Self::beehive(),
             Self::loaf(),
+            Self::torus_glider(),
+            Self::bounded_r_pentomino(),
+            Self::brians_brain_spaceship(),
+            Self::brians_brain_collision(),
+            Self::star_wars_spaceship(),
+            Self::wireworld_diode(),
+            Self::wireworld_or_gate(),
+            Self::wireworld_clock(),
+            Self::langtons_ant(),
+            Self::turmite_llrr(),
+            Self::turmite_rlr(),
+            Self::rule_30(),
+            Self::rule_90(),
+            Self::rule_110(),
+            Self::bosco_glider(),
+            Self::critters_glider(),
+            Self::cyclic_spirals(),
+            Self::greenberg_hastings_spiral(),
+            Self::orbium(),
         ]
     }
  
@@ -75,34 +163,18 @@ impl Preset {
     }
 
     pub fn random_small() -> Self {
-        let mut cells = Vec::new();
-        for x in -10..=10 {
-            for y in -10..=10 {
-                if (x * 7 + y * 13) % 3 == 0 {
-                    cells.push((x, y));
-                }
-            }
-        }
         Self::new(
             "Random Small",
             "Случайное распределение ячеек (20x20)",
-            cells,
+            soup_cells(Coord::new(-10, -10), 21, 1.0 / 3.0, 1),
         )
     }
 
     pub fn random_medium() -> Self {
-        let mut cells = Vec::new();
-        for x in -25..=25 {
-            for y in -25..=25 {
-                if (x * 11 + y * 17) % 4 == 0 {
-                    cells.push((x, y));
-                }
-            }
-        }
         Self::new(
             "Random Medium",
             "Случайное распределение ячеек (50x50)",
-            cells,
+            soup_cells(Coord::new(-25, -25), 51, 0.25, 2),
         )
     }
     
@@ -189,6 +261,278 @@ impl Preset {
         )
     }
  
+    pub fn torus_glider() -> Self {
+        Self::new(
+            "Torus Glider",
+            "Глайдер на торе 32x32 - уходит за край и возвращается через 128 поколений",
+            Self::glider().cells,
+        )
+        .with_topology(Topology::Torus {
+            width: 32,
+            height: 32,
+        })
+    }
+
+    pub fn bounded_r_pentomino() -> Self {
+        Self::new(
+            "Bounded R-pentomino",
+            "R-пентамино в коробке 64x64 с мёртвой границей",
+            Self::r_pentomino()
+                .cells
+                .into_iter()
+                .map(|(x, y)| (x + 31, y + 31))
+                .collect(),
+        )
+        .with_topology(Topology::Bounded {
+            width: 64,
+            height: 64,
+        })
+    }
+
+    pub fn brians_brain_spaceship() -> Self {
+        Self::multi_state(
+            "Brian's Brain Spaceship",
+            "Две включённые клетки и два угасающих следа - летит вправо со скоростью c",
+            vec![
+                (0, 0, 2), (1, 0, 1),
+                (0, 1, 2), (1, 1, 1),
+            ],
+        )
+        .with_rule("B2/S/C3")
+    }
+
+    pub fn brians_brain_collision() -> Self {
+        Self::multi_state(
+            "Brian's Brain Collision",
+            "Два встречных корабля Brian's Brain - столкновение рождает хаотичный рост",
+            vec![
+                (0, 0, 2), (1, 0, 1),
+                (0, 1, 2), (1, 1, 1),
+                (20, 1, 1), (21, 1, 2),
+                (20, 2, 1), (21, 2, 2),
+            ],
+        )
+        .with_rule("B2/S/C3")
+    }
+
+    pub fn star_wars_spaceship() -> Self {
+        Self::multi_state(
+            "Star Wars Spaceship",
+            "Корабль правила Star Wars с двухступенчатым следом - летит вправо со скоростью c",
+            vec![
+                (0, 0, 3), (1, 0, 2), (2, 0, 1),
+                (0, 1, 3), (1, 1, 2), (2, 1, 1),
+            ],
+        )
+        .with_rule("B2/S345/C4")
+    }
+
+    pub fn wireworld_diode() -> Self {
+        Self::multi_state(
+            "WireWorld Diode",
+            "Диод: электрон проходит слева направо, а идущий справа налево гаснет",
+            circuit(&[
+                "....##....",
+                "tH###.####",
+                "....##....",
+            ]),
+        )
+        .with_rule("WireWorld")
+    }
+
+    pub fn wireworld_or_gate() -> Self {
+        Self::multi_state(
+            "WireWorld OR Gate",
+            "Вентиль ИЛИ: электрон с любого из двух входов выходит на общий провод",
+            circuit(&[
+                "tH.......",
+                "..#......",
+                ".########",
+                "..#......",
+                "##.......",
+            ]),
+        )
+        .with_rule("WireWorld")
+    }
+
+    pub fn wireworld_clock() -> Self {
+        Self::multi_state(
+            "WireWorld Clock",
+            "Генератор: электрон в кольце выпускает импульс на выход каждые 10 поколений",
+            circuit(&[
+                ".tH##.....",
+                "#....#####",
+                ".####.....",
+            ]),
+        )
+        .with_rule("WireWorld")
+    }
+
+    pub fn langtons_ant() -> Self {
+        Self::new(
+            "Langton's Ant",
+            "Муравей Лэнгтона: ~10000 шагов хаоса, затем строит бесконечную магистраль",
+            vec![],
+        )
+        .with_rule("RL")
+        .with_ant(0, 0, Heading::North)
+    }
+
+    pub fn turmite_llrr() -> Self {
+        Self::new(
+            "Turmite LLRR",
+            "Четырёхцветный тьюрмит, растущий симметричным узором",
+            vec![],
+        )
+        .with_rule("LLRR")
+        .with_ant(0, 0, Heading::North)
+    }
+
+    pub fn turmite_rlr() -> Self {
+        Self::new(
+            "Turmite RLR",
+            "Трёхцветный тьюрмит, растущий хаотично без магистрали",
+            vec![],
+        )
+        .with_rule("RLR")
+        .with_ant(0, 0, Heading::North)
+    }
+
+    pub fn rule_30() -> Self {
+        Self::new(
+            "Rule 30",
+            "Элементарный автомат Вольфрама: из одной клетки растёт хаотичный треугольник",
+            vec![(0, 0)],
+        )
+        .with_rule("W30")
+    }
+
+    pub fn rule_90() -> Self {
+        Self::new(
+            "Rule 90",
+            "Элементарный автомат Вольфрама: треугольник Серпинского",
+            vec![(0, 0)],
+        )
+        .with_rule("W90")
+    }
+
+    pub fn rule_110() -> Self {
+        Self::new(
+            "Rule 110",
+            "Элементарный автомат Вольфрама, полный по Тьюрингу: структуры растут влево",
+            vec![(0, 0)],
+        )
+        .with_rule("W110")
+    }
+
+    pub fn bosco_glider() -> Self {
+        Self::new(
+            "Bosco Glider",
+            "Корабль Larger than Life в Bosco's Rule - за 16 поколений сдвигается на 8 клеток по диагонали",
+            picture(&[
+                "...####....",
+                ".##.#####..",
+                ".#...#####.",
+                "#.....####.",
+                "##.....####",
+                "###....####",
+                "####..####.",
+                ".#########.",
+                ".########..",
+                "..######...",
+                "....##.....",
+            ]),
+        )
+        .with_rule("R5,C0,M1,S34..58,B34..45,NM")
+    }
+
+    pub fn critters_glider() -> Self {
+        // клетки стоят на нечётных координатах, чтобы попасть в блоки Margolus как надо
+        Self::new(
+            "Critters Glider",
+            "Корабль обратимого блочного правила Critters - за 4 поколения сдвигается на 2 клетки вниз",
+            vec![(1, 1), (4, 1), (2, 2), (3, 2)],
+        )
+        .with_rule("MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0")
+    }
+
+    pub fn cyclic_spirals() -> Self {
+        Self::multi_state(
+            "Cyclic Spirals",
+            "Циклический автомат 313 на торе 96x96: из случайных цветов за пару сотен поколений вырастают спирали",
+            colour_soup(96, 3, 1),
+        )
+        .with_rule("R3/T5/C3/NM")
+        .with_topology(Topology::Torus {
+            width: 96,
+            height: 96,
+        })
+    }
+
+    pub fn greenberg_hastings_spiral() -> Self {
+        // оборванный фронт: возбуждённый ряд, за ним рефрактерный, и свободный конец волны
+        // закручивается в спираль
+        let cells = (48..96)
+            .flat_map(|x| [(x, 48, 1), (x, 49, 2)])
+            .collect();
+        Self::multi_state(
+            "Greenberg-Hastings Spiral",
+            "Возбудимая среда Гринберга-Хастингса: оборванная волна сворачивается в вечную спираль",
+            cells,
+        )
+        .with_rule("GH/R1/T1/C5/NN")
+        .with_topology(Topology::Torus {
+            width: 96,
+            height: 96,
+        })
+    }
+
+    pub fn orbium() -> Self {
+        // «Орбиум» из атласа Lenia Берта Чана, значения квантованы в байты
+        const ORBIUM: [[u8; 20]; 20] = [
+            [0, 0, 0, 0, 0, 0, 26, 36, 26, 0, 0, 8, 8, 0, 0, 76, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 20, 61, 76, 76, 46, 36, 38, 41, 38, 23, 51, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 38, 87, 112, 117, 97, 46, 36, 28, 33, 48, 46, 115, 0, 0, 0],
+            [0, 0, 0, 0, 15, 33, 99, 128, 128, 94, 15, 0, 0, 0, 5, 41, 173, 0, 0, 0],
+            [0, 0, 0, 28, 43, 43, 84, 102, 97, 71, 36, 0, 0, 0, 0, 0, 46, 107, 0, 0],
+            [0, 0, 23, 46, 33, 15, 20, 66, 82, 82, 69, 0, 0, 0, 0, 0, 0, 209, 0, 0],
+            [69, 0, 41, 31, 0, 0, 0, 64, 97, 112, 115, 87, 0, 0, 0, 0, 0, 56, 43, 0],
+            [0, 18, 51, 5, 0, 0, 0, 79, 122, 145, 153, 145, 0, 0, 0, 0, 0, 0, 125, 0],
+            [0, 150, 48, 0, 0, 0, 0, 51, 145, 176, 194, 194, 125, 0, 0, 0, 0, 0, 92, 0],
+            [0, 148, 48, 0, 0, 0, 0, 0, 171, 212, 230, 235, 222, 31, 0, 0, 0, 0, 56, 18],
+            [0, 0, 117, 0, 0, 0, 0, 0, 178, 237, 255, 255, 255, 156, 0, 0, 0, 0, 46, 28],
+            [0, 0, 209, 0, 0, 0, 0, 0, 120, 255, 255, 250, 255, 245, 69, 0, 0, 0, 48, 26],
+            [0, 0, 117, 0, 0, 0, 0, 0, 64, 255, 255, 214, 235, 247, 138, 36, 10, 26, 54, 13],
+            [0, 0, 0, 102, 0, 0, 0, 0, 23, 204, 255, 209, 204, 217, 161, 79, 46, 48, 51, 3],
+            [0, 0, 0, 92, 26, 0, 0, 0, 13, 138, 219, 201, 189, 184, 153, 99, 71, 61, 33, 0],
+            [0, 0, 0, 3, 76, 18, 0, 0, 20, 92, 163, 178, 163, 153, 130, 99, 74, 48, 10, 0],
+            [0, 0, 0, 0, 26, 61, 36, 26, 38, 74, 115, 135, 133, 117, 102, 79, 54, 20, 0, 0],
+            [0, 0, 0, 0, 0, 20, 54, 54, 56, 74, 92, 99, 94, 84, 66, 46, 23, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 8, 33, 48, 56, 61, 61, 59, 46, 33, 13, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0, 5, 15, 20, 23, 18, 13, 3, 0, 0, 0, 0, 0],
+        ];
+        let cells = ORBIUM
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|&(_, &state)| state != 0)
+                    .map(move |(x, &state)| (54 + x as i32, 54 + y as i32, state))
+            })
+            .collect();
+        Self::multi_state(
+            "Orbium",
+            "Непрерывный автомат Lenia: мягкое «существо» плывёт по тору 128x128",
+            cells,
+        )
+        .with_rule("Lenia/R13/T10/M0.15/S0.015/B1")
+        .with_topology(Topology::Torus {
+            width: 128,
+            height: 128,
+        })
+    }
+
     pub fn lightweight_spaceship() -> Self {
         Self::new(
             "LWSS",
@@ -377,6 +721,63 @@ impl Preset {
     }
 }
 
+/// квадратный суп с постоянным зерном, чтобы шаблон был одинаковым при каждой загрузке
+fn soup_cells(origin: Coord, side: u32, density: f64, seed: u64) -> Vec<(i32, i32)> {
+    Soup::new(origin, side, side, density, Symmetry::C1)
+        .expect("preset soup parameters are valid")
+        .cells(seed)
+        .into_iter()
+        .map(|cell| (cell.x, cell.y))
+        .collect()
+}
+
+/// квадрат случайных цветов `0..states` с постоянным зерном; нули не хранятся
+fn colour_soup(side: i32, states: u8, seed: u64) -> Vec<(i32, i32, u8)> {
+    let mut rng = Rng::new(seed);
+    let mut cells = Vec::new();
+    for y in 0..side {
+        for x in 0..side {
+            let state = (rng.next_u64() % u64::from(states)) as u8;
+            if state != 0 {
+                cells.push((x, y, state));
+            }
+        }
+    }
+    cells
+}
+
+/// шаблон по строкам: `#` - живая клетка
+fn picture(rows: &[&str]) -> Vec<(i32, i32)> {
+    let mut cells = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, ch) in row.chars().enumerate() {
+            if ch == '#' {
+                cells.push((x as i32, y as i32));
+            }
+        }
+    }
+    cells
+}
+
+/// схема WireWorld по строкам: `#` - проводник, `H` - голова, `t` - хвост
+fn circuit(rows: &[&str]) -> Vec<(i32, i32, u8)> {
+    use crate::automaton::wireworld::{CONDUCTOR, HEAD, TAIL};
+
+    let mut cells = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, ch) in row.chars().enumerate() {
+            let state = match ch {
+                '#' => CONDUCTOR,
+                'H' => HEAD,
+                't' => TAIL,
+                _ => continue,
+            };
+            cells.push((x as i32, y as i32, state));
+        }
+    }
+    cells
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,4 +959,111 @@ mod tests {
         let preset = Preset::acorn();
         assert_eq!(preset.cells.len(), 7);
     }
+
+    #[test]
+    fn test_topology_presets() {
+        let preset = Preset::torus_glider();
+        let world = preset.to_world();
+        assert_eq!(world.topology(), preset.topology.unwrap());
+        assert_eq!(world.active_cell_count(), 5);
+
+        let mut world = World::new();
+        Preset::bounded_r_pentomino().load_into(&mut world, Coord::new(0, 0));
+        assert!(world.topology().is_finite());
+        assert_eq!(world.active_cell_count(), 5);
+
+        // шаблон без топологии не меняет текущую
+        Preset::glider().load_into(&mut world, Coord::new(0, 0));
+        assert!(world.topology().is_finite());
+    }
+
+    #[test]
+    fn test_multi_state_presets() {
+        use crate::automaton::{Generations, Rule};
+
+        let cases = [
+            (Preset::brians_brain_spaceship(), Generations::brians_brain()),
+            (Preset::star_wars_spaceship(), Generations::star_wars()),
+        ];
+        for (preset, rule) in cases {
+            assert_eq!(preset.rule.as_deref(), Some(rule.rulestring()));
+            assert_eq!(preset.states.len(), preset.cells.len());
+
+            // корабль через 10 поколений - тот же шаблон, сдвинутый на 10 вправо
+            let mut world = preset.to_world();
+            for _ in 0..10 {
+                world = rule.apply(&world);
+            }
+            let mut expected = World::new();
+            preset.load_into(&mut expected, Coord::new(10, 0));
+            assert_eq!(world, expected, "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn test_turmite_presets() {
+        let mut world = World::new();
+        Preset::langtons_ant().load_into(&mut world, Coord::new(5, -3));
+        assert_eq!(world.active_cell_count(), 0);
+        assert_eq!(
+            world.ants(),
+            &[Ant::new(Coord::new(5, -3), Heading::North)]
+        );
+
+        for preset in [Preset::turmite_llrr(), Preset::turmite_rlr()] {
+            let rule = crate::automaton::parse_rule(preset.rule.as_deref().unwrap()).unwrap();
+            assert!(rule.moves_ants());
+            assert_eq!(preset.to_world().ants().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_bosco_glider_preset() {
+        use crate::automaton::{LargerThanLife, Rule};
+
+        let preset = Preset::bosco_glider();
+        let rule = LargerThanLife::bosco();
+        assert_eq!(preset.rule.as_deref(), Some(rule.rulestring()));
+
+        let world = rule.jump(&preset.to_world(), 16);
+        let mut expected = World::new();
+        preset.load_into(&mut expected, Coord::new(8, 8));
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn test_critters_glider_preset() {
+        use crate::automaton::{Margolus, Rule};
+
+        let preset = Preset::critters_glider();
+        let rule = Margolus::critters();
+        assert_eq!(preset.rule.as_deref(), Some(rule.rulestring()));
+
+        let world = rule.jump_at(&preset.to_world(), 0, 4);
+        let mut expected = World::new();
+        preset.load_into(&mut expected, Coord::new(0, 2));
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn test_excitable_presets() {
+        use crate::automaton::parse_rule;
+
+        let preset = Preset::cyclic_spirals();
+        let rule = parse_rule(preset.rule.as_deref().unwrap()).unwrap();
+        let world = preset.to_world();
+        assert!(world
+            .iter_cell_states()
+            .all(|(_, state)| u16::from(state) < rule.state_count()));
+        // примерно две трети клеток ненулевые
+        assert!((5000..7300).contains(&world.active_cell_count()));
+        let next = rule.apply(&world);
+        assert_ne!(next, world);
+
+        // спираль Гринберга-Хастингса на торе не гаснет
+        let preset = Preset::greenberg_hastings_spiral();
+        let rule = parse_rule(preset.rule.as_deref().unwrap()).unwrap();
+        let world = rule.jump(&preset.to_world(), 300);
+        assert!(world.cells_in_state(1).active_cell_count() > 0);
+    }
 }
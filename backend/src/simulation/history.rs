@@ -0,0 +1,261 @@
+use crate::world::{Coord, WorldDelta};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// сколько последних изменений мира хранится для `/api/world/diff`
+pub const HISTORY_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    tick: u64,
+    /// изменение мира извне (API), а не шаг правила
+    edit: bool,
+    delta: Arc<WorldDelta>,
+}
+
+/// окно последних изменений мира, из которого собираются дельты для клиентов
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    /// минимальный `since`, для которого история ещё полна
+    min_since: u64,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            min_since: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// шаг правила, который привёл мир к поколению `tick`
+    pub fn record_step(&mut self, tick: u64, delta: Arc<WorldDelta>) {
+        self.push(HistoryEntry {
+            tick,
+            edit: false,
+            delta,
+        });
+    }
+
+    /// изменение мира извне, сделанное на поколении `tick`
+    pub fn record_edit(&mut self, tick: u64, delta: Arc<WorldDelta>) {
+        self.push(HistoryEntry {
+            tick,
+            edit: true,
+            delta,
+        });
+    }
+
+    /// сброс счётчика тиков: старые номера поколений больше ничего не значат
+    pub fn reset(&mut self) {
+        self.entries.clear();
+        self.min_since = 1;
+    }
+
+    /// шаг назад с поколения `from_tick`: номера поколений пойдут по второму
+    /// кругу, и клиентам, видевшим любое из прежних, нужен снимок
+    pub fn rewind(&mut self, from_tick: u64) {
+        self.entries.clear();
+        self.min_since = self.min_since.max(from_tick + 1);
+    }
+
+    /// суммарные изменения с поколения `since` или `None`, если их уже не восстановить
+    pub fn diff_since(&self, since: u64, current_tick: u64) -> Option<WorldDelta> {
+        if since < self.min_since || since > current_tick {
+            return None;
+        }
+
+        // правка на том же тике могла случиться как до, так и после запроса клиента
+        if self.entries.iter().any(|e| e.edit && e.tick == since) {
+            return None;
+        }
+
+        // для каждой клетки: была ли она жива на `since` и её последнее состояние
+        let mut changes: HashMap<Coord, (bool, u8)> = HashMap::new();
+        let mut ants = None;
+        for entry in self.entries.iter().filter(|e| e.tick > since) {
+            if let Some(moved) = &entry.delta.ants {
+                ants = Some(moved.clone());
+            }
+            for &coord in &entry.delta.died {
+                changes.entry(coord).or_insert((true, 0)).1 = 0;
+            }
+            for &(coord, state) in &entry.delta.born {
+                changes.entry(coord).or_insert((false, 0)).1 = state;
+            }
+            for &(coord, state) in &entry.delta.changed {
+                changes.entry(coord).or_insert((true, 0)).1 = state;
+            }
+        }
+
+        let mut delta = WorldDelta {
+            ants,
+            ..WorldDelta::new()
+        };
+        for (coord, (was_alive, state)) in changes {
+            match (was_alive, state) {
+                (false, 0) => {}
+                (false, state) => delta.born.push((coord, state)),
+                (true, 0) => delta.died.push(coord),
+                (true, state) => delta.changed.push((coord, state)),
+            }
+        }
+        Some(delta)
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        if self.entries.len() == self.capacity {
+            if let Some(dropped) = self.entries.pop_front() {
+                let threshold = if dropped.edit {
+                    dropped.tick + 1
+                } else {
+                    dropped.tick
+                };
+                self.min_since = self.min_since.max(threshold);
+            }
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(born: &[(i32, i32)], died: &[(i32, i32)]) -> Arc<WorldDelta> {
+        Arc::new(WorldDelta {
+            born: born.iter().map(|&(x, y)| (Coord::new(x, y), 1)).collect(),
+            died: died.iter().map(|&(x, y)| Coord::new(x, y)).collect(),
+            changed: Vec::new(),
+            ants: None,
+        })
+    }
+
+    #[test]
+    fn test_diff_composes_steps() {
+        let mut history = History::new(16);
+        history.record_step(1, delta(&[(0, 0), (1, 1)], &[(5, 5)]));
+        history.record_step(2, delta(&[(2, 2)], &[(0, 0)]));
+
+        let diff = history.diff_since(0, 2).unwrap();
+        assert_eq!(diff.born.len(), 2);
+        assert!(diff.born.contains(&(Coord::new(1, 1), 1)));
+        assert!(diff.born.contains(&(Coord::new(2, 2), 1)));
+        assert_eq!(diff.died, vec![Coord::new(5, 5)]);
+
+        let diff = history.diff_since(1, 2).unwrap();
+        assert_eq!(diff.born, vec![(Coord::new(2, 2), 1)]);
+        assert_eq!(diff.died, vec![Coord::new(0, 0)]);
+
+        assert!(history.diff_since(2, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_too_old_or_in_future() {
+        let mut history = History::new(2);
+        history.record_step(1, delta(&[(0, 0)], &[]));
+        history.record_step(2, delta(&[(1, 0)], &[]));
+        history.record_step(3, delta(&[(2, 0)], &[]));
+
+        assert!(history.diff_since(0, 3).is_none());
+        assert_eq!(history.diff_since(1, 3).unwrap().born.len(), 2);
+        assert!(history.diff_since(4, 3).is_none());
+    }
+
+    #[test]
+    fn test_edit_on_same_tick_forces_snapshot() {
+        let mut history = History::new(16);
+        history.record_step(1, delta(&[(0, 0)], &[]));
+        history.record_edit(1, delta(&[(9, 9)], &[]));
+
+        assert!(history.diff_since(1, 1).is_none());
+        assert_eq!(history.diff_since(0, 1).unwrap().born.len(), 2);
+    }
+
+    #[test]
+    fn test_reset_invalidates_old_ticks() {
+        let mut history = History::new(16);
+        history.record_step(1, delta(&[(0, 0)], &[]));
+        history.reset();
+
+        assert!(history.is_empty());
+        assert!(history.diff_since(0, 0).is_none());
+    }
+
+    #[test]
+    fn test_rewind_invalidates_undone_ticks() {
+        let mut history = History::new(16);
+        history.record_step(1, delta(&[(0, 0)], &[]));
+        history.record_step(2, delta(&[(1, 0)], &[]));
+        history.rewind(2);
+
+        assert!(history.diff_since(1, 1).is_none());
+        history.record_step(2, delta(&[(2, 0)], &[]));
+        history.record_step(3, delta(&[(3, 0)], &[]));
+        assert!(history.diff_since(2, 3).is_none());
+        assert!(history.diff_since(1, 3).is_none());
+        assert!(history.diff_since(3, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_composes_state_changes() {
+        let step = |born: Vec<(Coord, u8)>, died: Vec<Coord>, changed: Vec<(Coord, u8)>| {
+            Arc::new(WorldDelta {
+                born,
+                died,
+                changed,
+                ants: None,
+            })
+        };
+        let a = Coord::new(0, 0);
+        let b = Coord::new(1, 0);
+
+        let mut history = History::new(16);
+        history.record_step(1, step(vec![(a, 2)], vec![], vec![(b, 3)]));
+        history.record_step(2, step(vec![], vec![b], vec![(a, 1)]));
+
+        let diff = history.diff_since(0, 2).unwrap();
+        assert_eq!(diff.born, vec![(a, 1)]);
+        assert_eq!(diff.died, vec![b]);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_keeps_latest_ants() {
+        use crate::world::{Ant, Heading};
+
+        let ant = |x: i32| Ant::new(Coord::new(x, 0), Heading::East);
+        let moved = |x: i32| {
+            Arc::new(WorldDelta {
+                ants: Some(vec![ant(x)]),
+                ..WorldDelta::new()
+            })
+        };
+
+        let mut history = History::new(16);
+        history.record_step(1, moved(1));
+        history.record_step(2, delta(&[(0, 0)], &[]));
+        history.record_step(3, moved(2));
+
+        assert_eq!(history.diff_since(0, 3).unwrap().ants, Some(vec![ant(2)]));
+        assert_eq!(history.diff_since(1, 3).unwrap().ants, Some(vec![ant(2)]));
+        assert_eq!(history.diff_since(3, 3).unwrap().ants, None);
+    }
+}
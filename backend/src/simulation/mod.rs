@@ -1,3 +1,6 @@
+pub mod history;
+#[allow(clippy::module_inception)]
 pub mod simulation;
 
-pub use simulation::{Simulation, SimulationCommand, SimulationState};
+pub use history::History;
+pub use simulation::{Simulation, SimulationCommand, SimulationState, WorldDiff, WorldUpdate};
@@ -1,8 +1,14 @@
-use crate::automaton::Rule;
-use crate::world::{Coord, World};
+use super::history::History;
+use crate::automaton::{ContinuousRule, GameOfLife, Rule};
+use crate::world::{random_seed, Field, Rng, World, WorldDelta};
+use std::fmt;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// сколько обновлений может накопить отстающий подписчик
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -12,17 +18,56 @@ pub enum SimulationState {
     Paused,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Clone)]
 pub enum SimulationCommand {
     Start,
     Stop,
     Pause,
     Resume,
     Step,
+    Jump(u64),
+    StepBack,
     SetSpeed(u32),
+    SetRule(Arc<dyn Rule>),
+    SetSeed(u64),
     Shutdown,
 }
 
+impl fmt::Debug for SimulationCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationCommand::Start => f.write_str("Start"),
+            SimulationCommand::Stop => f.write_str("Stop"),
+            SimulationCommand::Pause => f.write_str("Pause"),
+            SimulationCommand::Resume => f.write_str("Resume"),
+            SimulationCommand::Step => f.write_str("Step"),
+            SimulationCommand::Jump(generations) => {
+                f.debug_tuple("Jump").field(generations).finish()
+            }
+            SimulationCommand::StepBack => f.write_str("StepBack"),
+            SimulationCommand::SetSpeed(tps) => f.debug_tuple("SetSpeed").field(tps).finish(),
+            SimulationCommand::SetRule(rule) => f.debug_tuple("SetRule").field(&rule.name()).finish(),
+            SimulationCommand::SetSeed(seed) => f.debug_tuple("SetSeed").field(seed).finish(),
+            SimulationCommand::Shutdown => f.write_str("Shutdown"),
+        }
+    }
+}
+
+/// изменения мира, рассылаемые подписчикам после каждого тика
+#[derive(Debug, Clone)]
+pub struct WorldUpdate {
+    pub tick: u64,
+    pub delta: Arc<WorldDelta>,
+}
+
+/// ответ на запрос изменений с определённого поколения
+#[derive(Debug, Clone)]
+pub enum WorldDiff {
+    Delta { tick: u64, delta: WorldDelta },
+    /// изменения уже не восстановить из истории, отдаём мир целиком
+    Snapshot { tick: u64, world: World },
+}
+
 #[derive(Clone)]
 pub struct Simulation {
     world: Arc<Mutex<World>>,
@@ -31,16 +76,48 @@ pub struct Simulation {
 
     tick_count: Arc<Mutex<u64>>,
 
+    /// тик, на котором поставлено текущее правило: правила, шаг которых
+    /// зависит от номера поколения, считают поколения от него
+    rule_tick: Arc<Mutex<u64>>,
+
     tps: Arc<Mutex<u32>>,
+
+    rule: Arc<Mutex<Arc<dyn Rule>>>,
+
+    updates: broadcast::Sender<WorldUpdate>,
+
+    history: Arc<Mutex<History>>,
+
+    /// зерно текущего прогона и генератор, из которого берут числа вероятностные правила
+    seed: Arc<Mutex<u64>>,
+
+    rng: Arc<Mutex<Rng>>,
+
+    /// поле непрерывного правила без квантования; мир хранит лишь его байты,
+    /// а шаги продолжаются отсюда, чтобы не терять точность
+    field: Arc<Mutex<Option<Field>>>,
+
+    /// поколение перед текущим, если оно известно: по нему считают правила
+    /// второго порядка и шаг назад
+    previous: Arc<Mutex<Option<World>>>,
 }
 
 impl Simulation {
     pub fn new() -> Self {
+        let seed = random_seed();
         Self {
             world: Arc::new(Mutex::new(World::new())),
             state: Arc::new(Mutex::new(SimulationState::Stopped)),
             tick_count: Arc::new(Mutex::new(0)),
+            rule_tick: Arc::new(Mutex::new(0)),
             tps: Arc::new(Mutex::new(10)),
+            rule: Arc::new(Mutex::new(Arc::new(GameOfLife::new()))),
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+            history: Arc::new(Mutex::new(History::default())),
+            seed: Arc::new(Mutex::new(seed)),
+            rng: Arc::new(Mutex::new(Rng::new(seed))),
+            field: Arc::new(Mutex::new(None)),
+            previous: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -50,7 +127,72 @@ impl Simulation {
 
     pub fn set_world(&self, world: World) {
         let mut w = self.world.lock().unwrap();
+        self.replace_world(&mut w, world);
+    }
+
+    /// ставит правило и мир под одним замком мира: цикл симуляции не шагнёт
+    /// новым миром по старому правилу
+    pub fn load_world(&self, world: World, rule: Option<Arc<dyn Rule>>) {
+        let mut w = self.world.lock().unwrap();
+        if let Some(rule) = rule {
+            self.set_rule(rule);
+        }
+        self.replace_world(&mut w, world);
+    }
+
+    fn replace_world(&self, w: &mut World, world: World) {
+        let delta = Arc::new(WorldDelta::between(w, &world));
+        if !delta.is_empty() {
+            let tick = self.get_tick_count();
+            self.history.lock().unwrap().record_edit(tick, delta.clone());
+            self.publish(tick, delta);
+        }
         *w = world;
+        // правленый мир больше не совпадает с сохранённым полем и не следует
+        // из предыдущего поколения
+        self.field.lock().unwrap().take();
+        self.previous.lock().unwrap().take();
+    }
+
+    /// поколение перед текущим, если оно известно
+    pub fn get_previous_world(&self) -> Option<World> {
+        self.previous.lock().unwrap().clone()
+    }
+
+    /// поле непрерывного правила: сохранённое после последнего шага или
+    /// полученное из состояний клеток; у бесконечной плоскости поля нет
+    pub fn get_field(&self) -> Option<Field> {
+        let world = self.world.lock().unwrap();
+        let field = self.field.lock().unwrap();
+        field.clone().or_else(|| Field::from_world(&world))
+    }
+
+    pub fn diff_since(&self, since: u64) -> WorldDiff {
+        let world = self.world.lock().unwrap();
+        let tick = self.get_tick_count();
+
+        match self.history.lock().unwrap().diff_since(since, tick) {
+            Some(delta) => WorldDiff::Delta { tick, delta },
+            None => WorldDiff::Snapshot {
+                tick,
+                world: world.clone(),
+            },
+        }
+    }
+
+    /// подписка на обновления вместе со снимком мира, согласованным с ними:
+    /// все изменения после снимка придут в возвращённый приёмник
+    pub fn subscribe(&self) -> (World, u64, broadcast::Receiver<WorldUpdate>) {
+        let world = self.world.lock().unwrap();
+        let rx = self.updates.subscribe();
+        (world.clone(), self.get_tick_count(), rx)
+    }
+
+    fn publish(&self, tick: u64, delta: Arc<WorldDelta>) {
+        if self.updates.receiver_count() > 0 {
+            // ошибка означает лишь отсутствие подписчиков
+            let _ = self.updates.send(WorldUpdate { tick, delta });
+        }
     }
 
     pub fn get_state(&self) -> SimulationState {
@@ -61,6 +203,16 @@ impl Simulation {
         *self.tick_count.lock().unwrap()
     }
 
+    pub fn get_seed(&self) -> u64 {
+        *self.seed.lock().unwrap()
+    }
+
+    /// перезапускает генератор с зерна `seed`: с того же мира прогон повторится
+    pub fn set_seed(&self, seed: u64) {
+        *self.seed.lock().unwrap() = seed;
+        *self.rng.lock().unwrap() = Rng::new(seed);
+    }
+
     pub fn get_tps(&self) -> u32 {
         *self.tps.lock().unwrap()
     }
@@ -71,26 +223,217 @@ impl Simulation {
         *current_tps = tps;
     }
 
-    pub fn step<R: Rule>(&self, rule: &R) {
+    pub fn get_rule(&self) -> Arc<dyn Rule> {
+        self.rule.lock().unwrap().clone()
+    }
+
+    pub fn get_rule_name(&self) -> String {
+        self.rule.lock().unwrap().name().to_string()
+    }
+
+    /// ставит правило; его поколения отсчитываются от текущего тика
+    pub fn set_rule(&self, rule: Arc<dyn Rule>) {
+        let mut current = self.rule.lock().unwrap();
+        *current = rule;
+        *self.rule_tick.lock().unwrap() = self.get_tick_count();
+    }
+
+    /// номер текущего поколения для правила: тики с тех пор, как его поставили
+    pub fn get_rule_generation(&self) -> u64 {
+        let tick = self.get_tick_count();
+        tick.saturating_sub(*self.rule_tick.lock().unwrap())
+    }
+
+    pub fn step<R: Rule + ?Sized>(&self, rule: &R) {
+        let mut world = self.world.lock().unwrap();
+        self.step_world(&mut world, rule);
+    }
+
+    /// шаг текущим правилом; правило читается под замком мира, чтобы не
+    /// разойтись с `load_world`
+    fn step_current(&self) {
+        let mut world = self.world.lock().unwrap();
+        let rule = self.get_rule();
+        self.step_world(&mut world, rule.as_ref());
+    }
+
+    fn step_world<R: Rule + ?Sized>(&self, world: &mut World, rule: &R) {
+        let next_world = match self.advance_stateful(world, rule, 1) {
+            Some((next_world, _)) => next_world,
+            None => rule.apply_seeded(
+                world,
+                self.get_rule_generation(),
+                &mut self.rng.lock().unwrap(),
+            ),
+        };
+        self.commit_step(world, next_world, None, rule.moves_ants(), 1);
+    }
+
+    /// продвигает мир сразу на `generations` поколений
+    pub fn jump<R: Rule + ?Sized>(&self, rule: &R, generations: u64) {
+        let mut world = self.world.lock().unwrap();
+        self.jump_world(&mut world, rule, generations);
+    }
+
+    /// прыжок текущим правилом, как в `step_current`
+    fn jump_current(&self, generations: u64) {
+        let mut world = self.world.lock().unwrap();
+        let rule = self.get_rule();
+        self.jump_world(&mut world, rule.as_ref(), generations);
+    }
+
+    fn jump_world<R: Rule + ?Sized>(&self, world: &mut World, rule: &R, generations: u64) {
+        let (next_world, previous) = match self.advance_stateful(world, rule, generations) {
+            Some(advanced) => advanced,
+            None => {
+                let next_world = rule.jump_seeded(
+                    world,
+                    self.get_rule_generation(),
+                    generations,
+                    &mut self.rng.lock().unwrap(),
+                );
+                (next_world, None)
+            }
+        };
+        self.commit_step(
+            world,
+            next_world,
+            previous,
+            rule.moves_ants(),
+            generations,
+        );
+    }
+
+    /// шаг назад правила второго порядка: та же формула по паре
+    /// `(текущее, предыдущее)` даёт поколение перед предыдущим. Ничего не делает
+    /// и возвращает `false` для необратимых правил, на нулевом поколении и когда
+    /// предыдущее поколение неизвестно, например после правки мира
+    pub fn step_back<R: Rule + ?Sized>(&self, rule: &R) -> bool {
+        let Some(second_order) = rule.second_order() else {
+            return false;
+        };
         let mut world = self.world.lock().unwrap();
-        let next_world = rule.apply(&*world);
-        *world = next_world;
+        let mut tick = self.tick_count.lock().unwrap();
+        if *tick == 0 {
+            return false;
+        }
+        let Some(mut previous) = self.previous.lock().unwrap().take() else {
+            return false;
+        };
+
+        let before = second_order.advance(&world, &previous);
+        previous.set_ants(world.ants().to_vec());
+
+        let from_tick = *tick;
+        *tick -= 1;
+        let delta = Arc::new(WorldDelta::between(&world, &previous));
+        self.history.lock().unwrap().rewind(from_tick);
+        self.publish(*tick, delta);
+        *world = previous;
+        self.field.lock().unwrap().take();
+        *self.previous.lock().unwrap() = Some(before);
+        true
+    }
+
+    /// шаги правил, которым мало одного мира: непрерывным нужно поле, правилам
+    /// второго порядка - предыдущее поколение. Возвращает новый мир и поколение
+    /// перед ним, если оно известно; `None` значит обычный шаг правила
+    fn advance_stateful<R: Rule + ?Sized>(
+        &self,
+        world: &World,
+        rule: &R,
+        generations: u64,
+    ) -> Option<(World, Option<World>)> {
+        if let Some(continuous) = rule.continuous() {
+            if let Some(next_world) = self.advance_field(world, continuous, generations) {
+                return Some((next_world, None));
+            }
+        }
+        // дискретный шаг уводит мир от сохранённого поля
+        self.field.lock().unwrap().take();
+
+        let second_order = rule.second_order()?;
+        let mut previous = self.previous_or_empty(world);
+        let mut current = world.clone();
+        for _ in 0..generations {
+            let next = second_order.advance(&previous, &current);
+            previous = std::mem::replace(&mut current, next);
+        }
+        Some((current, Some(previous)))
+    }
+
+    /// сохранённое предыдущее поколение; если его нет, правила второго порядка
+    /// считают предыдущим пустой мир
+    fn previous_or_empty(&self, world: &World) -> World {
+        self.previous
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_else(|| World::with_topology(world.topology()))
+    }
+
+    /// `generations` шагов непрерывного правила по сохранённому полю; мир
+    /// получается квантованием. `None`, если поля нет - мир на плоскости
+    fn advance_field(
+        &self,
+        world: &World,
+        rule: &dyn ContinuousRule,
+        generations: u64,
+    ) -> Option<World> {
+        let mut cache = self.field.lock().unwrap();
+        let mut field = match cache.take() {
+            Some(field) => field,
+            None => Field::from_world(world)?,
+        };
+        for _ in 0..generations {
+            field = rule.step_field(&field);
+        }
+        let next_world = field.to_world(world.topology());
+        *cache = Some(field);
+        Some(next_world)
+    }
+
+    /// `previous` - поколение перед `next_world`, если шаг его знает; после
+    /// одиночного шага это и так прежний мир
+    fn commit_step(
+        &self,
+        world: &mut World,
+        mut next_world: World,
+        previous: Option<World>,
+        ants_moved: bool,
+        generations: u64,
+    ) {
+        // правила без агентов не знают о муравьях, те остаются на своих клетках
+        if !ants_moved {
+            next_world.set_ants(world.ants().to_vec());
+        }
 
         let mut tick = self.tick_count.lock().unwrap();
-        *tick += 1;
+        *tick += generations;
+
+        let delta = Arc::new(WorldDelta::between(world, &next_world));
+        self.history.lock().unwrap().record_step(*tick, delta.clone());
+        self.publish(*tick, delta);
+        let current = std::mem::replace(world, next_world);
+        *self.previous.lock().unwrap() = previous.or((generations == 1).then_some(current));
     }
 
     pub fn reset_tick_count(&self) {
         let mut tick = self.tick_count.lock().unwrap();
         *tick = 0;
+        *self.rule_tick.lock().unwrap() = 0;
+        self.history.lock().unwrap().reset();
+        // новый прогон с нулевого тика снова начинается с зерна
+        *self.rng.lock().unwrap() = Rng::new(self.get_seed());
     }
 
-    pub fn run<R: Rule + 'static>(
+    pub fn run(
         &self,
-        rule: Arc<R>,
+        rule: Arc<dyn Rule>,
         command_rx: std::sync::mpsc::Receiver<SimulationCommand>,
     ) -> thread::JoinHandle<()> {
         let sim = self.clone();
+        sim.set_rule(rule);
 
         thread::spawn(move || {
             loop {
@@ -116,11 +459,23 @@ impl Simulation {
                             }
                         }
                         SimulationCommand::Step => {
-                            sim.step(rule.as_ref());
+                            sim.step_current();
+                        }
+                        SimulationCommand::Jump(generations) => {
+                            sim.jump_current(generations);
+                        }
+                        SimulationCommand::StepBack => {
+                            sim.step_back(sim.get_rule().as_ref());
                         }
                         SimulationCommand::SetSpeed(tps) => {
                             sim.set_tps(tps);
                         }
+                        SimulationCommand::SetRule(rule) => {
+                            sim.set_rule(rule);
+                        }
+                        SimulationCommand::SetSeed(seed) => {
+                            sim.set_seed(seed);
+                        }
                         SimulationCommand::Shutdown => {
                             break;
                         }
@@ -131,7 +486,7 @@ impl Simulation {
                 if state == SimulationState::Running {
                     let start = Instant::now();
 
-                    sim.step(rule.as_ref());
+                    sim.step_current();
 
                     let tps = sim.get_tps();
                     let tick_duration = Duration::from_millis(1000 / tps as u64);
@@ -157,7 +512,8 @@ impl Default for Simulation {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::automaton::GameOfLife;
+    use crate::automaton::{HashLife, LifeLike};
+    use crate::world::Coord;
 
     #[test]
     fn test_simulation_new() {
@@ -318,4 +674,389 @@ mod tests {
         tx.send(SimulationCommand::Shutdown).unwrap();
         handle.join().unwrap();
     }
+
+    #[test]
+    fn test_set_rule_command() {
+        let sim = Simulation::new();
+        let rule = Arc::new(GameOfLife::new());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = sim.run(rule, rx);
+
+        assert_eq!(sim.get_rule_name(), "Conway's Game of Life");
+
+        tx.send(SimulationCommand::SetRule(Arc::new(LifeLike::seeds()))).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(sim.get_rule_name(), "B2/S");
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(1, 0), true);
+        sim.set_world(world);
+
+        tx.send(SimulationCommand::Step).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        let world_after = sim.get_world();
+        assert_eq!(world_after.active_cell_count(), 4);
+        assert!(!world_after.get_cell(Coord::new(0, 0)));
+
+        tx.send(SimulationCommand::Shutdown).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_load_world_installs_rule_with_world() {
+        let sim = Simulation::new();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = sim.run(Arc::new(GameOfLife::new()), rx);
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(1, 0), true);
+        sim.load_world(world, Some(Arc::new(LifeLike::seeds())));
+        // правило стоит сразу, без очереди команд
+        assert_eq!(sim.get_rule_name(), "B2/S");
+
+        tx.send(SimulationCommand::Step).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(sim.get_world().active_cell_count(), 4);
+
+        tx.send(SimulationCommand::Shutdown).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_subscribe_receives_tick_updates() {
+        let sim = Simulation::new();
+        let rule = GameOfLife::new();
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, -1), true);
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(0, 1), true);
+        sim.set_world(world);
+
+        let (mut snapshot, tick, mut rx) = sim.subscribe();
+        assert_eq!(tick, 0);
+        assert_eq!(snapshot.active_cell_count(), 3);
+
+        sim.step(&rule);
+
+        let update = rx.try_recv().unwrap();
+        assert_eq!(update.tick, 1);
+        assert_eq!(update.delta.born.len(), 2);
+        assert_eq!(update.delta.died.len(), 2);
+
+        update.delta.apply_to(&mut snapshot);
+        assert!(snapshot.get_cell(Coord::new(-1, 0)));
+        assert!(snapshot.get_cell(Coord::new(1, 0)));
+        assert!(!snapshot.get_cell(Coord::new(0, 1)));
+    }
+
+    #[test]
+    fn test_set_world_publishes_update() {
+        let sim = Simulation::new();
+        let (_, _, mut rx) = sim.subscribe();
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(7, 7), true);
+        sim.set_world(world);
+
+        let update = rx.try_recv().unwrap();
+        assert_eq!(update.tick, 0);
+        assert_eq!(update.delta.born, vec![(Coord::new(7, 7), 1)]);
+        assert!(update.delta.died.is_empty());
+    }
+
+    #[test]
+    fn test_diff_since() {
+        let sim = Simulation::new();
+        let rule = GameOfLife::new();
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, -1), true);
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(0, 1), true);
+        sim.set_world(world);
+
+        sim.step(&rule);
+        sim.step(&rule);
+
+        match sim.diff_since(1) {
+            WorldDiff::Delta { tick, delta } => {
+                assert_eq!(tick, 2);
+                assert_eq!(delta.born.len(), 2);
+                assert_eq!(delta.died.len(), 2);
+            }
+            WorldDiff::Snapshot { .. } => panic!("expected delta"),
+        }
+
+        match sim.diff_since(2) {
+            WorldDiff::Delta { delta, .. } => assert!(delta.is_empty()),
+            WorldDiff::Snapshot { .. } => panic!("expected delta"),
+        }
+
+        match sim.diff_since(0) {
+            WorldDiff::Snapshot { tick, world } => {
+                assert_eq!(tick, 2);
+                assert_eq!(world.active_cell_count(), 3);
+            }
+            WorldDiff::Delta { .. } => panic!("edit at tick 0 should force a snapshot"),
+        }
+    }
+
+    #[test]
+    fn test_jump() {
+        let sim = Simulation::new();
+        let rule = HashLife::new(LifeLike::conway());
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(1, 0), true);
+        world.set_cell(Coord::new(2, 1), true);
+        world.set_cell(Coord::new(0, 2), true);
+        world.set_cell(Coord::new(1, 2), true);
+        world.set_cell(Coord::new(2, 2), true);
+        sim.set_world(world);
+
+        sim.jump(&rule, 4000);
+
+        assert_eq!(sim.get_tick_count(), 4000);
+        let world_after = sim.get_world();
+        assert_eq!(world_after.active_cell_count(), 5);
+        assert!(world_after.get_cell(Coord::new(1001, 1000)));
+    }
+
+    #[test]
+    fn test_step_passes_generation_to_rule() {
+        use crate::automaton::Margolus;
+
+        // шар Margolus летит по диагонали, только если разбиение чередуется по тикам
+        let sim = Simulation::new();
+        let rule = Margolus::billiard_ball();
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        sim.set_world(world);
+
+        sim.step(&rule);
+        sim.step(&rule);
+        sim.jump(&rule, 3);
+
+        assert_eq!(sim.get_tick_count(), 5);
+        assert!(sim.get_world().get_cell(Coord::new(5, 5)));
+    }
+
+    #[test]
+    fn test_seed_reproduces_stochastic_run() {
+        use crate::automaton::StochasticLife;
+        use crate::presets::Preset;
+
+        let rule = StochasticLife::parse("B3,2:0.05/S23,4:0.5/N0.001").unwrap();
+        let run = |sim: &Simulation| {
+            sim.set_world(Preset::r_pentomino().to_world());
+            for _ in 0..20 {
+                sim.step(&rule);
+            }
+            sim.jump(&rule, 10);
+            sim.get_world()
+        };
+
+        let first = Simulation::new();
+        first.set_seed(99);
+        let second = Simulation::new();
+        second.set_seed(99);
+        assert_eq!(first.get_seed(), 99);
+        let world = run(&first);
+        assert_eq!(world, run(&second));
+
+        // сброс тиков возвращает генератор к зерну
+        first.reset_tick_count();
+        assert_eq!(run(&first), world);
+
+        second.set_seed(100);
+        assert_ne!(run(&second), world);
+    }
+
+    #[test]
+    fn test_step_moves_ants_only_for_turmites() {
+        use crate::automaton::Turmite;
+        use crate::world::{Ant, Heading};
+
+        let sim = Simulation::new();
+        let mut world = World::new();
+        world.add_ant(Ant::new(Coord::new(0, 0), Heading::North));
+        sim.set_world(world);
+        let (_, _, mut rx) = sim.subscribe();
+
+        sim.step(&Turmite::langtons_ant());
+        let expected = vec![Ant::new(Coord::new(1, 0), Heading::East)];
+        assert_eq!(sim.get_world().ants(), expected.as_slice());
+        assert_eq!(rx.try_recv().unwrap().delta.ants, Some(expected.clone()));
+
+        sim.step(&GameOfLife::new());
+        assert_eq!(sim.get_world().ants(), expected.as_slice());
+        assert_eq!(rx.try_recv().unwrap().delta.ants, None);
+    }
+
+    #[test]
+    fn test_continuous_rule_keeps_field_precision() {
+        use crate::automaton::{ContinuousRule, Lenia};
+        use crate::presets::Preset;
+
+        let preset = Preset::orbium();
+        let rule = Lenia::orbium();
+        let sim = Simulation::new();
+        sim.set_world(preset.to_world());
+
+        let mut expected = Field::from_world(&preset.to_world()).unwrap();
+        for _ in 0..5 {
+            expected = rule.step_field(&expected);
+        }
+        sim.step(&rule);
+        sim.jump(&rule, 4);
+
+        // шаги идут по полю без квантования, мир - его байтовая копия
+        assert_eq!(sim.get_field(), Some(expected.clone()));
+        assert_eq!(
+            sim.get_world(),
+            expected.to_world(preset.to_world().topology())
+        );
+        assert_ne!(rule.jump(&preset.to_world(), 5), sim.get_world());
+
+        // правка мира сбрасывает поле к состояниям клеток
+        sim.set_world(preset.to_world());
+        assert_eq!(sim.get_field(), Field::from_world(&preset.to_world()));
+        assert!(Simulation::new().get_field().is_none());
+    }
+
+    #[test]
+    fn test_keeps_previous_world() {
+        let sim = Simulation::new();
+        let mut world = World::new();
+        for y in -1..=1 {
+            world.set_cell(Coord::new(0, y), true);
+        }
+        sim.set_world(world.clone());
+        assert!(sim.get_previous_world().is_none());
+
+        sim.step(&GameOfLife::new());
+        assert_eq!(sim.get_previous_world(), Some(world.clone()));
+
+        // после прыжка обычного правила предыдущее поколение неизвестно
+        sim.jump(&GameOfLife::new(), 2);
+        assert!(sim.get_previous_world().is_none());
+        sim.step(&GameOfLife::new());
+        sim.set_world(world);
+        assert!(sim.get_previous_world().is_none());
+    }
+
+    #[test]
+    fn test_step_back_reverses_second_order_rule() {
+        use crate::automaton::SecondOrder;
+        use crate::world::Topology;
+
+        let rule = SecondOrder::parse("2nd/B3/S23").unwrap();
+        let sim = Simulation::new();
+        let mut world = World::with_topology(Topology::Torus {
+            width: 16,
+            height: 16,
+        });
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (7, 7), (8, 7)] {
+            world.set_cell(Coord::new(x, y), true);
+        }
+        sim.set_world(world.clone());
+        assert!(!sim.step_back(&rule));
+
+        sim.step(&rule);
+        sim.jump(&rule, 19);
+        assert_eq!(sim.get_world(), rule.jump(&world, 20));
+        let (_, _, mut rx) = sim.subscribe();
+
+        for tick in (0..20).rev() {
+            assert!(sim.step_back(&rule));
+            assert_eq!(sim.get_tick_count(), tick);
+        }
+        assert_eq!(sim.get_world(), world);
+        assert_eq!(rx.try_recv().unwrap().tick, 19);
+        assert!(!sim.step_back(&rule));
+
+        // обратно вперёд - снова тот же прогон
+        sim.jump(&rule, 20);
+        assert_eq!(sim.get_world(), rule.jump(&world, 20));
+        assert!(!sim.step_back(&GameOfLife::new()));
+    }
+
+    #[test]
+    fn test_step_back_refuses_after_edit() {
+        use crate::automaton::SecondOrder;
+
+        let rule = SecondOrder::parse("2nd/B3/S23").unwrap();
+        let sim = Simulation::new();
+        let mut world = World::new();
+        for x in 0..3 {
+            world.set_cell(Coord::new(x, 0), true);
+        }
+        sim.set_world(world);
+        sim.jump(&rule, 3);
+
+        // правка теряет предыдущее поколение, пустым его считать нельзя
+        let mut edited = sim.get_world();
+        edited.set_cell(Coord::new(10, 10), true);
+        sim.set_world(edited.clone());
+        assert!(!sim.step_back(&rule));
+        assert_eq!(sim.get_world(), edited);
+        assert_eq!(sim.get_tick_count(), 3);
+
+        // шаг вперёд снова даёт пару поколений
+        sim.step(&rule);
+        assert!(sim.step_back(&rule));
+        assert_eq!(sim.get_world(), edited);
+    }
+
+    #[test]
+    fn test_rule_generations_count_from_rule_change() {
+        use crate::automaton::Elementary;
+
+        let sim = Simulation::new();
+        sim.set_rule(Arc::new(GameOfLife::new()));
+        for _ in 0..10 {
+            sim.step(sim.get_rule().as_ref());
+        }
+
+        let mut world = World::new();
+        world.set_cell(Coord::new(0, 0), true);
+        sim.set_world(world.clone());
+        let rule = Arc::new(Elementary::new(30, 0));
+        sim.set_rule(rule.clone());
+        assert_eq!(sim.get_rule_generation(), 0);
+        for _ in 0..5 {
+            sim.step(sim.get_rule().as_ref());
+        }
+
+        // диаграмма идёт от строки 0, хотя общий тик уже 15
+        assert_eq!(sim.get_tick_count(), 15);
+        assert_eq!(sim.get_rule_generation(), 5);
+        assert_eq!(sim.get_world(), rule.jump(&world, 5));
+    }
+
+    #[test]
+    fn test_margolus_phase_counts_from_rule_change() {
+        use crate::automaton::parse_rule;
+        use crate::presets::Preset;
+
+        let sim = Simulation::new();
+        sim.set_rule(Arc::new(GameOfLife::new()));
+        sim.step(sim.get_rule().as_ref());
+
+        // корабль Critters после чужого тика всё равно едет вниз
+        let preset = Preset::critters_glider();
+        sim.set_rule(parse_rule(preset.rule.as_deref().unwrap()).unwrap());
+        sim.set_world(preset.to_world());
+        for _ in 0..8 {
+            sim.step(sim.get_rule().as_ref());
+        }
+        let (min, max) = sim.get_world().get_bounds().unwrap();
+        assert_eq!((min.y, max.y), (5, 6));
+    }
 }
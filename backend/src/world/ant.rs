@@ -0,0 +1,79 @@
+use super::coord::Coord;
+use serde::{Deserialize, Serialize};
+
+/// направление муравья; север - к меньшим `y`, как на экране
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Heading {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    const CLOCKWISE: [Heading; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    /// поворот на `quarter_turns` четвертей по часовой стрелке (отрицательные - против)
+    pub fn rotated(self, quarter_turns: i32) -> Self {
+        let index = (self as i32 + quarter_turns).rem_euclid(4);
+        Self::CLOCKWISE[index as usize]
+    }
+
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Self::North => (0, -1),
+            Self::East => (1, 0),
+            Self::South => (0, 1),
+            Self::West => (-1, 0),
+        }
+    }
+}
+
+/// агент тьюрмита, живущий поверх сетки клеток
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ant {
+    pub position: Coord,
+    pub heading: Heading,
+}
+
+impl Ant {
+    pub fn new(position: Coord, heading: Heading) -> Self {
+        Self { position, heading }
+    }
+
+    /// клетка перед муравьём; `None` у края диапазона координат
+    pub fn ahead(&self) -> Option<Coord> {
+        let (dx, dy) = self.heading.offset();
+        Some(Coord::new(
+            self.position.x.checked_add(dx)?,
+            self.position.y.checked_add(dy)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        assert_eq!(Heading::North.rotated(1), Heading::East);
+        assert_eq!(Heading::North.rotated(-1), Heading::West);
+        assert_eq!(Heading::West.rotated(2), Heading::East);
+        assert_eq!(Heading::South.rotated(0), Heading::South);
+    }
+
+    #[test]
+    fn test_ahead() {
+        let ant = Ant::new(Coord::new(3, 3), Heading::North);
+        assert_eq!(ant.ahead(), Some(Coord::new(3, 2)));
+        let ant = Ant::new(Coord::new(3, 3), Heading::East);
+        assert_eq!(ant.ahead(), Some(Coord::new(4, 3)));
+        let ant = Ant::new(Coord::new(i32::MAX, 3), Heading::East);
+        assert_eq!(ant.ahead(), None);
+        let ant = Ant::new(Coord::new(3, i32::MIN), Heading::North);
+        assert_eq!(ant.ahead(), None);
+    }
+}
@@ -1,48 +1,132 @@
-use std::collections::HashSet;
-
 pub const CHUNK_SIZE: i32 = 64;
 
+const CELLS: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// чанк 64x64: по одной 64-битной маске на строку, бит x - клетка (x, y) в ненулевом
+/// состоянии; сами состояния хранятся, только когда в чанке есть что-то кроме 0 и 1
 #[derive(Debug, Clone)]
 pub struct Chunk {
-    active_cells: HashSet<(i32, i32)>,
+    rows: [u64; CHUNK_SIZE as usize],
+    states: Option<Box<[u8; CELLS]>>,
 }
 
 impl Chunk {
     pub fn new() -> Self {
         Self {
-            active_cells: HashSet::new(),
+            rows: [0; CHUNK_SIZE as usize],
+            states: None,
         }
     }
 
+    /// двухсостоянийный чанк из готовых масок строк
+    pub fn from_rows(rows: [u64; CHUNK_SIZE as usize]) -> Self {
+        Self { rows, states: None }
+    }
+
     pub fn get_cell(&self, x: i32, y: i32) -> bool {
-        self.active_cells.contains(&(x, y))
+        debug_assert!((0..CHUNK_SIZE).contains(&x) && (0..CHUNK_SIZE).contains(&y));
+        self.rows[y as usize] >> x & 1 == 1
     }
 
     pub fn set_cell(&mut self, x: i32, y: i32, alive: bool) {
-        if alive {
-            self.active_cells.insert((x, y));
+        self.set_state(x, y, alive as u8);
+    }
+
+    /// состояние клетки: 0 - мёртвая/фон
+    pub fn get_state(&self, x: i32, y: i32) -> u8 {
+        if !self.get_cell(x, y) {
+            return 0;
+        }
+        self.states
+            .as_ref()
+            .map_or(1, |states| states[Self::index(x, y)])
+    }
+
+    pub fn set_state(&mut self, x: i32, y: i32, state: u8) {
+        debug_assert!((0..CHUNK_SIZE).contains(&x) && (0..CHUNK_SIZE).contains(&y));
+        if state == 0 {
+            self.rows[y as usize] &= !(1 << x);
         } else {
-            self.active_cells.remove(&(x, y));
+            self.rows[y as usize] |= 1 << x;
+        }
+
+        if state > 1 && self.states.is_none() {
+            let mut states = Box::new([0; CELLS]);
+            for (x, y) in self.iter_active() {
+                states[Self::index(x, y)] = 1;
+            }
+            self.states = Some(states);
+        }
+        if let Some(states) = &mut self.states {
+            states[Self::index(x, y)] = state;
         }
     }
 
+    /// есть ли в чанке состояния больше 1
+    pub fn is_multi_state(&self) -> bool {
+        self.states.is_some()
+    }
+
+    pub fn row(&self, y: i32) -> u64 {
+        self.rows[y as usize]
+    }
+
+    pub fn rows(&self) -> &[u64; CHUNK_SIZE as usize] {
+        &self.rows
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.active_cells.is_empty()
+        self.rows.iter().all(|&row| row == 0)
     }
 
     pub fn active_count(&self) -> usize {
-        self.active_cells.len()
+        self.rows.iter().map(|row| row.count_ones() as usize).sum()
     }
 
     pub fn iter_active(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
-        self.active_cells.iter().copied()
+        self.rows.iter().enumerate().flat_map(|(y, &row)| {
+            let mut bits = row;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let x = bits.trailing_zeros() as i32;
+                bits &= bits - 1;
+                Some((x, y as i32))
+            })
+        })
+    }
+
+    /// живые клетки вместе с их состояниями
+    pub fn iter_states(&self) -> impl Iterator<Item = (i32, i32, u8)> + '_ {
+        self.iter_active()
+            .map(|(x, y)| (x, y, self.get_state(x, y)))
     }
 
     pub fn clear(&mut self) {
-        self.active_cells.clear();
+        self.rows = [0; CHUNK_SIZE as usize];
+        self.states = None;
+    }
+
+    fn index(x: i32, y: i32) -> usize {
+        (y * CHUNK_SIZE + x) as usize
     }
 }
 
+impl PartialEq for Chunk {
+    fn eq(&self, other: &Self) -> bool {
+        if self.rows != other.rows {
+            return false;
+        }
+        match (&self.states, &other.states) {
+            (None, None) => true,
+            _ => self.iter_states().eq(other.iter_states()),
+        }
+    }
+}
+
+impl Eq for Chunk {}
+
 impl Default for Chunk {
     fn default() -> Self {
         Self::new()
@@ -98,4 +182,41 @@ mod tests {
         chunk.clear();
         assert!(chunk.is_empty());
     }
+
+    #[test]
+    fn test_row_bitmask_edges() {
+        let mut chunk = Chunk::new();
+        chunk.set_cell(0, 7, true);
+        chunk.set_cell(63, 7, true);
+
+        assert_eq!(chunk.row(7), 1 | 1 << 63);
+        assert_eq!(chunk.active_count(), 2);
+        assert_eq!(
+            chunk.iter_active().collect::<Vec<_>>(),
+            vec![(0, 7), (63, 7)]
+        );
+    }
+
+    #[test]
+    fn test_multi_state_cells() {
+        let mut chunk = Chunk::new();
+        chunk.set_cell(1, 1, true);
+        assert!(!chunk.is_multi_state());
+
+        chunk.set_state(2, 2, 3);
+        assert!(chunk.is_multi_state());
+        assert_eq!(chunk.get_state(1, 1), 1);
+        assert_eq!(chunk.get_state(2, 2), 3);
+        assert!(chunk.get_cell(2, 2));
+        assert_eq!(chunk.active_count(), 2);
+
+        chunk.set_state(2, 2, 0);
+        assert_eq!(chunk.get_state(2, 2), 0);
+        assert_eq!(chunk.iter_states().collect::<Vec<_>>(), vec![(1, 1, 1)]);
+
+        // равенство не зависит от того, выделен ли массив состояний
+        let mut plain = Chunk::new();
+        plain.set_cell(1, 1, true);
+        assert_eq!(chunk, plain);
+    }
 }
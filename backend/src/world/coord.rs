@@ -1,3 +1,4 @@
+use super::topology::Topology;
 use std::ops::{Add, Sub};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -37,6 +38,13 @@ impl Coord {
             Coord::new(self.x + 1, self.y + 1), // нижний правый
         ]
     }
+
+    /// соседи с учётом топологии: склеенные края переносятся, клетки за мёртвой границей пропускаются
+    pub fn neighbors_in(&self, topology: Topology) -> impl Iterator<Item = Coord> {
+        self.neighbors()
+            .into_iter()
+            .filter_map(move |neighbor| topology.wrap(neighbor))
+    }
 }
 
 impl Add for Coord {
@@ -91,4 +99,23 @@ mod tests {
         assert!(neighbors.contains(&Coord::new(4, 4)));
         assert!(neighbors.contains(&Coord::new(6, 6)));
     }
+
+    #[test]
+    fn test_neighbors_in_topology() {
+        let corner = Coord::new(0, 0);
+        let bounded = Topology::Bounded {
+            width: 4,
+            height: 4,
+        };
+        assert_eq!(corner.neighbors_in(bounded).count(), 3);
+
+        let klein = Topology::KleinBottle {
+            width: 4,
+            height: 4,
+        };
+        let neighbors: Vec<Coord> = corner.neighbors_in(klein).collect();
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.contains(&Coord::new(0, 3)));
+        assert!(neighbors.contains(&Coord::new(3, 0)));
+    }
 }
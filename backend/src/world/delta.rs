@@ -0,0 +1,145 @@
+use super::ant::Ant;
+use super::coord::Coord;
+use super::world::World;
+
+/// изменения между двумя состояниями мира
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldDelta {
+    /// клетки, ожившие из состояния 0, с новым состоянием
+    pub born: Vec<(Coord, u8)>,
+    /// клетки, перешедшие в состояние 0
+    pub died: Vec<Coord>,
+    /// живые клетки, сменившие одно ненулевое состояние на другое
+    pub changed: Vec<(Coord, u8)>,
+    /// новые позиции муравьёв, если они изменились
+    pub ants: Option<Vec<Ant>>,
+}
+
+impl WorldDelta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn between(prev: &World, next: &World) -> Self {
+        let mut delta = Self::new();
+        for (coord, state) in next.iter_cell_states() {
+            match prev.get_state(coord) {
+                0 => delta.born.push((coord, state)),
+                old if old != state => delta.changed.push((coord, state)),
+                _ => {}
+            }
+        }
+        delta.died = prev
+            .iter_active_cells()
+            .filter(|&coord| !next.get_cell(coord))
+            .collect();
+        if prev.ants() != next.ants() {
+            delta.ants = Some(next.ants().to_vec());
+        }
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.born.is_empty()
+            && self.died.is_empty()
+            && self.changed.is_empty()
+            && self.ants.is_none()
+    }
+
+    pub fn apply_to(&self, world: &mut World) {
+        for &coord in &self.died {
+            world.set_state(coord, 0);
+        }
+        for &(coord, state) in self.born.iter().chain(&self.changed) {
+            world.set_state(coord, state);
+        }
+        if let Some(ants) = &self.ants {
+            world.set_ants(ants.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_between() {
+        let mut prev = World::new();
+        prev.set_cell(Coord::new(0, 0), true);
+        prev.set_cell(Coord::new(1, 0), true);
+
+        let mut next = World::new();
+        next.set_cell(Coord::new(1, 0), true);
+        next.set_cell(Coord::new(100, 100), true);
+
+        let delta = WorldDelta::between(&prev, &next);
+        assert_eq!(delta.born, vec![(Coord::new(100, 100), 1)]);
+        assert_eq!(delta.died, vec![Coord::new(0, 0)]);
+        assert!(delta.changed.is_empty());
+    }
+
+    #[test]
+    fn test_delta_apply_to() {
+        let mut prev = World::new();
+        prev.set_cell(Coord::new(0, 0), true);
+
+        let mut next = World::new();
+        next.set_cell(Coord::new(5, 5), true);
+        next.set_cell(Coord::new(-70, 3), true);
+
+        let delta = WorldDelta::between(&prev, &next);
+        delta.apply_to(&mut prev);
+
+        assert_eq!(prev.active_cell_count(), 2);
+        assert!(prev.get_cell(Coord::new(5, 5)));
+        assert!(prev.get_cell(Coord::new(-70, 3)));
+        assert!(!prev.get_cell(Coord::new(0, 0)));
+    }
+
+    #[test]
+    fn test_identical_worlds_empty_delta() {
+        let mut world = World::new();
+        world.set_cell(Coord::new(3, 4), true);
+
+        assert!(WorldDelta::between(&world, &world.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_delta_tracks_state_changes() {
+        let mut prev = World::new();
+        prev.set_state(Coord::new(0, 0), 1);
+        prev.set_state(Coord::new(1, 0), 2);
+
+        let mut next = World::new();
+        next.set_state(Coord::new(0, 0), 3);
+        next.set_state(Coord::new(1, 0), 2);
+        next.set_state(Coord::new(2, 0), 2);
+
+        let delta = WorldDelta::between(&prev, &next);
+        assert_eq!(delta.born, vec![(Coord::new(2, 0), 2)]);
+        assert_eq!(delta.changed, vec![(Coord::new(0, 0), 3)]);
+        assert!(delta.died.is_empty());
+
+        delta.apply_to(&mut prev);
+        assert_eq!(prev, next);
+    }
+
+    #[test]
+    fn test_delta_tracks_ants() {
+        use crate::world::{Ant, Heading};
+
+        let prev = World::new();
+        let mut next = World::new();
+        next.add_ant(Ant::new(Coord::new(1, 2), Heading::South));
+
+        let delta = WorldDelta::between(&prev, &next);
+        assert!(!delta.is_empty());
+        assert_eq!(delta.ants.as_deref(), Some(next.ants()));
+
+        let mut world = prev.clone();
+        delta.apply_to(&mut world);
+        assert_eq!(world, next);
+        assert!(WorldDelta::between(&next, &world).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use super::coord::Coord;
+use super::topology::Topology;
+use super::world::World;
+
+/// наибольшая площадь поля: шаг непрерывного правила проходит все его клетки
+pub const MAX_FIELD_AREA: u64 = 1 << 22;
+
+/// непрерывное поле: значение из `[0; 1]` в каждой клетке конечного
+/// прямоугольника, края склеены как у тора
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl Field {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![0.0; width as usize * height as usize],
+        }
+    }
+
+    /// поле по состояниям клеток конечного мира: состояние `s` даёт `s / 255`;
+    /// у бесконечной плоскости и мира больше `MAX_FIELD_AREA` поля нет
+    pub fn from_world(world: &World) -> Option<Self> {
+        let (width, height) = world.topology().size()?;
+        if u64::from(width) * u64::from(height) > MAX_FIELD_AREA {
+            return None;
+        }
+        let mut field = Self::new(width, height);
+        for (coord, state) in world.iter_cell_states() {
+            field.set(coord, f32::from(state) / 255.0);
+        }
+        Some(field)
+    }
+
+    /// мир с квантованными значениями поля в состояниях клеток
+    pub fn to_world(&self, topology: Topology) -> World {
+        let mut world = World::with_topology(topology);
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let coord = Coord::new(x, y);
+                let state = quantize(self.get(coord));
+                if state != 0 {
+                    world.set_state(coord, state);
+                }
+            }
+        }
+        world
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// значения по строкам сверху вниз
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn values_mut(&mut self) -> &mut [f32] {
+        &mut self.values
+    }
+
+    fn index(&self, coord: Coord) -> usize {
+        let x = coord.x.rem_euclid(self.width as i32) as usize;
+        let y = coord.y.rem_euclid(self.height as i32) as usize;
+        y * self.width as usize + x
+    }
+
+    pub fn get(&self, coord: Coord) -> f32 {
+        self.values[self.index(coord)]
+    }
+
+    pub fn set(&mut self, coord: Coord, value: f32) {
+        let index = self.index(coord);
+        self.values[index] = value.clamp(0.0, 1.0);
+    }
+
+    /// сумма значений - «масса» существа Lenia
+    pub fn mass(&self) -> f64 {
+        self.values.iter().map(|&v| f64::from(v)).sum()
+    }
+
+    /// квантованные значения прямоугольника `min..=max` по строкам
+    pub fn region(&self, min: Coord, max: Coord) -> Vec<u8> {
+        (min.y..=max.y)
+            .flat_map(|y| (min.x..=max.x).map(move |x| Coord::new(x, y)))
+            .map(|coord| quantize(self.get(coord)))
+            .collect()
+    }
+}
+
+/// значение из `[0; 1]` в байт `0..=255`
+pub fn quantize(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wraps_like_torus() {
+        let mut field = Field::new(4, 3);
+        field.set(Coord::new(-1, -1), 0.5);
+        assert_eq!(field.get(Coord::new(3, 2)), 0.5);
+        field.set(Coord::new(0, 0), 2.0);
+        assert_eq!(field.get(Coord::new(4, 3)), 1.0);
+    }
+
+    #[test]
+    fn test_world_round_trip() {
+        let topology = Topology::Torus {
+            width: 8,
+            height: 8,
+        };
+        let mut world = World::with_topology(topology);
+        world.set_state(Coord::new(1, 2), 255);
+        world.set_state(Coord::new(3, 4), 51);
+
+        let field = Field::from_world(&world).unwrap();
+        assert_eq!(field.get(Coord::new(1, 2)), 1.0);
+        assert!((field.get(Coord::new(3, 4)) - 0.2).abs() < 1e-6);
+        assert_eq!(field.to_world(topology), world);
+        assert!(Field::from_world(&World::new()).is_none());
+        let huge = World::with_topology(Topology::Torus {
+            width: 1 << 12,
+            height: 1 << 12,
+        });
+        assert!(Field::from_world(&huge).is_none());
+    }
+
+    #[test]
+    fn test_region_is_quantized() {
+        let mut field = Field::new(4, 4);
+        field.set(Coord::new(1, 1), 0.5);
+        field.set(Coord::new(2, 1), 0.001);
+        assert_eq!(
+            field.region(Coord::new(1, 1), Coord::new(2, 2)),
+            [128, 0, 0, 0]
+        );
+        assert_eq!(field.region(Coord::new(5, 5), Coord::new(5, 5)), [128]);
+    }
+}
@@ -1,7 +1,31 @@
+pub mod ant;
 pub mod chunk;
 pub mod coord;
+pub mod delta;
+pub mod field;
+pub mod neighborhood;
+pub mod random;
+pub mod soup;
+pub mod step;
+pub mod summed_area;
+pub mod topology;
+#[allow(clippy::module_inception)]
 pub mod world;
 
+pub use ant::{Ant, Heading};
 pub use chunk::{Chunk, CHUNK_SIZE};
 pub use coord::Coord;
+pub use delta::WorldDelta;
+pub use field::{quantize, Field, MAX_FIELD_AREA};
+pub use neighborhood::{Neighborhood, NeighborhoodError, MAX_NEIGHBORHOOD_RADIUS};
+pub use random::{random_seed, Rng};
+#[cfg(test)]
+pub(crate) use soup::soup_world;
+pub use soup::{Soup, SoupError, Symmetry, MAX_SOUP_AREA};
+pub use step::{
+    step_chunk, step_chunk_isotropic, step_isotropic, step_neighborhood, step_totalistic,
+    step_totalistic_parallel, ChunkHalo,
+};
+pub use summed_area::SummedArea;
+pub use topology::{Topology, TopologyError};
 pub use world::World;
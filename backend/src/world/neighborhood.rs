@@ -0,0 +1,195 @@
+use super::coord::Coord;
+use super::topology::Topology;
+use thiserror::Error;
+
+/// наибольший радиус окрестности
+pub const MAX_NEIGHBORHOOD_RADIUS: u32 = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NeighborhoodError {
+    #[error("neighbourhood radius {0} is out of range: must be from 1 to {max}", max = MAX_NEIGHBORHOOD_RADIUS)]
+    InvalidRadius(u32),
+    #[error("custom neighbourhood has no cells besides the centre")]
+    Empty,
+}
+
+/// какие клетки считаются соседями; сама клетка в окрестность не входит
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// квадрат `(2r+1) x (2r+1)`; радиус 1 - обычные 8 соседей
+    Moore(u32),
+    /// ромб: `|dx| + |dy| <= r`; радиус 1 - 4 ортогональных соседа
+    VonNeumann(u32),
+    /// шестиугольная сетка на квадратной, как в Golly: Мур без клеток
+    /// справа сверху и слева снизу
+    Hexagonal,
+    /// произвольный список смещений
+    Custom(Vec<Coord>),
+}
+
+impl Default for Neighborhood {
+    fn default() -> Self {
+        Self::Moore(1)
+    }
+}
+
+impl Neighborhood {
+    /// произвольная окрестность: центр и повторы отбрасываются
+    pub fn custom(offsets: impl IntoIterator<Item = Coord>) -> Result<Self, NeighborhoodError> {
+        let mut list: Vec<Coord> = Vec::new();
+        for offset in offsets {
+            if offset != Coord::new(0, 0) && !list.contains(&offset) {
+                list.push(offset);
+            }
+        }
+        let neighborhood = Self::Custom(list);
+        neighborhood.validate()?;
+        Ok(neighborhood)
+    }
+
+    pub fn validate(&self) -> Result<(), NeighborhoodError> {
+        match self {
+            Self::Custom(offsets) if offsets.is_empty() => Err(NeighborhoodError::Empty),
+            _ if !(1..=MAX_NEIGHBORHOOD_RADIUS).contains(&self.radius()) => {
+                Err(NeighborhoodError::InvalidRadius(self.radius()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// наибольшее смещение по любой оси
+    pub fn radius(&self) -> u32 {
+        match self {
+            Self::Moore(r) | Self::VonNeumann(r) => *r,
+            Self::Hexagonal => 1,
+            Self::Custom(offsets) => offsets
+                .iter()
+                .map(|o| o.x.unsigned_abs().max(o.y.unsigned_abs()))
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    /// смещения соседей относительно клетки
+    pub fn offsets(&self) -> Vec<Coord> {
+        let r = self.radius() as i32;
+        let square = (-r..=r).flat_map(move |dy| (-r..=r).map(move |dx| Coord::new(dx, dy)));
+        match self {
+            Self::Moore(_) => square.filter(|&o| o != Coord::new(0, 0)).collect(),
+            Self::VonNeumann(_) => square
+                .filter(|&o| o != Coord::new(0, 0) && o.x.abs() + o.y.abs() <= r)
+                .collect(),
+            // x == -y отсекает центр и пару диагоналей справа сверху и слева снизу
+            Self::Hexagonal => square.filter(|&o| o.x != -o.y).collect(),
+            Self::Custom(offsets) => offsets.clone(),
+        }
+    }
+
+    /// число соседей - наибольшее значение счётчика
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Moore(r) => ((2 * r + 1) * (2 * r + 1) - 1) as usize,
+            Self::VonNeumann(r) => (2 * r * (r + 1)) as usize,
+            Self::Hexagonal => 6,
+            Self::Custom(offsets) => offsets.len(),
+        }
+    }
+
+    /// обычные 8 соседей - для них есть побитовый шаг
+    pub fn is_moore(&self) -> bool {
+        *self == Self::Moore(1)
+    }
+
+    /// буква окрестности в строке Life-like правила: `H`, `V` или ничего для Мура
+    pub fn suffix(&self) -> Option<char> {
+        match self {
+            Self::Hexagonal => Some('H'),
+            Self::VonNeumann(1) => Some('V'),
+            _ => None,
+        }
+    }
+
+    pub fn from_suffix(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'H' => Some(Self::Hexagonal),
+            'V' => Some(Self::VonNeumann(1)),
+            'M' => Some(Self::Moore(1)),
+            _ => None,
+        }
+    }
+
+    /// соседи клетки с учётом топологии
+    pub fn around(&self, coord: Coord, topology: Topology) -> impl Iterator<Item = Coord> {
+        self.offsets()
+            .into_iter()
+            .filter_map(move |offset| topology.wrap(coord + offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sizes_match_offsets() {
+        let cases = [
+            Neighborhood::Moore(1),
+            Neighborhood::Moore(3),
+            Neighborhood::VonNeumann(1),
+            Neighborhood::VonNeumann(4),
+            Neighborhood::Hexagonal,
+        ];
+        for neighborhood in cases {
+            let offsets = neighborhood.offsets();
+            assert_eq!(offsets.len(), neighborhood.size(), "{:?}", neighborhood);
+            assert!(!offsets.contains(&Coord::new(0, 0)));
+        }
+        assert_eq!(Neighborhood::Moore(1).size(), 8);
+        assert_eq!(Neighborhood::VonNeumann(1).size(), 4);
+    }
+
+    #[test]
+    fn test_hexagonal_drops_one_diagonal() {
+        let offsets = Neighborhood::Hexagonal.offsets();
+        assert!(offsets.contains(&Coord::new(-1, -1)));
+        assert!(offsets.contains(&Coord::new(1, 1)));
+        assert!(!offsets.contains(&Coord::new(1, -1)));
+        assert!(!offsets.contains(&Coord::new(-1, 1)));
+    }
+
+    #[test]
+    fn test_custom_and_validation() {
+        let knight = Neighborhood::custom([
+            Coord::new(1, 2),
+            Coord::new(2, 1),
+            Coord::new(1, 2),
+            Coord::new(0, 0),
+        ])
+        .unwrap();
+        assert_eq!(knight.size(), 2);
+        assert_eq!(knight.radius(), 2);
+
+        assert_eq!(
+            Neighborhood::custom([Coord::new(0, 0)]),
+            Err(NeighborhoodError::Empty)
+        );
+        assert!(Neighborhood::Moore(0).validate().is_err());
+        assert!(Neighborhood::VonNeumann(MAX_NEIGHBORHOOD_RADIUS + 1)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_around_wraps() {
+        let torus = Topology::Torus {
+            width: 4,
+            height: 4,
+        };
+        let mut around: Vec<_> = Neighborhood::VonNeumann(1)
+            .around(Coord::new(0, 0), torus)
+            .map(|c| (c.x, c.y))
+            .collect();
+        around.sort();
+        assert_eq!(around, vec![(0, 1), (0, 3), (1, 0), (3, 0)]);
+    }
+}
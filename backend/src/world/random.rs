@@ -0,0 +1,81 @@
+use super::coord::Coord;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// генератор SplitMix64: быстрый, без зависимостей, и по одному зерну
+/// всегда выдаёт одну и ту же последовательность
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix(self.state)
+    }
+
+    /// равномерное число из `[0; 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        unit(self.next_u64())
+    }
+
+    /// случайное число, не зависящее от порядка обхода клеток: одно и то же
+    /// для пары (`key`, `coord`), поэтому клетки можно считать параллельно
+    pub fn cell_unit(key: u64, coord: Coord) -> f64 {
+        let position = (coord.x as u32 as u64) << 32 | coord.y as u32 as u64;
+        unit(mix(key ^ mix(position)))
+    }
+}
+
+/// зерно для запуска, которому его не задали; не длиннее 53 бит,
+/// чтобы без потерь пройти через число JSON во фронтенде
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    mix(nanos ^ u64::from(std::process::id())) >> 11
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// старшие 53 бита в число из `[0; 1)`
+fn unit(value: u64) -> f64 {
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+        let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first, (0..8).map(|_| c.next_u64()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_units_are_in_range_and_spread() {
+        let mut rng = Rng::new(7);
+        let values: Vec<f64> = (0..10_000).map(|_| rng.next_f64()).collect();
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((mean - 0.5).abs() < 0.02, "mean {}", mean);
+
+        let cell = Rng::cell_unit(5, Coord::new(-3, 8));
+        assert_eq!(cell, Rng::cell_unit(5, Coord::new(-3, 8)));
+        assert_ne!(cell, Rng::cell_unit(5, Coord::new(8, -3)));
+        assert_ne!(cell, Rng::cell_unit(6, Coord::new(-3, 8)));
+    }
+}
@@ -0,0 +1,306 @@
+use super::coord::Coord;
+use super::random::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// наибольшая площадь случайного супа
+pub const MAX_SOUP_AREA: u64 = 1 << 22;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SoupError {
+    #[error("soup size {width}x{height} is out of range: area must be from 1 to {max}")]
+    InvalidSize { width: u32, height: u32, max: u64 },
+    #[error("density {0} is outside of 0..1")]
+    InvalidDensity(f64),
+    #[error("symmetry {symmetry} needs a square area, got {width}x{height}")]
+    NotSquare {
+        symmetry: Symmetry,
+        width: u32,
+        height: u32,
+    },
+    #[error("soup at ({x}, {y}) of size {width}x{height} does not fit into the coordinate range")]
+    OutOfRange {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    #[error("unknown symmetry '{0}': expected C1, C2, C4, D2, D4 or D8")]
+    UnknownSymmetry(String),
+}
+
+/// симметрия супа в обозначениях apgsearch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Symmetry {
+    /// без симметрии
+    #[default]
+    C1,
+    /// поворот на 180°
+    C2,
+    /// повороты на 90°
+    C4,
+    /// отражение слева направо
+    D2,
+    /// отражения по обеим осям
+    D4,
+    /// все повороты и отражения квадрата
+    D8,
+}
+
+impl Symmetry {
+    /// нужен ли квадрат: повороты на 90° и диагонали меняют стороны местами
+    pub fn needs_square(&self) -> bool {
+        matches!(self, Self::C4 | Self::D8)
+    }
+
+    /// образы клетки `(x, y)` прямоугольника `width` x `height` под действием группы
+    fn images(&self, x: i32, y: i32, width: i32, height: i32) -> Vec<(i32, i32)> {
+        let (right, bottom) = (width - 1, height - 1);
+        let half_turn = (right - x, bottom - y);
+        let quarter_turns = [(bottom - y, x), (y, right - x)];
+        match self {
+            Self::C1 => vec![(x, y)],
+            Self::C2 => vec![(x, y), half_turn],
+            Self::C4 => vec![(x, y), half_turn, quarter_turns[0], quarter_turns[1]],
+            Self::D2 => vec![(x, y), (right - x, y)],
+            Self::D4 => vec![(x, y), (right - x, y), (x, bottom - y), half_turn],
+            Self::D8 => vec![
+                (x, y),
+                (right - x, y),
+                (x, bottom - y),
+                half_turn,
+                quarter_turns[0],
+                quarter_turns[1],
+                (y, x),
+                (bottom - y, right - x),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = SoupError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "C1" => Ok(Self::C1),
+            "C2" => Ok(Self::C2),
+            "C4" => Ok(Self::C4),
+            "D2" => Ok(Self::D2),
+            "D4" => Ok(Self::D4),
+            "D8" => Ok(Self::D8),
+            _ => Err(SoupError::UnknownSymmetry(s.to_string())),
+        }
+    }
+}
+
+/// случайный суп: прямоугольник с левым верхним углом `origin`, где каждая
+/// клетка жива с вероятностью `density`, а узор повторяет симметрию
+#[derive(Debug, Clone, PartialEq)]
+pub struct Soup {
+    origin: Coord,
+    width: u32,
+    height: u32,
+    density: f64,
+    symmetry: Symmetry,
+}
+
+impl Soup {
+    pub fn new(
+        origin: Coord,
+        width: u32,
+        height: u32,
+        density: f64,
+        symmetry: Symmetry,
+    ) -> Result<Self, SoupError> {
+        let area = u64::from(width) * u64::from(height);
+        if area == 0 || area > MAX_SOUP_AREA {
+            return Err(SoupError::InvalidSize {
+                width,
+                height,
+                max: MAX_SOUP_AREA,
+            });
+        }
+        // последняя клетка `origin + size - 1` должна оставаться в i32
+        let fits =
+            |start: i32, size: u32| i64::from(start) + i64::from(size) - 1 <= i64::from(i32::MAX);
+        if !fits(origin.x, width) || !fits(origin.y, height) {
+            return Err(SoupError::OutOfRange {
+                x: origin.x,
+                y: origin.y,
+                width,
+                height,
+            });
+        }
+        if !(0.0..=1.0).contains(&density) {
+            return Err(SoupError::InvalidDensity(density));
+        }
+        if symmetry.needs_square() && width != height {
+            return Err(SoupError::NotSquare {
+                symmetry,
+                width,
+                height,
+            });
+        }
+        Ok(Self {
+            origin,
+            width,
+            height,
+            density,
+            symmetry,
+        })
+    }
+
+    /// все клетки прямоугольника супа
+    pub fn area(&self) -> impl Iterator<Item = Coord> + '_ {
+        (0..self.height as i32).flat_map(move |y| {
+            (0..self.width as i32).map(move |x| Coord::new(self.origin.x + x, self.origin.y + y))
+        })
+    }
+
+    /// живые клетки супа для зерна `seed`: клетка решает по наименьшему из своих
+    /// образов, поэтому вся орбита группы симметрии живёт или умирает вместе
+    pub fn cells(&self, seed: u64) -> Vec<Coord> {
+        let (width, height) = (self.width as i32, self.height as i32);
+        self.area()
+            .filter(|&cell| {
+                let (x, y) = (cell.x - self.origin.x, cell.y - self.origin.y);
+                let (x, y) = self
+                    .symmetry
+                    .images(x, y, width, height)
+                    .into_iter()
+                    .min()
+                    .unwrap_or((x, y));
+                Rng::cell_unit(seed, Coord::new(x, y)) < self.density
+            })
+            .collect()
+    }
+}
+
+/// мир со случайным квадратным супом для тестов правил
+#[cfg(test)]
+pub(crate) fn soup_world(origin: Coord, side: u32, density: f64, seed: u64) -> super::World {
+    let mut world = super::World::new();
+    let soup = Soup::new(origin, side, side, density, Symmetry::C1).unwrap();
+    for cell in soup.cells(seed) {
+        world.set_cell(cell, true);
+    }
+    world
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn local_cells(soup: &Soup, seed: u64) -> HashSet<(i32, i32)> {
+        soup.cells(seed)
+            .into_iter()
+            .map(|c| (c.x - soup.origin.x, c.y - soup.origin.y))
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_soup() {
+        let soup = Soup::new(Coord::new(-8, 3), 16, 16, 0.5, Symmetry::C1).unwrap();
+        assert_eq!(soup.cells(1), soup.cells(1));
+        assert_ne!(soup.cells(1), soup.cells(2));
+        assert!(soup
+            .cells(1)
+            .iter()
+            .all(|c| (-8..8).contains(&c.x) && (3..19).contains(&c.y)));
+    }
+
+    #[test]
+    fn test_density() {
+        let soup = Soup::new(Coord::new(0, 0), 100, 100, 0.3, Symmetry::C1).unwrap();
+        let count = soup.cells(9).len();
+        assert!((2700..3300).contains(&count), "count {}", count);
+
+        let empty = Soup::new(Coord::new(0, 0), 10, 10, 0.0, Symmetry::C1).unwrap();
+        assert!(empty.cells(9).is_empty());
+        let full = Soup::new(Coord::new(0, 0), 10, 10, 1.0, Symmetry::D8).unwrap();
+        assert_eq!(full.cells(9).len(), 100);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let all = [
+            Symmetry::C1,
+            Symmetry::C2,
+            Symmetry::C4,
+            Symmetry::D2,
+            Symmetry::D4,
+            Symmetry::D8,
+        ];
+        for symmetry in all {
+            let soup = Soup::new(Coord::new(5, -5), 15, 15, 0.5, symmetry).unwrap();
+            let cells = local_cells(&soup, 3);
+            for &(x, y) in &cells {
+                for image in symmetry.images(x, y, 15, 15) {
+                    assert!(cells.contains(&image), "{} {:?}", symmetry, image);
+                }
+            }
+        }
+
+        // у несимметричного супа найдётся клетка без зеркальной пары
+        let plain = Soup::new(Coord::new(0, 0), 15, 15, 0.5, Symmetry::C1).unwrap();
+        let cells = local_cells(&plain, 3);
+        assert!(cells.iter().any(|&(x, y)| !cells.contains(&(14 - x, y))));
+
+        let wide = Soup::new(Coord::new(0, 0), 12, 5, 0.5, Symmetry::D4).unwrap();
+        let cells = local_cells(&wide, 4);
+        assert!(cells.iter().all(|&(x, y)| cells.contains(&(11 - x, 4 - y))));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            Soup::new(Coord::new(0, 0), 0, 5, 0.5, Symmetry::C1),
+            Err(SoupError::InvalidSize { .. })
+        ));
+        assert!(matches!(
+            Soup::new(Coord::new(0, 0), 1 << 12, 1 << 12, 0.5, Symmetry::C1),
+            Err(SoupError::InvalidSize { .. })
+        ));
+        assert_eq!(
+            Soup::new(Coord::new(0, 0), 5, 5, 1.5, Symmetry::C1),
+            Err(SoupError::InvalidDensity(1.5))
+        );
+        assert!(matches!(
+            Soup::new(Coord::new(0, 0), 6, 5, 0.5, Symmetry::C4),
+            Err(SoupError::NotSquare { .. })
+        ));
+        assert!(Soup::new(Coord::new(0, 0), 6, 5, 0.5, Symmetry::D4).is_ok());
+        assert!(matches!(
+            Soup::new(Coord::new(i32::MAX, 0), 2, 1, 0.5, Symmetry::C1),
+            Err(SoupError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            Soup::new(Coord::new(0, i32::MAX - 2), 4, 4, 0.5, Symmetry::C1),
+            Err(SoupError::OutOfRange { .. })
+        ));
+        let corner = Soup::new(Coord::new(i32::MAX - 1, i32::MAX), 2, 1, 1.0, Symmetry::C1);
+        assert_eq!(corner.unwrap().cells(1).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_symmetry() {
+        assert_eq!("d8".parse::<Symmetry>().unwrap(), Symmetry::D8);
+        assert_eq!(Symmetry::C4.to_string(), "C4");
+        assert!("D3".parse::<Symmetry>().is_err());
+        assert_eq!(
+            serde_json::from_str::<Symmetry>(r#""D2""#).unwrap(),
+            Symmetry::D2
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use super::chunk::{Chunk, CHUNK_SIZE};
+use super::coord::Coord;
+use super::neighborhood::Neighborhood;
+use super::summed_area::SummedArea;
+use super::world::World;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+const LAST: i32 = CHUNK_SIZE - 1;
+
+/// чанк вместе с восемью соседями - всё, что нужно для шага его клеток
+pub struct ChunkHalo<'a> {
+    chunks: [[Option<&'a Chunk>; 3]; 3],
+}
+
+impl<'a> ChunkHalo<'a> {
+    pub fn new(chunks: [[Option<&'a Chunk>; 3]; 3]) -> Self {
+        Self { chunks }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.iter().flatten().all(Option::is_none)
+    }
+
+    /// строка `y` (от -1 до 64) центрального чанка и строки соседей слева и справа
+    fn row(&self, y: i32) -> (u64, u64, u64) {
+        let (band, local_y) = match y {
+            -1 => (0, LAST),
+            CHUNK_SIZE => (2, 0),
+            _ => (1, y),
+        };
+        let row = |column: usize| {
+            self.chunks[band][column]
+                .map(|chunk| chunk.row(local_y))
+                .unwrap_or(0)
+        };
+        (row(0), row(1), row(2))
+    }
+}
+
+/// побитовый сумматор: счётчик соседей хранится в четырёх битовых плоскостях
+#[derive(Default)]
+struct Counter {
+    planes: [u64; 4],
+}
+
+impl Counter {
+    fn add(&mut self, bits: u64) {
+        let mut carry = bits;
+        for plane in &mut self.planes {
+            let next_carry = *plane & carry;
+            *plane ^= carry;
+            carry = next_carry;
+        }
+    }
+
+    /// маска клеток, у которых ровно `count` соседей
+    fn equals(&self, count: u8) -> u64 {
+        self.planes
+            .iter()
+            .enumerate()
+            .fold(!0, |mask, (bit, &plane)| {
+                if count >> bit & 1 == 1 {
+                    mask & plane
+                } else {
+                    mask & !plane
+                }
+            })
+    }
+}
+
+/// считает следующее поколение центрального чанка; `birth` и `survival` -
+/// битовые маски числа соседей (бит n - n соседей)
+pub fn step_chunk(halo: &ChunkHalo, birth: u16, survival: u16) -> Chunk {
+    let mut rows = [0u64; CHUNK_SIZE as usize];
+    let mut above = halo.row(-1);
+    let mut current = halo.row(0);
+
+    for (y, out) in rows.iter_mut().enumerate() {
+        let below = halo.row(y as i32 + 1);
+        let mut counter = Counter::default();
+
+        for (index, &(left, middle, right)) in [above, current, below].iter().enumerate() {
+            // сосед слева для бита x - клетка x-1, справа - x+1
+            counter.add(middle << 1 | left >> LAST);
+            counter.add(middle >> 1 | right << LAST);
+            if index != 1 {
+                counter.add(middle);
+            }
+        }
+
+        let alive = current.1;
+        let mut next = 0;
+        for count in 0..=8u8 {
+            let born = birth >> count & 1 == 1;
+            let survives = survival >> count & 1 == 1;
+            if !born && !survives {
+                continue;
+            }
+            let matching = counter.equals(count);
+            if born {
+                next |= matching & !alive;
+            }
+            if survives {
+                next |= matching & alive;
+            }
+        }
+        *out = next;
+
+        above = current;
+        current = below;
+    }
+
+    Chunk::from_rows(rows)
+}
+
+/// следующее поколение центрального чанка по таблицам конфигураций: бит `i`
+/// индекса - сосед N, NE, E, SE, S, SW, W, NW (по часовой стрелке с севера)
+pub fn step_chunk_isotropic(
+    halo: &ChunkHalo,
+    birth: &[bool; 256],
+    survival: &[bool; 256],
+) -> Chunk {
+    let west = |(left, middle, _): (u64, u64, u64)| middle << 1 | left >> LAST;
+    let east = |(_, middle, right): (u64, u64, u64)| middle >> 1 | right << LAST;
+
+    let mut rows = [0u64; CHUNK_SIZE as usize];
+    let mut above = halo.row(-1);
+    let mut current = halo.row(0);
+
+    for (y, out) in rows.iter_mut().enumerate() {
+        let below = halo.row(y as i32 + 1);
+        let neighbors = [
+            above.1,
+            east(above),
+            east(current),
+            east(below),
+            below.1,
+            west(below),
+            west(current),
+            west(above),
+        ];
+
+        let alive = current.1;
+        // мёртвая клетка без соседей не рождается: B0 запрещено
+        let mut candidates = neighbors.iter().fold(alive, |mask, bits| mask | bits);
+        let mut next = 0;
+        while candidates != 0 {
+            let x = candidates.trailing_zeros();
+            candidates &= candidates - 1;
+            let index = neighbors
+                .iter()
+                .enumerate()
+                .fold(0, |index, (bit, bits)| index | (bits >> x & 1) << bit);
+            let table = if alive >> x & 1 == 1 { survival } else { birth };
+            if table[index as usize] {
+                next |= 1 << x;
+            }
+        }
+        *out = next;
+
+        above = current;
+        current = below;
+    }
+
+    Chunk::from_rows(rows)
+}
+
+/// шаг тоталистического правила Moore-окрестности по всему миру
+pub fn step_totalistic(world: &World, birth: u16, survival: u16) -> World {
+    let source = world.with_ghost_cells(1);
+    let mut next = world.empty_like();
+    for key in candidate_chunks(&source, 1) {
+        let chunk = step_chunk(&source.halo(key), birth, survival);
+        next.insert_chunk(key, chunk);
+    }
+    next.clip_to_topology();
+    next
+}
+
+/// то же, что `step_totalistic`, но чанки считаются параллельно на пуле rayon
+pub fn step_totalistic_parallel(world: &World, birth: u16, survival: u16) -> World {
+    let source = world.with_ghost_cells(1);
+    let keys: Vec<(i32, i32)> = candidate_chunks(&source, 1).into_iter().collect();
+    let chunks: Vec<((i32, i32), Chunk)> = keys
+        .into_par_iter()
+        .map(|key| (key, step_chunk(&source.halo(key), birth, survival)))
+        .filter(|(_, chunk)| !chunk.is_empty())
+        .collect();
+
+    let mut next = world.empty_like();
+    for (key, chunk) in chunks {
+        next.insert_chunk(key, chunk);
+    }
+    next.clip_to_topology();
+    next
+}
+
+/// шаг изотропного нетоталистического правила по таблицам конфигураций соседей
+pub fn step_isotropic(world: &World, birth: &[bool; 256], survival: &[bool; 256]) -> World {
+    let source = world.with_ghost_cells(1);
+    let keys: Vec<(i32, i32)> = candidate_chunks(&source, 1).into_iter().collect();
+    let chunks: Vec<((i32, i32), Chunk)> = keys
+        .into_par_iter()
+        .map(|key| (key, step_chunk_isotropic(&source.halo(key), birth, survival)))
+        .filter(|(_, chunk)| !chunk.is_empty())
+        .collect();
+
+    let mut next = world.empty_like();
+    for (key, chunk) in chunks {
+        next.insert_chunk(key, chunk);
+    }
+    next.clip_to_topology();
+    next
+}
+
+/// шаг тоталистического правила с любой окрестностью: `next(alive, count)`
+/// решает судьбу клетки; соседи каждого чанка считаются по таблице сумм
+/// его окрестности, так что окно Мура любого радиуса стоит O(1) на клетку
+pub fn step_neighborhood<F>(world: &World, neighborhood: &Neighborhood, next: F) -> World
+where
+    F: Fn(bool, usize) -> bool + Sync,
+{
+    let radius = neighborhood.radius() as i32;
+    let source = world.with_ghost_cells(radius as u32);
+    let reach = (radius + CHUNK_SIZE - 1) / CHUNK_SIZE;
+    let offsets = neighborhood.offsets();
+
+    let keys: Vec<(i32, i32)> = candidate_chunks(&source, reach).into_iter().collect();
+    let chunks: Vec<((i32, i32), Chunk)> = keys
+        .into_par_iter()
+        .map(|(chunk_x, chunk_y)| {
+            let (min_x, min_y) = (chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+            let side = CHUNK_SIZE + 2 * radius;
+            let sums = SummedArea::new(&source, min_x - radius, min_y - radius, side, side);
+
+            let mut chunk = Chunk::new();
+            for y in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let coord = Coord::new(min_x + x, min_y + y);
+                    let count = window_count(&sums, neighborhood, &offsets, coord);
+                    if next(sums.get(coord), count) {
+                        chunk.set_cell(x, y, true);
+                    }
+                }
+            }
+            ((chunk_x, chunk_y), chunk)
+        })
+        .filter(|(_, chunk)| !chunk.is_empty())
+        .collect();
+
+    let mut result = world.empty_like();
+    for (key, chunk) in chunks {
+        result.insert_chunk(key, chunk);
+    }
+    result.clip_to_topology();
+    result
+}
+
+/// живые соседи клетки по таблице сумм
+fn window_count(
+    sums: &SummedArea,
+    neighborhood: &Neighborhood,
+    offsets: &[Coord],
+    coord: Coord,
+) -> usize {
+    let Coord { x, y } = coord;
+    let center = sums.get(coord) as u32;
+    let count = match *neighborhood {
+        Neighborhood::Moore(r) => {
+            let r = r as i32;
+            sums.sum(x - r, y - r, x + r, y + r) - center
+        }
+        Neighborhood::VonNeumann(r) => {
+            let r = r as i32;
+            let rows: u32 = (-r..=r)
+                .map(|dy| {
+                    let half = r - dy.abs();
+                    sums.sum(x - half, y + dy, x + half, y + dy)
+                })
+                .sum();
+            rows - center
+        }
+        _ => offsets.iter().filter(|&&o| sums.get(coord + o)).count() as u32,
+    };
+    count as usize
+}
+
+/// занятые чанки и чанки в `reach` от них - только в них могут появиться живые клетки
+pub fn candidate_chunks(world: &World, reach: i32) -> HashSet<(i32, i32)> {
+    let mut candidates = HashSet::new();
+    for (chunk_x, chunk_y) in world.chunk_keys() {
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                candidates.insert((chunk_x + dx, chunk_y + dy));
+            }
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{soup_world, Topology};
+
+    const B3: u16 = 1 << 3;
+    const S23: u16 = 1 << 2 | 1 << 3;
+
+    fn naive_step(world: &World, birth: u16, survival: u16) -> World {
+        let mut next = world.empty_like();
+        let mut candidates = HashSet::new();
+        for cell in world.iter_active_cells() {
+            candidates.insert(cell);
+            candidates.extend(cell.neighbors_in(world.topology()));
+        }
+        for coord in candidates {
+            let mask = if world.get_cell(coord) {
+                survival
+            } else {
+                birth
+            };
+            if mask >> world.count_neighbors(coord) & 1 == 1 {
+                next.set_cell(coord, true);
+            }
+        }
+        next
+    }
+
+    fn sorted_cells(world: &World) -> Vec<(i32, i32)> {
+        let mut cells: Vec<_> = world.iter_active_cells().map(|c| (c.x, c.y)).collect();
+        cells.sort();
+        cells
+    }
+
+    fn world_of(cells: &[(i32, i32)]) -> World {
+        let mut world = World::new();
+        for &(x, y) in cells {
+            world.set_cell(Coord::new(x, y), true);
+        }
+        world
+    }
+
+    #[test]
+    fn test_blinker_on_chunk_corner() {
+        // вертикальная мигалка на стыке четырёх чанков
+        let world = world_of(&[(-1, -1), (-1, 0), (-1, 1)]);
+        let next = step_totalistic(&world, B3, S23);
+
+        assert_eq!(sorted_cells(&next), vec![(-2, 0), (-1, 0), (0, 0)]);
+        assert_eq!(
+            sorted_cells(&step_totalistic(&next, B3, S23)),
+            sorted_cells(&world)
+        );
+    }
+
+    #[test]
+    fn test_empty_chunks_are_dropped() {
+        let world = world_of(&[(63, 63)]);
+        let next = step_totalistic(&world, B3, S23);
+        assert_eq!(next.chunk_count(), 0);
+    }
+
+    #[test]
+    fn test_glider_crosses_chunks_like_naive() {
+        let mut bitwise = world_of(&[(61, 60), (62, 61), (60, 62), (61, 62), (62, 62)]);
+        let mut naive = bitwise.clone();
+
+        for generation in 0..200 {
+            bitwise = step_totalistic(&bitwise, B3, S23);
+            naive = naive_step(&naive, B3, S23);
+            assert_eq!(
+                sorted_cells(&bitwise),
+                sorted_cells(&naive),
+                "generation {}",
+                generation
+            );
+        }
+        assert_eq!(bitwise.active_cell_count(), 5);
+    }
+
+    #[test]
+    fn test_soup_matches_naive_for_several_rules() {
+        let soup = soup_world(Coord::new(-70, -70), 140, 0.3, 0x2545_f491_4f6c_dd1d);
+
+        // Life, HighLife, Day & Night, Seeds
+        let rules = [
+            (B3, S23),
+            (1 << 3 | 1 << 6, S23),
+            (0b1_1100_1000, 0b1_1101_1000),
+            (1 << 2, 0),
+        ];
+        for (birth, survival) in rules {
+            let mut bitwise = soup.clone();
+            let mut naive = soup.clone();
+            for _ in 0..8 {
+                bitwise = step_totalistic(&bitwise, birth, survival);
+                naive = naive_step(&naive, birth, survival);
+            }
+            assert_eq!(sorted_cells(&bitwise), sorted_cells(&naive));
+        }
+    }
+
+    #[test]
+    fn test_parallel_matches_serial_on_large_soups() {
+        for seed in [1, 0x9e37_79b9_7f4a_7c15] {
+            let mut serial = soup_world(Coord::new(-256, -256), 512, 0.3, seed);
+            let mut parallel = serial.clone();
+            assert!(serial.chunk_count() >= 64);
+
+            for _ in 0..16 {
+                serial = step_totalistic(&serial, B3, S23);
+                parallel = step_totalistic_parallel(&parallel, B3, S23);
+                assert_eq!(parallel, serial);
+            }
+        }
+    }
+
+    #[test]
+    fn test_topologies_match_naive() {
+        let topologies = [
+            Topology::Bounded {
+                width: 70,
+                height: 50,
+            },
+            Topology::Torus {
+                width: 70,
+                height: 50,
+            },
+            Topology::Cylinder {
+                width: 70,
+                height: 50,
+            },
+            Topology::KleinBottle {
+                width: 70,
+                height: 50,
+            },
+            Topology::Torus {
+                width: 1,
+                height: 3,
+            },
+        ];
+        for topology in topologies {
+            let mut bitwise = soup_world(Coord::new(-80, -80), 160, 0.3, 7);
+            bitwise.set_topology(topology);
+            let mut naive = bitwise.clone();
+
+            for _ in 0..30 {
+                bitwise = step_totalistic(&bitwise, B3, S23);
+                naive = naive_step(&naive, B3, S23);
+                assert_eq!(bitwise, naive, "{:?}", topology);
+            }
+            assert_eq!(
+                step_totalistic_parallel(&naive, B3, S23),
+                step_totalistic(&naive, B3, S23)
+            );
+        }
+    }
+
+    #[test]
+    fn test_glider_wraps_around_torus() {
+        let topology = Topology::Torus {
+            width: 12,
+            height: 12,
+        };
+        let mut world = world_of(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        world.set_topology(topology);
+        let start = world.clone();
+
+        for _ in 0..4 * 12 {
+            world = step_totalistic(&world, B3, S23);
+        }
+        assert_eq!(world, start);
+    }
+}
@@ -0,0 +1,111 @@
+use super::chunk::CHUNK_SIZE;
+use super::coord::Coord;
+use super::world::World;
+
+/// таблица сумм по областям для прямоугольного окна мира: число живых клеток
+/// в любом прямоугольнике внутри окна считается за четыре обращения
+#[derive(Debug, Clone)]
+pub struct SummedArea {
+    min_x: i32,
+    min_y: i32,
+    width: i32,
+    height: i32,
+    /// `sums[y * (width + 1) + x]` - живые клетки в `[min_x; min_x + x) x [min_y; min_y + y)`
+    sums: Vec<u32>,
+}
+
+impl SummedArea {
+    /// таблица для окна `width x height` с левым верхним углом в `(min_x, min_y)`
+    pub fn new(world: &World, min_x: i32, min_y: i32, width: i32, height: i32) -> Self {
+        let stride = (width + 1) as usize;
+        let mut sums = vec![0u32; stride * (height + 1) as usize];
+
+        let chunk_xs = min_x.div_euclid(CHUNK_SIZE)..=(min_x + width - 1).div_euclid(CHUNK_SIZE);
+        let chunk_ys = min_y.div_euclid(CHUNK_SIZE)..=(min_y + height - 1).div_euclid(CHUNK_SIZE);
+        for chunk_y in chunk_ys {
+            for chunk_x in chunk_xs.clone() {
+                let Some(chunk) = world.chunk(chunk_x, chunk_y) else {
+                    continue;
+                };
+                for (x, y) in chunk.iter_active() {
+                    let wx = chunk_x * CHUNK_SIZE + x - min_x;
+                    let wy = chunk_y * CHUNK_SIZE + y - min_y;
+                    if (0..width).contains(&wx) && (0..height).contains(&wy) {
+                        sums[(wy + 1) as usize * stride + (wx + 1) as usize] = 1;
+                    }
+                }
+            }
+        }
+
+        for y in 1..=height as usize {
+            for x in 1..=width as usize {
+                sums[y * stride + x] += sums[(y - 1) * stride + x] + sums[y * stride + x - 1]
+                    - sums[(y - 1) * stride + x - 1];
+            }
+        }
+
+        Self {
+            min_x,
+            min_y,
+            width,
+            height,
+            sums,
+        }
+    }
+
+    /// живые клетки в прямоугольнике `[x1; x2] x [y1; y2]`; часть вне окна не считается
+    pub fn sum(&self, x1: i32, y1: i32, x2: i32, y2: i32) -> u32 {
+        let clamp_x = |x: i32| (x - self.min_x).clamp(0, self.width) as usize;
+        let clamp_y = |y: i32| (y - self.min_y).clamp(0, self.height) as usize;
+        let (left, right) = (clamp_x(x1), clamp_x(x2 + 1));
+        let (top, bottom) = (clamp_y(y1), clamp_y(y2 + 1));
+        if left >= right || top >= bottom {
+            return 0;
+        }
+
+        let stride = (self.width + 1) as usize;
+        self.sums[bottom * stride + right] + self.sums[top * stride + left]
+            - self.sums[top * stride + right]
+            - self.sums[bottom * stride + left]
+    }
+
+    pub fn get(&self, coord: Coord) -> bool {
+        self.sum(coord.x, coord.y, coord.x, coord.y) == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sums_match_direct_count() {
+        let mut world = World::new();
+        for &(x, y) in &[(0, 0), (63, 63), (64, 64), (-1, 5), (10, -3), (70, 2)] {
+            world.set_cell(Coord::new(x, y), true);
+        }
+
+        let sums = SummedArea::new(&world, -5, -5, 80, 80);
+        let direct = |x1: i32, y1: i32, x2: i32, y2: i32| {
+            world
+                .iter_active_cells()
+                .filter(|c| (x1..=x2).contains(&c.x) && (y1..=y2).contains(&c.y))
+                .filter(|c| (-5..75).contains(&c.x) && (-5..75).contains(&c.y))
+                .count() as u32
+        };
+
+        for &(x1, y1, x2, y2) in &[
+            (-5, -5, 74, 74),
+            (0, 0, 63, 63),
+            (-1, -3, 10, 5),
+            (60, 60, 100, 100),
+            (-50, -50, -6, -6),
+            (3, 3, 2, 2),
+        ] {
+            assert_eq!(sums.sum(x1, y1, x2, y2), direct(x1, y1, x2, y2));
+        }
+        assert!(sums.get(Coord::new(-1, 5)));
+        assert!(sums.get(Coord::new(70, 2)));
+        assert!(!sums.get(Coord::new(1, 1)));
+    }
+}
@@ -0,0 +1,278 @@
+use super::coord::Coord;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// наибольшая сторона конечного мира
+pub const MAX_TOPOLOGY_SIZE: u32 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TopologyError {
+    #[error("world size {width}x{height} is out of range: sides must be from 1 to {max}")]
+    InvalidSize { width: u32, height: u32, max: u32 },
+}
+
+/// форма мира; конечные миры занимают прямоугольник `[0; width) x [0; height)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Topology {
+    /// бесконечная плоскость
+    #[default]
+    Plane,
+    /// прямоугольник, за границей которого клетки всегда мертвы
+    Bounded { width: u32, height: u32 },
+    /// склеены обе пары сторон
+    Torus { width: u32, height: u32 },
+    /// склеены левая и правая стороны, сверху и снизу мёртвая граница
+    Cylinder { width: u32, height: u32 },
+    /// как тор, но при переходе через верх/низ отражается x
+    KleinBottle { width: u32, height: u32 },
+}
+
+impl Topology {
+    pub fn validate(&self) -> Result<(), TopologyError> {
+        match self.size() {
+            Some((width, height))
+                if !(1..=MAX_TOPOLOGY_SIZE).contains(&width)
+                    || !(1..=MAX_TOPOLOGY_SIZE).contains(&height) =>
+            {
+                Err(TopologyError::InvalidSize {
+                    width,
+                    height,
+                    max: MAX_TOPOLOGY_SIZE,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// ширина и высота конечного мира
+    pub fn size(&self) -> Option<(u32, u32)> {
+        match *self {
+            Self::Plane => None,
+            Self::Bounded { width, height }
+            | Self::Torus { width, height }
+            | Self::Cylinder { width, height }
+            | Self::KleinBottle { width, height } => Some((width, height)),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.size().is_some()
+    }
+
+    /// лежит ли клетка внутри прямоугольника конечного мира
+    pub fn contains(&self, coord: Coord) -> bool {
+        match self.size() {
+            None => true,
+            Some((width, height)) => {
+                (0..width as i32).contains(&coord.x) && (0..height as i32).contains(&coord.y)
+            }
+        }
+    }
+
+    /// переводит координату в каноническую клетку мира; `None` - клетка за мёртвой границей
+    pub fn wrap(&self, coord: Coord) -> Option<Coord> {
+        let wrap_axis = |value: i32, size: u32| value.rem_euclid(size as i32);
+        match *self {
+            Self::Plane => Some(coord),
+            Self::Bounded { .. } => self.contains(coord).then_some(coord),
+            Self::Torus { width, height } => Some(Coord::new(
+                wrap_axis(coord.x, width),
+                wrap_axis(coord.y, height),
+            )),
+            Self::Cylinder { width, height } => (0..height as i32)
+                .contains(&coord.y)
+                .then(|| Coord::new(wrap_axis(coord.x, width), coord.y)),
+            Self::KleinBottle { width, height } => {
+                let turns = coord.y.div_euclid(height as i32);
+                let x = if turns % 2 == 0 {
+                    coord.x
+                } else {
+                    width as i32 - 1 - coord.x
+                };
+                Some(Coord::new(wrap_axis(x, width), wrap_axis(coord.y, height)))
+            }
+        }
+    }
+
+    /// подходит ли квадрат `side` x `side` с углом `min` к краю прямоугольника
+    /// ближе чем на `width`; только такие клетки дают копии в полосу призраков
+    pub fn touches_ghost_band(&self, min: Coord, side: i32, width: u32) -> bool {
+        let Some((w, h)) = self.size() else {
+            return false;
+        };
+        let band = i64::from(width);
+        let near = |low: i32, size: u32| {
+            let (low, size) = (i64::from(low), i64::from(size));
+            low < band || low + i64::from(side) > size - band
+        };
+        near(min.x, w) || near(min.y, h)
+    }
+
+    /// клетки полосы шириной `width` вокруг прямоугольника, которые склейка
+    /// краёв отображает в клетку `coord`: в них кладутся её копии, чтобы шаг
+    /// на плоскости учитывал топологию
+    pub fn ghost_images(&self, coord: Coord, width: u32) -> Vec<Coord> {
+        let (w, h, wraps_y) = match *self {
+            Self::Plane | Self::Bounded { .. } => return Vec::new(),
+            Self::Torus { width, height } | Self::KleinBottle { width, height } => {
+                (width, height, true)
+            }
+            Self::Cylinder { width, height } => (width, height, false),
+        };
+        let (w, h, band) = (i64::from(w), i64::from(h), i64::from(width));
+        // узкий мир повторяется в полосе несколько раз
+        let repeats = |size: i64| (band + size - 1) / size;
+        let rows = if wraps_y { repeats(h) } else { 0 };
+
+        let mut images = Vec::new();
+        for turn in -rows..=rows {
+            let y = i64::from(coord.y) + turn * h;
+            if !(-band..h + band).contains(&y) {
+                continue;
+            }
+            let x = match self {
+                Self::KleinBottle { .. } if turn % 2 != 0 => w - 1 - i64::from(coord.x),
+                _ => i64::from(coord.x),
+            };
+            for column in -repeats(w)..=repeats(w) {
+                let x = x + column * w;
+                let inside = (0..w).contains(&x) && (0..h).contains(&y);
+                if (-band..w + band).contains(&x) && !inside {
+                    images.push(Coord::new(x as i32, y as i32));
+                }
+            }
+        }
+        images
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        let torus = Topology::Torus {
+            width: 4,
+            height: 3,
+        };
+        assert_eq!(torus.wrap(Coord::new(-1, -1)), Some(Coord::new(3, 2)));
+        assert_eq!(torus.wrap(Coord::new(4, 3)), Some(Coord::new(0, 0)));
+
+        let bounded = Topology::Bounded {
+            width: 4,
+            height: 3,
+        };
+        assert_eq!(bounded.wrap(Coord::new(3, 2)), Some(Coord::new(3, 2)));
+        assert_eq!(bounded.wrap(Coord::new(4, 0)), None);
+
+        let cylinder = Topology::Cylinder {
+            width: 4,
+            height: 3,
+        };
+        assert_eq!(cylinder.wrap(Coord::new(-1, 0)), Some(Coord::new(3, 0)));
+        assert_eq!(cylinder.wrap(Coord::new(0, -1)), None);
+
+        let klein = Topology::KleinBottle {
+            width: 4,
+            height: 3,
+        };
+        assert_eq!(klein.wrap(Coord::new(-1, 0)), Some(Coord::new(3, 0)));
+        assert_eq!(klein.wrap(Coord::new(1, -1)), Some(Coord::new(2, 2)));
+        assert_eq!(klein.wrap(Coord::new(0, 3)), Some(Coord::new(3, 0)));
+        assert_eq!(klein.wrap(Coord::new(0, 6)), Some(Coord::new(0, 0)));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Topology::Plane.validate().is_ok());
+        assert!(Topology::Torus {
+            width: 1,
+            height: 1
+        }
+        .validate()
+        .is_ok());
+        assert!(Topology::Bounded {
+            width: 0,
+            height: 10
+        }
+        .validate()
+        .is_err());
+        assert!(Topology::Cylinder {
+            width: 10,
+            height: MAX_TOPOLOGY_SIZE + 1
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_ghost_images_cover_the_ring() {
+        for topology in [
+            Topology::Torus {
+                width: 3,
+                height: 2,
+            },
+            Topology::Cylinder {
+                width: 3,
+                height: 2,
+            },
+            Topology::KleinBottle {
+                width: 3,
+                height: 2,
+            },
+        ] {
+            for band in [1, 3] {
+                let mut images: Vec<Coord> = (0..2)
+                    .flat_map(|y| (0..3).map(move |x| Coord::new(x, y)))
+                    .flat_map(|cell| {
+                        let images = topology.ghost_images(cell, band);
+                        assert!(images.iter().all(|&g| topology.wrap(g) == Some(cell)));
+                        images
+                    })
+                    .collect();
+                images.sort_by_key(|c| (c.y, c.x));
+
+                let band = band as i32;
+                let mut ring: Vec<Coord> = (-band..2 + band)
+                    .flat_map(|y| (-band..3 + band).map(move |x| Coord::new(x, y)))
+                    .filter(|&g| !topology.contains(g) && topology.wrap(g).is_some())
+                    .collect();
+                ring.sort_by_key(|c| (c.y, c.x));
+                assert_eq!(images, ring, "{:?} band {}", topology, band);
+            }
+        }
+        assert!(Topology::Plane.ghost_images(Coord::new(0, 0), 1).is_empty());
+    }
+
+    #[test]
+    fn test_touches_ghost_band() {
+        let torus = Topology::Torus {
+            width: 64,
+            height: 64,
+        };
+        assert!(torus.touches_ghost_band(Coord::new(0, 16), 8, 1));
+        assert!(torus.touches_ghost_band(Coord::new(56, 16), 8, 1));
+        assert!(!torus.touches_ghost_band(Coord::new(8, 16), 8, 1));
+        assert!(torus.touches_ghost_band(Coord::new(8, 16), 8, 9));
+        assert!(!Topology::Plane.touches_ghost_band(Coord::new(0, 0), 8, 1));
+    }
+
+    #[test]
+    fn test_serde_format() {
+        let topology: Topology =
+            serde_json::from_str(r#"{"type":"klein_bottle","width":8,"height":6}"#).unwrap();
+        assert_eq!(
+            topology,
+            Topology::KleinBottle {
+                width: 8,
+                height: 6
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&Topology::Plane).unwrap(),
+            r#"{"type":"plane"}"#
+        );
+    }
+}
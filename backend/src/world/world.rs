@@ -1,20 +1,75 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use super::ant::Ant;
 use super::chunk::{Chunk, CHUNK_SIZE};
 use super::coord::Coord;
+use super::neighborhood::Neighborhood;
+use super::step::ChunkHalo;
+use super::topology::Topology;
 
-/// мир - сетка из чанков
-#[derive(Debug, Clone)]
+/// мир - сетка из чанков и муравьи тьюрмитов поверх неё
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct World {
     chunks: HashMap<(i32, i32), Chunk>,
+    topology: Topology,
+    ants: Vec<Ant>,
 }
 
 impl World {
     pub fn new() -> Self {
+        Self::with_topology(Topology::Plane)
+    }
+
+    pub fn with_topology(topology: Topology) -> Self {
         Self {
             chunks: HashMap::new(),
+            topology,
+            ants: Vec::new(),
         }
     }
+
+    /// пустой мир той же топологии, без муравьёв
+    pub fn empty_like(&self) -> Self {
+        Self::with_topology(self.topology)
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// меняет топологию; клетки и муравьи за пределами нового мира удаляются
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+        self.clip_to_topology();
+        self.ants.retain(|ant| topology.contains(ant.position));
+    }
+
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    /// сажает муравья в клетку; за мёртвой границей муравей не появляется
+    pub fn add_ant(&mut self, ant: Ant) -> bool {
+        match self.topology.wrap(ant.position) {
+            Some(position) => {
+                self.ants.push(Ant { position, ..ant });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_ants(&mut self, ants: Vec<Ant>) {
+        self.ants.clear();
+        for ant in ants {
+            self.add_ant(ant);
+        }
+    }
+
     pub fn get_cell(&self, coord: Coord) -> bool {
+        let Some(coord) = self.topology.wrap(coord) else {
+            return false;
+        };
         let (chunk_x, chunk_y) = coord.chunk_coord(CHUNK_SIZE);
         let (local_x, local_y) = coord.local_coord(CHUNK_SIZE);
 
@@ -25,20 +80,39 @@ impl World {
     }
 
     pub fn set_cell(&mut self, coord: Coord, alive: bool) {
+        self.set_state(coord, alive as u8);
+    }
+
+    /// состояние клетки: 0 - мёртвая/фон, для Life-like правил живая клетка - 1
+    pub fn get_state(&self, coord: Coord) -> u8 {
+        let Some(coord) = self.topology.wrap(coord) else {
+            return 0;
+        };
         let (chunk_x, chunk_y) = coord.chunk_coord(CHUNK_SIZE);
         let (local_x, local_y) = coord.local_coord(CHUNK_SIZE);
 
-        if alive {
+        self.chunks
+            .get(&(chunk_x, chunk_y))
+            .map(|chunk| chunk.get_state(local_x, local_y))
+            .unwrap_or(0)
+    }
+
+    pub fn set_state(&mut self, coord: Coord, state: u8) {
+        let Some(coord) = self.topology.wrap(coord) else {
+            return;
+        };
+        let (chunk_x, chunk_y) = coord.chunk_coord(CHUNK_SIZE);
+        let (local_x, local_y) = coord.local_coord(CHUNK_SIZE);
+
+        if state != 0 {
             let chunk = self.chunks
                 .entry((chunk_x, chunk_y))
-                .or_insert_with(Chunk::new);
-            chunk.set_cell(local_x, local_y, true);
-        } else {
-            if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_y)) {
-                chunk.set_cell(local_x, local_y, false);
-                if chunk.is_empty() {
-                    self.chunks.remove(&(chunk_x, chunk_y));
-                }
+                .or_default();
+            chunk.set_state(local_x, local_y, state);
+        } else if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_y)) {
+            chunk.set_state(local_x, local_y, 0);
+            if chunk.is_empty() {
+                self.chunks.remove(&(chunk_x, chunk_y));
             }
         }
     }
@@ -47,6 +121,34 @@ impl World {
         self.chunks.len()
     }
 
+    pub fn chunk(&self, chunk_x: i32, chunk_y: i32) -> Option<&Chunk> {
+        self.chunks.get(&(chunk_x, chunk_y))
+    }
+
+    pub fn chunk_keys(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// кладёт готовый чанк целиком; пустые чанки не хранятся, топология
+    /// не проверяется - после вставки нужен `clip_to_topology`
+    pub fn insert_chunk(&mut self, key: (i32, i32), chunk: Chunk) {
+        if chunk.is_empty() {
+            self.chunks.remove(&key);
+        } else {
+            self.chunks.insert(key, chunk);
+        }
+    }
+
+    /// чанк `key` с соседями для побитового шага
+    pub fn halo(&self, (chunk_x, chunk_y): (i32, i32)) -> ChunkHalo<'_> {
+        let chunk = |dx: i32, dy: i32| self.chunks.get(&(chunk_x + dx, chunk_y + dy));
+        ChunkHalo::new([
+            [chunk(-1, -1), chunk(0, -1), chunk(1, -1)],
+            [chunk(-1, 0), chunk(0, 0), chunk(1, 0)],
+            [chunk(-1, 1), chunk(0, 1), chunk(1, 1)],
+        ])
+    }
+
     pub fn active_cell_count(&self) -> usize {
         self.chunks.values().map(|chunk| chunk.active_count()).sum()
     }
@@ -62,6 +164,37 @@ impl World {
         })
     }
 
+    /// двухсостоянийный мир той же топологии из клеток в состоянии `state`
+    pub fn cells_in_state(&self, state: u8) -> World {
+        let mut world = self.empty_like();
+        for (&key, chunk) in &self.chunks {
+            if !chunk.is_multi_state() {
+                if state == 1 {
+                    world.chunks.insert(key, chunk.clone());
+                }
+                continue;
+            }
+            let mut filtered = Chunk::new();
+            for (x, y, _) in chunk.iter_states().filter(|&(_, _, s)| s == state) {
+                filtered.set_cell(x, y, true);
+            }
+            world.insert_chunk(key, filtered);
+        }
+        world
+    }
+
+    /// живые клетки вместе с их состояниями
+    pub fn iter_cell_states(&self) -> impl Iterator<Item = (Coord, u8)> + '_ {
+        self.chunks.iter().flat_map(|((chunk_x, chunk_y), chunk)| {
+            let chunk_offset_x = chunk_x * CHUNK_SIZE;
+            let chunk_offset_y = chunk_y * CHUNK_SIZE;
+
+            chunk.iter_states().map(move |(local_x, local_y, state)| {
+                (Coord::new(chunk_offset_x + local_x, chunk_offset_y + local_y), state)
+            })
+        })
+    }
+
     pub fn get_bounds(&self) -> Option<(Coord, Coord)> {
         let mut active_cells: Vec<Coord> = self.iter_active_cells().collect();
 
@@ -82,13 +215,75 @@ impl World {
 
     pub fn clear(&mut self) {
         self.chunks.clear();
+        self.ants.clear();
     }
 
+    /// живые соседи по Муру
     pub fn count_neighbors(&self, coord: Coord) -> u8 {
-        coord.neighbors()
-            .iter()
-            .filter(|&&neighbor| self.get_cell(neighbor))
-            .count() as u8
+        self.count_neighbors_in(coord, &Neighborhood::default()) as u8
+    }
+
+    pub fn count_neighbors_in(&self, coord: Coord, neighborhood: &Neighborhood) -> usize {
+        neighborhood
+            .around(coord, self.topology)
+            .filter(|&neighbor| self.get_cell(neighbor))
+            .count()
+    }
+
+    /// копия на плоскости с полосой клеток-призраков шириной `width` вокруг
+    /// склеенных краёв; шаг такого мира с последующим `clip_to_topology`
+    /// учитывает топологию. Копируются только клетки чанков у краёв
+    pub fn with_ghost_cells(&self, width: u32) -> Cow<'_, World> {
+        if matches!(self.topology, Topology::Plane | Topology::Bounded { .. }) {
+            return Cow::Borrowed(self);
+        }
+
+        let mut padded = self.clone();
+        padded.topology = Topology::Plane;
+        for (&(chunk_x, chunk_y), chunk) in &self.chunks {
+            let min = Coord::new(chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+            if !self.topology.touches_ghost_band(min, CHUNK_SIZE, width) {
+                continue;
+            }
+            for (x, y, state) in chunk.iter_states() {
+                let cell = Coord::new(min.x + x, min.y + y);
+                for ghost in self.topology.ghost_images(cell, width) {
+                    padded.set_state(ghost, state);
+                }
+            }
+        }
+        Cow::Owned(padded)
+    }
+
+    /// удаляет клетки за пределами конечного мира
+    pub fn clip_to_topology(&mut self) {
+        let Some((width, height)) = self.topology.size() else {
+            return;
+        };
+        let (width, height) = (width as i32, height as i32);
+
+        self.chunks.retain(|&(chunk_x, chunk_y), chunk| {
+            let min_x = chunk_x * CHUNK_SIZE;
+            let min_y = chunk_y * CHUNK_SIZE;
+            let inside = min_x >= 0
+                && min_y >= 0
+                && min_x + CHUNK_SIZE <= width
+                && min_y + CHUNK_SIZE <= height;
+            if inside {
+                return true;
+            }
+
+            let outside: Vec<(i32, i32)> = chunk
+                .iter_active()
+                .filter(|&(x, y)| {
+                    !(0..width).contains(&(min_x + x)) || !(0..height).contains(&(min_y + y))
+                })
+                .collect();
+            for (x, y) in outside {
+                chunk.set_state(x, y, 0);
+            }
+            !chunk.is_empty()
+        });
     }
 }
 
@@ -101,6 +296,7 @@ impl Default for World {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::world::{soup_world, Heading};
 
     #[test]
     fn test_world_new() {
@@ -170,4 +366,108 @@ mod tests {
         assert_eq!(min, Coord::new(-50, -100));
         assert_eq!(max, Coord::new(100, 200));
     }
+
+    #[test]
+    fn test_bounded_world_ignores_outside_cells() {
+        let mut world = World::with_topology(Topology::Bounded {
+            width: 10,
+            height: 10,
+        });
+        world.set_cell(Coord::new(10, 0), true);
+        world.set_cell(Coord::new(-1, 5), true);
+        assert_eq!(world.active_cell_count(), 0);
+
+        world.set_cell(Coord::new(0, 0), true);
+        world.set_cell(Coord::new(1, 0), true);
+        assert_eq!(world.count_neighbors(Coord::new(0, 1)), 2);
+        assert_eq!(world.count_neighbors(Coord::new(9, 9)), 0);
+    }
+
+    #[test]
+    fn test_torus_counts_neighbors_across_edges() {
+        let mut world = World::with_topology(Topology::Torus {
+            width: 8,
+            height: 8,
+        });
+        world.set_cell(Coord::new(7, 7), true);
+        world.set_cell(Coord::new(7, 0), true);
+        world.set_cell(Coord::new(0, 7), true);
+
+        assert_eq!(world.count_neighbors(Coord::new(0, 0)), 3);
+        assert!(world.get_cell(Coord::new(-1, -1)));
+    }
+
+    #[test]
+    fn test_set_topology_clips_cells() {
+        let mut world = World::new();
+        world.set_cell(Coord::new(5, 5), true);
+        world.set_cell(Coord::new(100, 5), true);
+        world.set_cell(Coord::new(-3, 2), true);
+
+        world.set_topology(Topology::Torus {
+            width: 50,
+            height: 50,
+        });
+        let cells: Vec<Coord> = world.iter_active_cells().collect();
+        assert_eq!(cells, vec![Coord::new(5, 5)]);
+    }
+
+    #[test]
+    fn test_cell_states() {
+        let mut world = World::new();
+        world.set_state(Coord::new(3, 4), 2);
+        world.set_cell(Coord::new(-5, 0), true);
+
+        assert_eq!(world.get_state(Coord::new(3, 4)), 2);
+        assert!(world.get_cell(Coord::new(3, 4)));
+        assert_eq!(world.get_state(Coord::new(-5, 0)), 1);
+        assert_eq!(world.get_state(Coord::new(0, 0)), 0);
+
+        let mut states: Vec<_> = world.iter_cell_states().collect();
+        states.sort_by_key(|(c, _)| (c.x, c.y));
+        assert_eq!(states, vec![(Coord::new(-5, 0), 1), (Coord::new(3, 4), 2)]);
+
+        world.set_state(Coord::new(3, 4), 0);
+        world.set_state(Coord::new(-5, 0), 0);
+        assert_eq!(world.chunk_count(), 0);
+    }
+
+    #[test]
+    fn test_ants() {
+        let mut world = World::with_topology(Topology::Torus {
+            width: 10,
+            height: 10,
+        });
+        assert!(world.add_ant(Ant::new(Coord::new(-1, 12), Heading::East)));
+        assert_eq!(world.ants(), &[Ant::new(Coord::new(9, 2), Heading::East)]);
+
+        world.set_topology(Topology::Bounded {
+            width: 5,
+            height: 5,
+        });
+        assert!(world.ants().is_empty());
+        assert!(!world.add_ant(Ant::new(Coord::new(5, 0), Heading::North)));
+        assert!(world.add_ant(Ant::new(Coord::new(4, 0), Heading::North)));
+
+        world.clear();
+        assert!(world.ants().is_empty());
+    }
+
+    #[test]
+    fn test_ghost_cells_copy_wrapped_edges() {
+        let topology = Topology::KleinBottle {
+            width: 100,
+            height: 90,
+        };
+        let mut world = soup_world(Coord::new(0, 0), 100, 0.3, 11);
+        world.set_topology(topology);
+
+        let padded = world.with_ghost_cells(2);
+        for y in -2..92 {
+            for x in -2..102 {
+                let cell = Coord::new(x, y);
+                assert_eq!(padded.get_state(cell), world.get_state(cell), "{:?}", cell);
+            }
+        }
+    }
 }
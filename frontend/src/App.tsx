@@ -1,14 +1,23 @@
 import { useEffect, useRef, useState } from 'react';
   import { api } from './api';
-  import type { StatusResponse, PresetInfo, Cell } from './types';
+  import type { StatusResponse, PresetInfo, Cell, Ant } from './types';
   import './App.css';
 
+  // состояние 1 - основной цвет, следующие состояния постепенно тускнеют
+  function cellColor(state: number, stateCount: number) {
+    if (state <= 1 || stateCount <= 2) return '#a78bfa';
+    const fade = (state - 1) / (stateCount - 1);
+    return `hsl(${258 - fade * 60}, 90%, ${76 - fade * 46}%)`;
+  }
+
   function App() {
     const canvasRef = useRef<HTMLCanvasElement>(null);
     const [status, setStatus] = useState<StatusResponse | null>(null);
     const [presets, setPresets] = useState<PresetInfo[]>([]);
     const [cells, setCells] = useState<Cell[]>([]);
+    const [ants, setAnts] = useState<Ant[]>([]);
     const [loading, setLoading] = useState(true);
+    const stateCount = status?.state_count ?? 2;
 
     
     const [scale, setScale] = useState(20);
@@ -40,21 +49,41 @@ import { useEffect, useRef, useState } from 'react';
     useEffect(() => {
       const interval = setInterval(async () => {
         try {
-          const [statusData, cellsData] = await Promise.all([
-            api.getStatus(),
-            api.getAllCells(),
-          ]);
-          setStatus(statusData);
-          setCells(cellsData);
+          setStatus(await api.getStatus());
         } catch (error) {
           console.error('Failed to update:', error);
         }
-      }, 100);
+      }, 250);
 
       return () => clearInterval(interval);
     }, []);
 
     
+    useEffect(() => {
+      const live = new Map<string, Cell>();
+      const key = (cell: Cell) => `${cell.x},${cell.y}`;
+
+      const socket = api.connectStream((msg) => {
+        if (msg.type === 'snapshot') {
+          live.clear();
+          for (const cell of msg.cells) live.set(key(cell), cell);
+          setAnts(msg.ants);
+        } else if (msg.type === 'delta') {
+          for (const cell of msg.died) live.delete(key(cell));
+          for (const cell of msg.born) live.set(key(cell), cell);
+          for (const cell of msg.changed) live.set(key(cell), cell);
+          if (msg.ants) setAnts(msg.ants);
+        } else {
+          console.error('Stream error:', msg.message);
+          return;
+        }
+        setCells(Array.from(live.values()));
+      });
+
+      return () => socket.close();
+    }, []);
+
+    
     useEffect(() => {
       const canvas = canvasRef.current;
       if (!canvas) return;
@@ -96,7 +125,6 @@ import { useEffect, useRef, useState } from 'react';
       }
 
       
-      ctx.fillStyle = '#a78bfa';
       ctx.shadowColor = '#a78bfa';
       ctx.shadowBlur = 10;
 
@@ -105,12 +133,31 @@ import { useEffect, useRef, useState } from 'react';
         const y = cell.y * scale + offsetY;
 
         if (x > -scale && x < canvas.width && y > -scale && y < canvas.height) {
+          ctx.fillStyle = cellColor(cell.state, stateCount);
           ctx.fillRect(x + 1, y + 1, scale - 2, scale - 2);
         }
       }
 
       ctx.shadowBlur = 0;
-    }, [cells, scale, offsetX, offsetY]);
+
+      // муравей - красный треугольник, смотрящий по направлению движения
+      const angles = { north: 0, east: Math.PI / 2, south: Math.PI, west: -Math.PI / 2 };
+      ctx.fillStyle = '#f87171';
+      for (const ant of ants) {
+        const cx = (ant.x + 0.5) * scale + offsetX;
+        const cy = (ant.y + 0.5) * scale + offsetY;
+        ctx.save();
+        ctx.translate(cx, cy);
+        ctx.rotate(angles[ant.heading]);
+        ctx.beginPath();
+        ctx.moveTo(0, -scale * 0.4);
+        ctx.lineTo(scale * 0.3, scale * 0.35);
+        ctx.lineTo(-scale * 0.3, scale * 0.35);
+        ctx.closePath();
+        ctx.fill();
+        ctx.restore();
+      }
+    }, [cells, ants, scale, offsetX, offsetY, stateCount]);
 
     
     const handleMouseDown = (e: React.MouseEvent) => {
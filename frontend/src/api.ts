@@ -1,4 +1,4 @@
-import type { StatusResponse, PresetsListResponse, Cell } from './types';
+import type { StatusResponse, PresetsListResponse, Cell, RuleResponse, RuleTableResponse, ServerMessage, Viewport, DiffResponse, Topology, TopologyResponse, Ant, AntsResponse, Heading, RandomSoupRequest, RandomSoupResponse, FieldRegionResponse } from './types';
 
 const API_BASE = '/api';
 
@@ -29,6 +29,20 @@ export const api = {
     return res.json() as Promise<StatusResponse>;
   },
 
+  async stepBack() {
+    const res = await fetch(`${API_BASE}/control/step_back`, { method: 'POST' });
+    return res.json() as Promise<StatusResponse>;
+  },
+
+  async jump(generations: number) {
+    const res = await fetch(`${API_BASE}/control/jump`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ generations }),
+    });
+    return res.json() as Promise<StatusResponse>;
+  },
+
   async setSpeed(tps: number) {
     const res = await fetch(`${API_BASE}/control/speed`, {
       method: 'POST',
@@ -38,6 +52,38 @@ export const api = {
     return res.json() as Promise<StatusResponse>;
   },
 
+  async getRule() {
+    const res = await fetch(`${API_BASE}/control/rule`);
+    return res.json() as Promise<RuleResponse>;
+  },
+
+  async setRule(rule: string, engine?: 'default' | 'hashlife') {
+    const res = await fetch(`${API_BASE}/control/rule`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ rule, engine }),
+    });
+    return res.json() as Promise<StatusResponse>;
+  },
+
+  async setSeed(seed?: number) {
+    const res = await fetch(`${API_BASE}/control/seed`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ seed }),
+    });
+    return res.json() as Promise<StatusResponse>;
+  },
+
+  async loadRuleTable(table: string) {
+    const res = await fetch(`${API_BASE}/control/rule/table`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ table }),
+    });
+    return res.json() as Promise<RuleTableResponse>;
+  },
+
   async getStatus() {
     const res = await fetch(`${API_BASE}/control/status`);
     return res.json() as Promise<StatusResponse>;
@@ -49,16 +95,55 @@ export const api = {
     return res.json() as Promise<Cell[]>;
   },
 
+  async getDiff(since: number) {
+    const res = await fetch(`${API_BASE}/world/diff?since=${since}`);
+    return res.json() as Promise<DiffResponse>;
+  },
+
   async getPresets() {
     const res = await fetch(`${API_BASE}/world/presets`);
     return res.json() as Promise<PresetsListResponse>;
   },
 
-  async loadPreset(name: string, offsetX = 0, offsetY = 0) {
+  async loadPreset(name: string, offsetX = 0, offsetY = 0, topology?: Topology) {
     const res = await fetch(`${API_BASE}/world/preset`, {
       method: 'POST',
       headers: { 'Content-Type': 'application/json' },
-      body: JSON.stringify({ name, offset_x: offsetX, offset_y: offsetY }),
+      body: JSON.stringify({ name, offset_x: offsetX, offset_y: offsetY, topology }),
+    });
+    return res.json();
+  },
+
+  async getTopology(): Promise<TopologyResponse> {
+    const res = await fetch(`${API_BASE}/world/topology`);
+    return res.json();
+  },
+
+  async setTopology(topology: Topology) {
+    const res = await fetch(`${API_BASE}/world/topology`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ topology }),
+    });
+    return res.json();
+  },
+
+  async getField({ x1, y1, x2, y2 }: Viewport): Promise<FieldRegionResponse> {
+    const res = await fetch(`${API_BASE}/world/field?x1=${x1}&y1=${y1}&x2=${x2}&y2=${y2}`);
+    return res.json();
+  },
+
+  async getAnts(): Promise<AntsResponse> {
+    const res = await fetch(`${API_BASE}/world/ants`);
+    return res.json();
+  },
+
+  async addAnt(x: number, y: number, heading: Heading = 'north'): Promise<AntsResponse> {
+    const ant: Ant = { x, y, heading };
+    const res = await fetch(`${API_BASE}/world/ants`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(ant),
     });
     return res.json();
   },
@@ -72,8 +157,44 @@ export const api = {
     return res.json();
   },
 
+  async randomSoup(request: RandomSoupRequest) {
+    const res = await fetch(`${API_BASE}/world/random`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(request),
+    });
+    return res.json() as Promise<RandomSoupResponse>;
+  },
+
+  async importPattern(pattern: string, offsetX = 0, offsetY = 0) {
+    const res = await fetch(`${API_BASE}/world/import`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ pattern, offset_x: offsetX, offset_y: offsetY }),
+    });
+    return res.json();
+  },
+
+  async exportPattern(format = 'rle') {
+    const res = await fetch(`${API_BASE}/world/export?format=${format}`);
+    return res.text();
+  },
+
   async clearWorld() {
     const res = await fetch(`${API_BASE}/world/clear`, { method: 'POST' });
     return res.json();
   },
+
+  // Live updates
+  connectStream(onMessage: (msg: ServerMessage) => void, viewport?: Viewport) {
+    const protocol = window.location.protocol === 'https:' ? 'wss' : 'ws';
+    const socket = new WebSocket(`${protocol}://${window.location.host}${API_BASE}/ws`);
+    socket.onopen = () => {
+      if (viewport) {
+        socket.send(JSON.stringify({ type: 'subscribe', ...viewport }));
+      }
+    };
+    socket.onmessage = (event) => onMessage(JSON.parse(event.data) as ServerMessage);
+    return socket;
+  },
 };
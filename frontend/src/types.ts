@@ -1,6 +1,19 @@
 export interface Cell {
   x: number;
   y: number;
+  state: number;
+}
+
+export type Heading = 'north' | 'east' | 'south' | 'west';
+
+export interface Ant {
+  x: number;
+  y: number;
+  heading: Heading;
+}
+
+export interface AntsResponse {
+  ants: Ant[];
 }
 
 export interface StatusResponse {
@@ -8,14 +21,89 @@ export interface StatusResponse {
   tick_count: number;
   tps: number;
   active_cells: number;
+  rule: string;
+  state_count: number;
+  seed: number;
+  reversible: boolean;
+}
+
+export interface RuleResponse {
+  rule: string;
+  state_count: number;
+}
+
+export interface RuleTableResponse {
+  rule: string;
+  description: string;
+  state_count: number;
+  transitions: number;
+  metadata: Record<string, string>;
+}
+
+export type Topology =
+  | { type: 'plane' }
+  | { type: 'bounded' | 'torus' | 'cylinder' | 'klein_bottle'; width: number; height: number };
+
+export interface TopologyResponse {
+  topology: Topology;
 }
 
 export interface PresetInfo {
   name: string;
   description: string;
   cell_count: number;
+  topology: Topology | null;
+  rule: string | null;
 }
 
 export interface PresetsListResponse {
   presets: PresetInfo[];
 }
+
+export interface DiffResponse {
+  tick: number;
+  full_snapshot: boolean;
+  cells: Cell[];
+  born: Cell[];
+  died: Cell[];
+  changed: Cell[];
+  ants?: Ant[];
+}
+
+export type Symmetry = 'C1' | 'C2' | 'C4' | 'D2' | 'D4' | 'D8';
+
+export interface RandomSoupRequest {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  density: number;
+  seed?: number;
+  symmetry?: Symmetry;
+}
+
+export interface RandomSoupResponse {
+  seed: number;
+  symmetry: Symmetry;
+  soup_cells: number;
+  active_cells: number;
+}
+
+export interface FieldRegionResponse {
+  bounds: Viewport;
+  width: number;
+  height: number;
+  values: number[];
+}
+
+export interface Viewport {
+  x1: number;
+  y1: number;
+  x2: number;
+  y2: number;
+}
+
+export type ServerMessage =
+  | { type: 'snapshot'; tick: number; cells: Cell[]; ants: Ant[] }
+  | { type: 'delta'; tick: number; born: Cell[]; died: Cell[]; changed: Cell[]; ants?: Ant[] }
+  | { type: 'error'; message: string };
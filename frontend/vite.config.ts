@@ -5,6 +5,7 @@ export default defineConfig({
   plugins: [react()],
   server: {
     proxy: {
+      '/api/ws': { target: 'ws://127.0.0.1:3000', ws: true },
       '/api': 'http://127.0.0.1:3000',
     },
   },